@@ -0,0 +1,316 @@
+//! Privileged helper, run as root through pkexec when Unbind is not allowed
+//! to signal a process itself. It does exactly one thing:
+//!
+//!     unbind-helper signal <pid> <SIGTERM|SIGKILL> <start-time>
+//!
+//! and re-validates the target before acting, since everything on the command
+//! line comes from an unprivileged caller. That includes the app's built-in
+//! protection (scanner/builtin.rs, shared by path), applied to the name and
+//! executable the helper reads itself. Deliberately std + libc only so the
+//! binary that runs as root stays small.
+//!
+//! Exit codes are read by `scanner::elevate` and must stay in sync with it.
+
+// Only main() exists off Linux, reporting that the helper is unsupported
+#![cfg_attr(not(target_os = "linux"), allow(dead_code))]
+
+#[path = "../scanner/builtin.rs"]
+mod builtin;
+
+use std::process::ExitCode;
+
+const EXIT_USAGE: u8 = 2;
+const EXIT_NOT_FOUND: u8 = 3;
+const EXIT_CHANGED: u8 = 4;
+const EXIT_REFUSED: u8 = 5;
+const EXIT_FAILED: u8 = 6;
+
+/// One validated request
+#[derive(Debug, PartialEq, Eq)]
+struct Request {
+    pid: u32,
+    signal: &'static str,
+    start_time: u64,
+}
+
+/// Signals the helper will deliver: enough to free a port, nothing that could
+/// leave a root process stopped or reloaded
+const ALLOWED_SIGNALS: &[&str] = &["SIGTERM", "SIGKILL"];
+
+fn parse_args(args: &[String]) -> Result<Request, String> {
+    let [command, pid, signal, start_time] = args else {
+        return Err("usage: unbind-helper signal <pid> <SIGTERM|SIGKILL> <start-time>".into());
+    };
+    if command != "signal" {
+        return Err(format!("unknown command: {}", command));
+    }
+    let pid: u32 = pid.parse().map_err(|_| format!("invalid pid: {}", pid))?;
+    let signal = ALLOWED_SIGNALS
+        .iter()
+        .copied()
+        .find(|allowed| allowed == signal)
+        .ok_or_else(|| format!("signal not allowed: {}", signal))?;
+    let start_time: u64 = start_time
+        .parse()
+        .map_err(|_| format!("invalid start time: {}", start_time))?;
+    Ok(Request {
+        pid,
+        signal,
+        start_time,
+    })
+}
+
+/// The parts of /proc/<pid>/stat the checks need
+#[derive(Debug, PartialEq, Eq)]
+struct Stat {
+    comm: String,
+    flags: u64,
+    start_time: u64,
+}
+
+/// Parse /proc/<pid>/stat. comm may contain spaces and parentheses, so the
+/// fixed fields start after the last ')'.
+fn parse_stat(content: &str) -> Option<Stat> {
+    let open = content.find('(')?;
+    let close = content.rfind(')')?;
+    let comm = content.get(open + 1..close)?.to_string();
+    let fields: Vec<&str> = content[close + 1..].split_whitespace().collect();
+    // fields[0] is state (field 3 of the file), flags is field 9, starttime 22
+    Some(Stat {
+        comm,
+        flags: fields.get(6)?.parse().ok()?,
+        start_time: fields.get(19)?.parse().ok()?,
+    })
+}
+
+/// Why the request must be refused even though the caller authenticated.
+/// `exe` is the target's executable as read by the helper, if readable.
+fn refusal(request: &Request, stat: &Stat, exe: Option<&str>) -> Option<String> {
+    const PF_KTHREAD: u64 = 0x0020_0000;
+    if request.pid <= 1 {
+        return Some("refusing to signal init or a process group".to_string());
+    }
+    let names = builtin::candidate_names(&stat.comm, exe);
+    builtin::pid_reason(request.pid, std::process::id(), builtin::session_leader())
+        .or_else(|| builtin::process_reason(stat.flags & PF_KTHREAD != 0, &names))
+        .map(|reason| format!("refusing to signal {}: {}", stat.comm, reason))
+}
+
+#[cfg(target_os = "linux")]
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let request = match parse_args(&args) {
+        Ok(request) => request,
+        Err(message) => return fail(EXIT_USAGE, &message),
+    };
+    match run(&request) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err((code, message)) => fail(code, &message),
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn main() -> ExitCode {
+    fail(EXIT_FAILED, "unbind-helper is only supported on Linux")
+}
+
+fn fail(code: u8, message: &str) -> ExitCode {
+    eprintln!("unbind-helper: {}", message);
+    ExitCode::from(code)
+}
+
+/// Pin the process with a pidfd first, then check its identity, then signal
+/// through the pidfd: a pid recycled after the check can't receive the signal
+#[cfg(target_os = "linux")]
+fn run(request: &Request) -> Result<(), (u8, String)> {
+    let pid = libc::pid_t::try_from(request.pid)
+        .map_err(|_| (EXIT_USAGE, format!("invalid pid: {}", request.pid)))?;
+    let not_found = || (EXIT_NOT_FOUND, format!("no such process: {}", request.pid));
+
+    let pidfd = match PidFd::open(pid) {
+        Ok(fd) => Some(fd),
+        Err(e) if e.raw_os_error() == Some(libc::ESRCH) => return Err(not_found()),
+        // Kernels before 5.3 have no pidfds; fall back to check-then-kill
+        Err(_) => None,
+    };
+
+    let content =
+        std::fs::read_to_string(format!("/proc/{}/stat", request.pid)).map_err(|_| not_found())?;
+    let stat = parse_stat(&content).ok_or_else(|| {
+        (
+            EXIT_FAILED,
+            format!("unreadable /proc/{}/stat", request.pid),
+        )
+    })?;
+    let exe = std::fs::read_link(format!("/proc/{}/exe", request.pid)).ok();
+    let exe = exe.as_deref().and_then(|path| path.to_str());
+    if let Some(reason) = refusal(request, &stat, exe) {
+        return Err((EXIT_REFUSED, reason));
+    }
+    if stat.start_time != request.start_time {
+        return Err((
+            EXIT_CHANGED,
+            format!("process {} is not the one that was scanned", request.pid),
+        ));
+    }
+
+    let signal = match request.signal {
+        "SIGKILL" => libc::SIGKILL,
+        _ => libc::SIGTERM,
+    };
+    let result = match &pidfd {
+        Some(fd) => fd.send(signal),
+        None => {
+            // SAFETY: pid is a single positive pid (refusal() rejected <= 1)
+            if unsafe { libc::kill(pid, signal) } == 0 {
+                Ok(())
+            } else {
+                Err(std::io::Error::last_os_error())
+            }
+        }
+    };
+    result.map_err(|e| match e.raw_os_error() {
+        Some(libc::ESRCH) => not_found(),
+        _ => (EXIT_FAILED, format!("kill failed: {}", e)),
+    })
+}
+
+/// A process file descriptor, closed on drop
+#[cfg(target_os = "linux")]
+struct PidFd(libc::c_int);
+
+#[cfg(target_os = "linux")]
+impl PidFd {
+    fn open(pid: libc::pid_t) -> std::io::Result<Self> {
+        // SAFETY: pidfd_open takes a pid and flags and returns a new descriptor
+        let fd = unsafe { libc::syscall(libc::SYS_pidfd_open, pid, 0) };
+        if fd < 0 {
+            Err(std::io::Error::last_os_error())
+        } else {
+            Ok(PidFd(fd as libc::c_int))
+        }
+    }
+
+    fn send(&self, signal: libc::c_int) -> std::io::Result<()> {
+        // SAFETY: self.0 is an open pidfd; a null siginfo is allowed
+        let ret = unsafe {
+            libc::syscall(
+                libc::SYS_pidfd_send_signal,
+                self.0,
+                signal,
+                std::ptr::null::<libc::siginfo_t>(),
+                0,
+            )
+        };
+        if ret == 0 {
+            Ok(())
+        } else {
+            Err(std::io::Error::last_os_error())
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl Drop for PidFd {
+    fn drop(&mut self) {
+        // SAFETY: closing the descriptor this value owns, exactly once
+        unsafe { libc::close(self.0) };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_args() {
+        assert_eq!(
+            parse_args(&args(&["signal", "1234", "SIGTERM", "98765"])),
+            Ok(Request {
+                pid: 1234,
+                signal: "SIGTERM",
+                start_time: 98765,
+            })
+        );
+        assert!(parse_args(&args(&["signal", "1234", "SIGSTOP", "1"])).is_err());
+        assert!(parse_args(&args(&["signal", "-1", "SIGKILL", "1"])).is_err());
+        assert!(parse_args(&args(&["signal", "1234", "SIGKILL"])).is_err());
+        assert!(parse_args(&args(&["exec", "1234", "SIGKILL", "1"])).is_err());
+        assert!(parse_args(&args(&["signal", "1234", "SIGKILL", "1", "extra"])).is_err());
+    }
+
+    #[test]
+    fn test_parse_stat() {
+        let line = "4242 (my (odd) srv) S 1 4242 4242 0 -1 4194560 150 0 0 0 \
+                    12 3 0 0 20 0 1 0 5551234 1000000 200 18446744073709551615";
+        assert_eq!(
+            parse_stat(line),
+            Some(Stat {
+                comm: "my (odd) srv".to_string(),
+                flags: 4194560,
+                start_time: 5551234,
+            })
+        );
+        assert_eq!(parse_stat("4242 (truncated) S 1"), None);
+    }
+
+    fn stat(comm: &str, flags: u64) -> Stat {
+        Stat {
+            comm: comm.to_string(),
+            flags,
+            start_time: 1,
+        }
+    }
+
+    #[test]
+    fn test_refusal() {
+        let user = stat("node", 0);
+        let request = |pid| Request {
+            pid,
+            signal: "SIGTERM",
+            start_time: 1,
+        };
+        assert!(refusal(&request(0), &user, None).is_some());
+        assert!(refusal(&request(1), &user, None).is_some());
+        assert!(refusal(&request(std::process::id()), &user, None).is_some());
+        assert!(refusal(&request(4242), &user, Some("/usr/bin/node")).is_none());
+
+        let kthread = stat("kworker/0:1", 0x0020_0040);
+        assert!(refusal(&request(4242), &kthread, None).is_some());
+    }
+
+    #[test]
+    fn test_refusal_applies_builtin_names() {
+        let request = Request {
+            pid: 4242,
+            signal: "SIGKILL",
+            start_time: 1,
+        };
+        let sshd = stat("sshd", 0);
+        assert!(refusal(&request, &sshd, Some("/usr/sbin/sshd")).is_some());
+
+        // comm is truncated and can be renamed; the executable still counts
+        let renamed = stat("worker", 0);
+        assert!(refusal(&request, &renamed, Some("/usr/bin/dbus-daemon")).is_some());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_refusal_protects_the_callers_session() {
+        let Some(leader) = builtin::session_leader() else {
+            return;
+        };
+        let request = Request {
+            pid: leader,
+            signal: "SIGTERM",
+            start_time: 1,
+        };
+        if leader != std::process::id() {
+            assert!(refusal(&request, &stat("bash", 0), Some("/usr/bin/bash")).is_some());
+        }
+    }
+}
@@ -1,4 +1,10 @@
-use crate::scanner::{self, PortInfo};
+use crate::scanner::{
+    self, BackendInfo, BatchKillOutcome, ContainerInfo, FreePortReport, KillOutcome, KillReport,
+    KillStrategy, KillTarget, PortInfo, ProcessDetails, ProcessIdentity, ProcessTree,
+    ProtectionPolicy, ProtectionRule, ScanDiagnostics, ScanError, ScanErrorKind, ScannerSettings,
+    ServiceHint, Signal, SystemdUnit, TreeKillMode,
+};
+use crate::watcher;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -6,6 +12,8 @@ pub struct CommandResponse<T> {
     pub success: bool,
     pub data: Option<T>,
     pub error: Option<String>,
+    /// Structured form of `error`: kind code, errno/exit status, pid/port
+    pub error_details: Option<ScanError>,
 }
 
 impl<T> CommandResponse<T> {
@@ -14,44 +22,376 @@ impl<T> CommandResponse<T> {
             success: true,
             data: Some(data),
             error: None,
+            error_details: None,
         }
     }
 
-    pub fn err(message: String) -> Self {
+    pub fn err(error: ScanError) -> Self {
         CommandResponse {
             success: false,
             data: None,
-            error: Some(message),
+            error: Some(error.message.clone()),
+            error_details: Some(error),
         }
     }
 }
 
-/// Scan all listening ports
+/// Run a command's work on the blocking pool so a slow scan or kill doesn't
+/// hold up the IPC thread
+async fn blocking<T, F>(work: F) -> CommandResponse<T>
+where
+    F: FnOnce() -> CommandResponse<T> + Send + 'static,
+    T: Send + 'static,
+{
+    match tauri::async_runtime::spawn_blocking(work).await {
+        Ok(response) => response,
+        Err(e) => CommandResponse::err(ScanError::new(
+            ScanErrorKind::Other,
+            format!("The command's worker thread failed: {}", e),
+        )),
+    }
+}
+
+/// Scan all listening ports. Calls made while a scan is running share its
+/// result instead of starting another.
 #[tauri::command]
-pub fn scan_ports() -> CommandResponse<Vec<PortInfo>> {
-    match scanner::scan_ports() {
+pub async fn scan_ports() -> CommandResponse<Vec<PortInfo>> {
+    blocking(move || match scanner::scan_ports() {
         Ok(ports) => CommandResponse::ok(ports),
-        Err(e) => CommandResponse::err(e.message),
-    }
+        Err(e) => CommandResponse::err(e),
+    })
+    .await
 }
 
-/// Kill a process by PID
+/// The listeners as of the watcher's last scan; scans now if it hasn't
+/// finished one yet. Changes after that arrive as port-* events.
 #[tauri::command]
-pub fn kill_process(pid: u32) -> CommandResponse<()> {
-    match scanner::kill_process(pid) {
-        Ok(()) => CommandResponse::ok(()),
-        Err(e) => CommandResponse::err(e.message),
+pub async fn get_port_snapshot() -> CommandResponse<Vec<PortInfo>> {
+    match watcher::snapshot() {
+        Some(ports) => CommandResponse::ok(ports),
+        None => scan_ports().await,
     }
 }
 
-/// Get a specific port info
+/// Set the watcher's scan interval while shown and, optionally, while hidden
+/// (from settings); returns the clamped shown interval
+#[tauri::command]
+pub fn set_watch_interval(
+    interval_ms: u64,
+    hidden_interval_ms: Option<u64>,
+) -> CommandResponse<u64> {
+    CommandResponse::ok(watcher::set_interval(interval_ms, hidden_interval_ms))
+}
+
+/// Kill a process by PID. When `port` is given, every other process sharing
+/// that port (forked workers) is killed too, since killing only the master
+/// leaves the port busy. Returns the PIDs that were killed.
+///
+/// Pass the scan's `identity` to refuse if the pid has since been reused.
+/// Protected processes (init, sshd, Unbind itself...) are refused with a
+/// `protected` error unless `force` is set. Both apply to the other kill and
+/// signal commands too.
+#[tauri::command]
+pub async fn kill_process(
+    pid: u32,
+    port: Option<u16>,
+    identity: Option<ProcessIdentity>,
+    force: Option<bool>,
+) -> CommandResponse<Vec<u32>> {
+    blocking(move || {
+        let force = force.unwrap_or(false);
+        let result = scanner::verify_identity(pid, identity.as_ref()).and_then(|()| match port {
+            Some(port) => scanner::kill_port_owners(pid, port, force),
+            None => scanner::check_protected(pid, force)
+                .and_then(|()| scanner::kill_process(pid))
+                .map(|()| vec![pid]),
+        });
+
+        match result {
+            Ok(killed) => CommandResponse::ok(killed),
+            Err(e) => CommandResponse::err(e),
+        }
+    })
+    .await
+}
+
+/// Listening unix-domain sockets, which have no port and so aren't in the
+/// port list (Linux only)
+#[tauri::command]
+pub async fn list_unix_sockets() -> CommandResponse<Vec<PortInfo>> {
+    blocking(|| match scanner::unix_sockets() {
+        Ok(sockets) => CommandResponse::ok(sockets),
+        Err(e) => CommandResponse::err(e),
+    })
+    .await
+}
+
+/// Get a specific port info, with the owners of every socket on that port
 #[tauri::command]
-pub fn get_port_info(port: u16) -> CommandResponse<Option<PortInfo>> {
-    match scanner::scan_ports() {
+pub async fn get_port_info(port: u16) -> CommandResponse<Option<PortInfo>> {
+    blocking(move || match scanner::scan_ports() {
         Ok(ports) => {
-            let port_info = ports.into_iter().find(|p| p.port == port);
+            let owners = scanner::port_owners(&ports, port);
+            let port_info = scanner::listeners_on(&ports, port, None)
+                .next()
+                .cloned()
+                .map(|mut info| {
+                    info.owners = owners;
+                    info
+                });
             CommandResponse::ok(port_info)
         }
-        Err(e) => CommandResponse::err(e.message),
-    }
+        Err(e) => CommandResponse::err(e),
+    })
+    .await
+}
+
+/// Full metadata for one listener's process: argv, exe, cwd, user, parent,
+/// start time. Tells apart the six `node` processes in the list.
+#[tauri::command]
+pub async fn get_process_details(pid: u32) -> CommandResponse<ProcessDetails> {
+    blocking(move || match scanner::process_details(pid) {
+        Ok(details) => CommandResponse::ok(details),
+        Err(e) => CommandResponse::err(e),
+    })
+    .await
+}
+
+/// SIGTERM, give the process (and its port) the grace period to go away,
+/// then SIGKILL. Reports which stage ended it.
+#[tauri::command]
+pub async fn kill_process_graceful(
+    pid: u32,
+    port: Option<u16>,
+    strategy: Option<KillStrategy>,
+    identity: Option<ProcessIdentity>,
+    force: Option<bool>,
+) -> CommandResponse<KillReport> {
+    blocking(move || {
+        let strategy = strategy.unwrap_or_default();
+        let result = scanner::verify_identity(pid, identity.as_ref())
+            .and_then(|()| scanner::graceful_kill(pid, port, &strategy, force.unwrap_or(false)));
+        match result {
+            Ok(report) => CommandResponse::ok(report),
+            Err(e) => CommandResponse::err(e),
+        }
+    })
+    .await
+}
+
+/// Make a port free: terminate every process holding it (all address
+/// families and owners) with `strategy`, then re-scan until it is released
+#[tauri::command]
+pub async fn free_port(
+    port: u16,
+    protocol: Option<String>,
+    strategy: Option<KillStrategy>,
+    force: Option<bool>,
+) -> CommandResponse<FreePortReport> {
+    blocking(move || {
+        let strategy = strategy.unwrap_or_default();
+        match scanner::free_port(port, protocol.as_deref(), &strategy, force.unwrap_or(false)) {
+            Ok(report) => CommandResponse::ok(report),
+            Err(e) => CommandResponse::err(e),
+        }
+    })
+    .await
+}
+
+/// Kill several pids/ports at once on a bounded worker pool. Every target
+/// gets its own outcome, so one failure doesn't hide the rest.
+#[tauri::command]
+pub async fn kill_batch(
+    targets: Vec<KillTarget>,
+    strategy: Option<KillStrategy>,
+    force: Option<bool>,
+) -> CommandResponse<Vec<BatchKillOutcome>> {
+    blocking(move || {
+        CommandResponse::ok(scanner::kill_batch(
+            &targets,
+            &strategy.unwrap_or_default(),
+            force.unwrap_or(false),
+        ))
+    })
+    .await
+}
+
+/// Send SIGHUP, SIGINT, SIGUSR1, SIGSTOP/SIGCONT... to a process
+#[tauri::command]
+pub async fn send_signal(
+    pid: u32,
+    signal: Signal,
+    identity: Option<ProcessIdentity>,
+    force: Option<bool>,
+) -> CommandResponse<()> {
+    blocking(move || {
+        let result = scanner::verify_identity(pid, identity.as_ref())
+            .and_then(|()| scanner::send_signal(pid, signal, force.unwrap_or(false)));
+        match result {
+            Ok(()) => CommandResponse::ok(()),
+            Err(e) => CommandResponse::err(e),
+        }
+    })
+    .await
+}
+
+/// Retry a SIGTERM/SIGKILL that failed with `permission_denied`, through the
+/// privileged helper (pkexec + polkit prompt, Linux only). The helper
+/// re-validates the target's identity before signalling it.
+#[tauri::command]
+pub async fn send_signal_elevated(
+    pid: u32,
+    signal: Signal,
+    identity: Option<ProcessIdentity>,
+    force: Option<bool>,
+) -> CommandResponse<()> {
+    blocking(move || {
+        let result = scanner::verify_identity(pid, identity.as_ref()).and_then(|()| {
+            scanner::send_signal_elevated(pid, signal, identity.as_ref(), force.unwrap_or(false))
+        });
+        match result {
+            Ok(()) => CommandResponse::ok(()),
+            Err(e) => CommandResponse::err(e),
+        }
+    })
+    .await
+}
+
+/// Ancestry and descendants of a listener's process
+#[tauri::command]
+pub async fn get_process_tree(pid: u32) -> CommandResponse<ProcessTree> {
+    blocking(move || match scanner::process_tree(pid) {
+        Ok(tree) => CommandResponse::ok(tree),
+        Err(e) => CommandResponse::err(e),
+    })
+    .await
+}
+
+/// Kill a process with its whole subtree (default) or process group,
+/// leaves first, reporting the result for every pid
+#[tauri::command]
+pub async fn kill_process_tree(
+    pid: u32,
+    mode: Option<TreeKillMode>,
+    identity: Option<ProcessIdentity>,
+    force: Option<bool>,
+) -> CommandResponse<Vec<KillOutcome>> {
+    blocking(move || {
+        let mode = mode.unwrap_or_default();
+        let result = scanner::verify_identity(pid, identity.as_ref())
+            .and_then(|()| scanner::kill_process_tree(pid, mode, force.unwrap_or(false)));
+        match result {
+            Ok(outcomes) => CommandResponse::ok(outcomes),
+            Err(e) => CommandResponse::err(e),
+        }
+    })
+    .await
+}
+
+/// Stop the systemd unit behind a listener (`PortInfo.systemd_unit`) instead
+/// of killing a process systemd would restart
+#[tauri::command]
+pub async fn stop_systemd_unit(unit: SystemdUnit) -> CommandResponse<()> {
+    blocking(move || match scanner::stop_systemd_unit(&unit) {
+        Ok(()) => CommandResponse::ok(()),
+        Err(e) => CommandResponse::err(e),
+    })
+    .await
+}
+
+/// Restart the systemd unit behind a listener
+#[tauri::command]
+pub async fn restart_systemd_unit(unit: SystemdUnit) -> CommandResponse<()> {
+    blocking(move || match scanner::restart_systemd_unit(&unit) {
+        Ok(()) => CommandResponse::ok(()),
+        Err(e) => CommandResponse::err(e),
+    })
+    .await
+}
+
+/// Stop the container behind a listener (`PortInfo.container`) through its
+/// engine, waiting `timeout_secs` (default 10) before it is killed
+#[tauri::command]
+pub async fn stop_container(
+    container: ContainerInfo,
+    timeout_secs: Option<u32>,
+) -> CommandResponse<()> {
+    blocking(
+        move || match scanner::stop_container(&container, timeout_secs.unwrap_or(10)) {
+            Ok(()) => CommandResponse::ok(()),
+            Err(e) => CommandResponse::err(e),
+        },
+    )
+    .await
+}
+
+/// What usually runs on a port (e.g. for labeling favorites); protocol None
+/// matches either
+#[tauri::command]
+pub fn lookup_service(port: u16, protocol: Option<String>) -> CommandResponse<Option<ServiceHint>> {
+    CommandResponse::ok(scanner::lookup_service(port, protocol.as_deref()))
+}
+
+/// Re-read the service overrides file; returns how many entries it has
+#[tauri::command]
+pub async fn reload_service_overrides() -> CommandResponse<usize> {
+    blocking(|| match scanner::reload_service_overrides() {
+        Ok(count) => CommandResponse::ok(count),
+        Err(e) => CommandResponse::err(e),
+    })
+    .await
+}
+
+/// Built-in protected names and the user's protection rules
+#[tauri::command]
+pub fn get_protection_policy() -> CommandResponse<ProtectionPolicy> {
+    CommandResponse::ok(scanner::protection_policy())
+}
+
+/// Replace the user's protection rules (name/path patterns, uids) from settings
+#[tauri::command]
+pub async fn set_protection_rules(rules: Vec<ProtectionRule>) -> CommandResponse<()> {
+    blocking(move || match scanner::set_protection_rules(rules) {
+        Ok(()) => CommandResponse::ok(()),
+        Err(e) => CommandResponse::err(e),
+    })
+    .await
+}
+
+/// List the scan backends for this OS with availability and capabilities
+#[tauri::command]
+pub async fn list_scan_backends() -> CommandResponse<Vec<BackendInfo>> {
+    blocking(|| CommandResponse::ok(scanner::scan_backends())).await
+}
+
+/// Choose the backend tried first (from settings), None for the default order
+#[tauri::command]
+pub async fn set_scan_backend(name: Option<String>) -> CommandResponse<()> {
+    blocking(move || match scanner::set_scan_backend(name) {
+        Ok(()) => CommandResponse::ok(()),
+        Err(e) => CommandResponse::err(e),
+    })
+    .await
+}
+
+/// Saved scanner options, for the settings page
+#[tauri::command]
+pub fn get_scanner_settings() -> CommandResponse<ScannerSettings> {
+    CommandResponse::ok(scanner::scanner_settings())
+}
+
+/// Turn scanning of other network namespaces on or off (from settings)
+#[tauri::command]
+pub async fn set_namespace_scanning(enabled: bool) -> CommandResponse<()> {
+    blocking(move || match scanner::set_namespace_scanning(enabled) {
+        Ok(()) => CommandResponse::ok(()),
+        Err(e) => CommandResponse::err(e),
+    })
+    .await
+}
+
+/// Report which backend served the last scan and why the others were skipped
+#[tauri::command]
+pub fn get_scan_diagnostics() -> CommandResponse<Option<ScanDiagnostics>> {
+    CommandResponse::ok(scanner::scan_diagnostics())
 }
@@ -1,5 +1,6 @@
 mod commands;
 mod scanner;
+mod watcher;
 
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::{SystemTime, UNIX_EPOCH};
@@ -50,12 +51,14 @@ fn show_window(app: &tauri::AppHandle, position: Option<PhysicalPosition<f64>>)
         }
         let _ = window.show();
         let _ = window.set_focus();
+        watcher::set_window_visible(true);
     }
 }
 
 fn hide_window(app: &tauri::AppHandle) {
     if let Some(window) = app.get_webview_window("main") {
         let _ = window.hide();
+        watcher::set_window_visible(false);
     }
 }
 
@@ -98,8 +101,31 @@ pub fn run() {
         )
         .invoke_handler(tauri::generate_handler![
             commands::scan_ports,
+            commands::get_port_snapshot,
+            commands::set_watch_interval,
             commands::kill_process,
+            commands::kill_process_graceful,
+            commands::free_port,
+            commands::kill_batch,
+            commands::send_signal,
+            commands::send_signal_elevated,
             commands::get_port_info,
+            commands::list_unix_sockets,
+            commands::get_process_details,
+            commands::get_process_tree,
+            commands::kill_process_tree,
+            commands::stop_systemd_unit,
+            commands::restart_systemd_unit,
+            commands::stop_container,
+            commands::lookup_service,
+            commands::reload_service_overrides,
+            commands::get_protection_policy,
+            commands::set_protection_rules,
+            commands::list_scan_backends,
+            commands::set_scan_backend,
+            commands::get_scanner_settings,
+            commands::set_namespace_scanning,
+            commands::get_scan_diagnostics,
         ])
         .setup(|app| {
             if cfg!(debug_assertions) {
@@ -110,6 +136,25 @@ pub fn run() {
                 )?;
             }
 
+            // Saved scanner settings, protection rules and user additions to
+            // the port/service registry
+            match app.path().app_config_dir() {
+                Ok(dir) => {
+                    if let Err(e) = scanner::load_scanner_settings(&dir.join("scanner.json")) {
+                        log::warn!("Failed to load scanner settings: {}", e.message);
+                    }
+                    if let Err(e) = scanner::load_protection_rules(&dir.join("protection.json")) {
+                        log::warn!("Failed to load protection rules: {}", e.message);
+                    }
+                    if let Err(e) = scanner::load_service_overrides(&dir.join("services.json")) {
+                        log::warn!("Failed to load service overrides: {}", e.message);
+                    }
+                }
+                Err(e) => log::warn!("No config directory for settings: {}", e),
+            }
+
+            watcher::start(app.handle().clone());
+
             // Register global shortcut: Ctrl+Shift+P (Windows/Linux) or Cmd+Shift+P (macOS)
             let shortcut = Shortcut::new(Some(Modifiers::CONTROL | Modifiers::SHIFT), Code::KeyP);
             if let Err(e) = app.global_shortcut().register(shortcut) {
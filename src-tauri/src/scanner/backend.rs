@@ -0,0 +1,473 @@
+use super::{listeners_on, PortInfo, ScanError, ScanErrorKind, ScanResult};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::Instant;
+
+/// What a backend is able to report
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Capabilities {
+    /// Owning PIDs and process names
+    pub pids: bool,
+    /// Real local addresses (not just 0.0.0.0)
+    pub addresses: bool,
+    /// UDP sockets
+    pub udp: bool,
+}
+
+/// A way of listing listening sockets on the current OS
+pub trait ScanBackend {
+    /// Stable identifier, also used to select the backend from settings
+    fn name(&self) -> &'static str;
+
+    /// Cheap check run before scanning; Err carries the reason it was skipped
+    fn check_available(&self) -> Result<(), String>;
+
+    fn capabilities(&self) -> Capabilities;
+
+    fn scan(&self) -> ScanResult<Vec<PortInfo>>;
+
+    /// Whether anything in our namespace listens on `port` (one protocol, or
+    /// any port-based one). Polled while a kill waits for the port to be
+    /// released, so backends that can answer without resolving owners should.
+    fn port_in_use(&self, port: u16, protocol: Option<&str>) -> ScanResult<bool> {
+        self.scan()
+            .map(|ports| listeners_on(&ports, port, protocol).next().is_some())
+    }
+}
+
+/// Description of a backend for the settings UI
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackendInfo {
+    pub name: String,
+    pub available: bool,
+    pub unavailable_reason: Option<String>,
+    pub capabilities: Capabilities,
+    /// Selected in settings to be tried first
+    pub preferred: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AttemptOutcome {
+    /// This backend produced the results
+    Served,
+    /// Ran but returned an error
+    Failed,
+    /// Skipped because its availability check failed
+    Unavailable,
+    /// Not tried because an earlier backend already served the scan
+    NotReached,
+}
+
+/// What happened to one backend during a scan
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackendAttempt {
+    pub name: String,
+    pub outcome: AttemptOutcome,
+    pub reason: Option<String>,
+    pub duration_ms: Option<f64>,
+}
+
+/// Report of the last scan: who served it and why the others didn't
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanDiagnostics {
+    pub served_by: Option<String>,
+    pub preferred: Option<String>,
+    pub attempts: Vec<BackendAttempt>,
+}
+
+/// Backend selected in settings, tried before the default order
+static PREFERRED_BACKEND: Mutex<Option<String>> = Mutex::new(None);
+static LAST_DIAGNOSTICS: Mutex<Option<ScanDiagnostics>> = Mutex::new(None);
+
+pub fn preferred_backend() -> Option<String> {
+    PREFERRED_BACKEND
+        .lock()
+        .map(|guard| guard.clone())
+        .unwrap_or_default()
+}
+
+/// Select the backend to try first, or None for the default order
+pub fn set_preferred_backend(
+    backends: &[Box<dyn ScanBackend>],
+    name: Option<String>,
+) -> ScanResult<()> {
+    if let Some(name) = &name {
+        if !backends.iter().any(|b| b.name() == name) {
+            return Err(ScanError::new(
+                ScanErrorKind::Other,
+                format!("Unknown scan backend: {}", name),
+            ));
+        }
+    }
+
+    if let Ok(mut guard) = PREFERRED_BACKEND.lock() {
+        *guard = name;
+    }
+    Ok(())
+}
+
+pub fn last_diagnostics() -> Option<ScanDiagnostics> {
+    LAST_DIAGNOSTICS
+        .lock()
+        .map(|guard| guard.clone())
+        .unwrap_or_default()
+}
+
+pub fn describe(backends: &[Box<dyn ScanBackend>], preferred: Option<&str>) -> Vec<BackendInfo> {
+    backends
+        .iter()
+        .map(|backend| {
+            let availability = backend.check_available();
+            BackendInfo {
+                name: backend.name().to_string(),
+                available: availability.is_ok(),
+                unavailable_reason: availability.err(),
+                capabilities: backend.capabilities(),
+                preferred: preferred == Some(backend.name()),
+            }
+        })
+        .collect()
+}
+
+/// Try each backend in order (preferred one first) until one succeeds,
+/// recording what happened to every backend for `last_diagnostics`
+pub fn run_scan(backends: Vec<Box<dyn ScanBackend>>) -> ScanResult<Vec<PortInfo>> {
+    let preferred = preferred_backend();
+    let (diagnostics, result) = scan_in_order(order_backends(backends, preferred.as_deref()));
+
+    if let Ok(mut guard) = LAST_DIAGNOSTICS.lock() {
+        *guard = Some(ScanDiagnostics {
+            preferred,
+            ..diagnostics
+        });
+    }
+    result
+}
+
+/// Like `run_scan`, but leaves `last_diagnostics` alone: for the scans kills
+/// and port checks make along the way, which would otherwise replace the
+/// report of the scan the user is looking at
+pub fn run_scan_unrecorded(backends: Vec<Box<dyn ScanBackend>>) -> ScanResult<Vec<PortInfo>> {
+    let preferred = preferred_backend();
+    scan_in_order(order_backends(backends, preferred.as_deref())).1
+}
+
+/// Ask the first available backend, in scan order, whether `port` is in use
+pub fn port_in_use(
+    backends: Vec<Box<dyn ScanBackend>>,
+    port: u16,
+    protocol: Option<&str>,
+) -> ScanResult<bool> {
+    let preferred = preferred_backend();
+    let mut last_error = None;
+    for backend in order_backends(backends, preferred.as_deref()) {
+        if backend.check_available().is_err() {
+            continue;
+        }
+        match backend.port_in_use(port, protocol) {
+            Ok(in_use) => return Ok(in_use),
+            Err(e) => last_error = Some(e),
+        }
+    }
+    Err(last_error
+        .unwrap_or_else(|| ScanError::new(ScanErrorKind::ToolMissing, "No scan backend available")))
+}
+
+/// Move the preferred backend to the front, keeping the rest as fallbacks
+fn order_backends(
+    mut backends: Vec<Box<dyn ScanBackend>>,
+    preferred: Option<&str>,
+) -> Vec<Box<dyn ScanBackend>> {
+    if let Some(pos) = preferred.and_then(|name| backends.iter().position(|b| b.name() == name)) {
+        let backend = backends.remove(pos);
+        backends.insert(0, backend);
+    }
+    backends
+}
+
+fn scan_in_order(
+    backends: Vec<Box<dyn ScanBackend>>,
+) -> (ScanDiagnostics, ScanResult<Vec<PortInfo>>) {
+    let mut attempts = Vec::with_capacity(backends.len());
+    let mut served: Option<(String, Vec<PortInfo>)> = None;
+    let mut last_error: Option<ScanError> = None;
+
+    for backend in &backends {
+        let name = backend.name().to_string();
+
+        if served.is_some() {
+            attempts.push(BackendAttempt {
+                name,
+                outcome: AttemptOutcome::NotReached,
+                reason: None,
+                duration_ms: None,
+            });
+            continue;
+        }
+
+        let started = Instant::now();
+        if let Err(reason) = backend.check_available() {
+            attempts.push(BackendAttempt {
+                name,
+                outcome: AttemptOutcome::Unavailable,
+                reason: Some(reason),
+                duration_ms: Some(elapsed_ms(started)),
+            });
+            continue;
+        }
+
+        match backend.scan() {
+            Ok(ports) => {
+                attempts.push(BackendAttempt {
+                    name: name.clone(),
+                    outcome: AttemptOutcome::Served,
+                    reason: None,
+                    duration_ms: Some(elapsed_ms(started)),
+                });
+                served = Some((name, ports));
+            }
+            Err(e) => {
+                log::debug!("Scan backend {} failed: {}", name, e.message);
+                attempts.push(BackendAttempt {
+                    name,
+                    outcome: AttemptOutcome::Failed,
+                    reason: Some(e.message.clone()),
+                    duration_ms: Some(elapsed_ms(started)),
+                });
+                last_error = Some(e);
+            }
+        }
+    }
+
+    let (served_by, result) = match served {
+        Some((name, ports)) => (Some(name), Ok(ports)),
+        None => (None, Err(no_backend_error(&attempts, last_error))),
+    };
+
+    let diagnostics = ScanDiagnostics {
+        served_by,
+        preferred: None,
+        attempts,
+    };
+    (diagnostics, result)
+}
+
+fn no_backend_error(attempts: &[BackendAttempt], last_error: Option<ScanError>) -> ScanError {
+    if let Some(error) = last_error {
+        return error;
+    }
+    if attempts.is_empty() {
+        return ScanError::new(ScanErrorKind::UnsupportedOs, "Unsupported operating system");
+    }
+
+    let reasons: Vec<String> = attempts
+        .iter()
+        .map(|a| format!("{}: {}", a.name, a.reason.as_deref().unwrap_or("skipped")))
+        .collect();
+    ScanError::new(
+        ScanErrorKind::ToolMissing,
+        format!("No scan backend available ({})", reasons.join("; ")),
+    )
+}
+
+fn elapsed_ms(started: Instant) -> f64 {
+    started.elapsed().as_secs_f64() * 1000.0
+}
+
+/// Whether an executable is reachable through PATH
+#[cfg(unix)]
+pub fn find_in_path(tool: &str) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    std::env::var_os("PATH")
+        .map(|paths| {
+            std::env::split_paths(&paths).any(|dir| {
+                std::fs::metadata(dir.join(tool))
+                    .map(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+                    .unwrap_or(false)
+            })
+        })
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct FakeBackend {
+        name: &'static str,
+        available: bool,
+        result: Result<usize, ScanErrorKind>,
+    }
+
+    impl ScanBackend for FakeBackend {
+        fn name(&self) -> &'static str {
+            self.name
+        }
+
+        fn check_available(&self) -> Result<(), String> {
+            if self.available {
+                Ok(())
+            } else {
+                Err(format!("{} is not installed", self.name))
+            }
+        }
+
+        fn capabilities(&self) -> Capabilities {
+            Capabilities {
+                pids: true,
+                addresses: true,
+                udp: true,
+            }
+        }
+
+        fn scan(&self) -> ScanResult<Vec<PortInfo>> {
+            match self.result {
+                Ok(count) => Ok((0..count)
+                    .map(|i| PortInfo::new(i as u16, "tcp", "0.0.0.0".to_string(), vec![]))
+                    .collect()),
+                Err(kind) => Err(ScanError::new(kind, format!("{} broke", self.name))),
+            }
+        }
+    }
+
+    fn fake(
+        name: &'static str,
+        available: bool,
+        result: Result<usize, ScanErrorKind>,
+    ) -> Box<dyn ScanBackend> {
+        Box::new(FakeBackend {
+            name,
+            available,
+            result,
+        })
+    }
+
+    fn outcomes(diagnostics: &ScanDiagnostics) -> Vec<(&str, AttemptOutcome)> {
+        diagnostics
+            .attempts
+            .iter()
+            .map(|a| (a.name.as_str(), a.outcome))
+            .collect()
+    }
+
+    #[test]
+    fn test_first_working_backend_serves() {
+        let backends = vec![
+            fake("netlink", true, Err(ScanErrorKind::PermissionDenied)),
+            fake("ss", false, Ok(9)),
+            fake("proc", true, Ok(2)),
+            fake("extra", true, Ok(5)),
+        ];
+        let (diagnostics, result) = scan_in_order(backends);
+
+        assert_eq!(result.unwrap().len(), 2);
+        assert_eq!(diagnostics.served_by.as_deref(), Some("proc"));
+        assert_eq!(
+            outcomes(&diagnostics),
+            vec![
+                ("netlink", AttemptOutcome::Failed),
+                ("ss", AttemptOutcome::Unavailable),
+                ("proc", AttemptOutcome::Served),
+                ("extra", AttemptOutcome::NotReached),
+            ]
+        );
+        assert_eq!(
+            diagnostics.attempts[1].reason.as_deref(),
+            Some("ss is not installed")
+        );
+        assert!(diagnostics.attempts[3].duration_ms.is_none());
+    }
+
+    #[test]
+    fn test_all_failing_returns_last_error() {
+        let backends = vec![
+            fake("a", true, Err(ScanErrorKind::Io)),
+            fake("b", true, Err(ScanErrorKind::PermissionDenied)),
+        ];
+        let (diagnostics, result) = scan_in_order(backends);
+
+        let error = result.unwrap_err();
+        assert_eq!(error.kind, ScanErrorKind::PermissionDenied);
+        assert!(diagnostics.served_by.is_none());
+    }
+
+    #[test]
+    fn test_none_available_is_tool_missing() {
+        let (_, result) = scan_in_order(vec![fake("ss", false, Ok(1))]);
+        let error = result.unwrap_err();
+        assert_eq!(error.kind, ScanErrorKind::ToolMissing);
+        assert!(error.message.contains("ss is not installed"));
+    }
+
+    #[test]
+    fn test_no_backends_is_unsupported() {
+        let (_, result) = scan_in_order(Vec::new());
+        assert_eq!(result.unwrap_err().kind, ScanErrorKind::UnsupportedOs);
+    }
+
+    #[test]
+    fn test_preferred_backend_goes_first() {
+        let backends = vec![fake("a", true, Ok(1)), fake("b", true, Ok(2))];
+        let ordered = order_backends(backends, Some("b"));
+        let names: Vec<&str> = ordered.iter().map(|b| b.name()).collect();
+        assert_eq!(names, vec!["b", "a"]);
+
+        let backends = vec![fake("a", true, Ok(1)), fake("b", true, Ok(2))];
+        let ordered = order_backends(backends, Some("missing"));
+        let names: Vec<&str> = ordered.iter().map(|b| b.name()).collect();
+        assert_eq!(names, vec!["a", "b"]);
+    }
+
+    #[test]
+    fn test_set_preferred_backend_rejects_unknown() {
+        let backends = vec![fake("a", true, Ok(1))];
+        let error = set_preferred_backend(&backends, Some("zzz".to_string())).unwrap_err();
+        assert!(error.message.contains("zzz"));
+    }
+
+    #[test]
+    fn test_port_in_use_falls_back() {
+        // The fake lists ports 0..count
+        let backends = vec![
+            fake("a", false, Ok(10)),
+            fake("b", true, Err(ScanErrorKind::Io)),
+            fake("c", true, Ok(3)),
+        ];
+        assert!(port_in_use(backends, 2, Some("tcp")).unwrap());
+
+        let backends = vec![fake("c", true, Ok(3))];
+        assert!(!port_in_use(backends, 5, None).unwrap());
+
+        let backends = vec![fake("c", true, Ok(3))];
+        assert!(!port_in_use(backends, 2, Some("udp")).unwrap());
+
+        let backends = vec![fake("b", true, Err(ScanErrorKind::Io))];
+        assert_eq!(
+            port_in_use(backends, 2, None).unwrap_err().kind,
+            ScanErrorKind::Io
+        );
+    }
+
+    #[test]
+    fn test_describe_reports_availability() {
+        let backends = vec![fake("a", true, Ok(1)), fake("b", false, Ok(1))];
+        let infos = describe(&backends, Some("b"));
+        assert!(infos[0].available);
+        assert!(!infos[1].available);
+        assert!(!infos[0].preferred);
+        assert!(infos[1].preferred);
+        assert_eq!(
+            infos[1].unavailable_reason.as_deref(),
+            Some("b is not installed")
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_find_in_path() {
+        assert!(find_in_path("sh"));
+        assert!(!find_in_path("definitely-not-a-real-tool-xyz"));
+    }
+}
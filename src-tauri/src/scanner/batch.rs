@@ -0,0 +1,210 @@
+use super::{
+    free_port, graceful_kill, verify_identity, BatchKillOutcome, KillStage, KillStrategy,
+    KillTarget, ScanError, ScanErrorKind, TargetStatus,
+};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+/// Upper bound on kills in flight at once
+const MAX_CONCURRENT_KILLS: usize = 4;
+
+/// Kill every target with `strategy` on a small worker pool. Outcomes come
+/// back in input order, one per target, whatever happened to the others.
+pub fn kill_batch(
+    targets: &[KillTarget],
+    strategy: &KillStrategy,
+    force: bool,
+) -> Vec<BatchKillOutcome> {
+    let next = AtomicUsize::new(0);
+    let results: Mutex<Vec<Option<BatchKillOutcome>>> =
+        Mutex::new((0..targets.len()).map(|_| None).collect());
+
+    thread::scope(|scope| {
+        for _ in 0..MAX_CONCURRENT_KILLS.min(targets.len()) {
+            scope.spawn(|| loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                let Some(target) = targets.get(index) else {
+                    break;
+                };
+                let outcome = kill_target(target, strategy, force);
+                if let Ok(mut results) = results.lock() {
+                    results[index] = Some(outcome);
+                }
+            });
+        }
+    });
+
+    let results = match results.into_inner() {
+        Ok(results) => results,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    results
+        .into_iter()
+        .zip(targets)
+        .map(|(outcome, target)| {
+            outcome.unwrap_or_else(|| {
+                failed(
+                    target,
+                    ScanError::from("Kill worker stopped before reaching this target"),
+                )
+            })
+        })
+        .collect()
+}
+
+fn kill_target(target: &KillTarget, strategy: &KillStrategy, force: bool) -> BatchKillOutcome {
+    match target {
+        KillTarget::Pid { pid, identity } => {
+            let result = verify_identity(*pid, identity.as_ref())
+                .and_then(|()| graceful_kill(*pid, None, strategy, force));
+            match result {
+                Ok(report) if report.stage == KillStage::AlreadyExited => BatchKillOutcome {
+                    target: target.clone(),
+                    status: TargetStatus::AlreadyGone,
+                    killed: Vec::new(),
+                    error: None,
+                },
+                Ok(_) => BatchKillOutcome {
+                    target: target.clone(),
+                    status: TargetStatus::Killed,
+                    killed: vec![*pid],
+                    error: None,
+                },
+                Err(e) => failed(target, e),
+            }
+        }
+        KillTarget::Port { port, protocol } => {
+            match free_port(*port, protocol.as_deref(), strategy, force) {
+                Ok(mut report) => {
+                    let killed: Vec<u32> = report.killed.iter().map(|freed| freed.pid).collect();
+                    let status = if !report.released {
+                        match report.errors.first() {
+                            Some(e) => status_for(e),
+                            None => TargetStatus::Failed,
+                        }
+                    } else if killed.is_empty() {
+                        TargetStatus::AlreadyGone
+                    } else {
+                        TargetStatus::Killed
+                    };
+                    let error = if report.released {
+                        None
+                    } else if report.errors.is_empty() {
+                        Some(
+                            ScanError::new(
+                                ScanErrorKind::Other,
+                                format!("Port {} is still in use", port),
+                            )
+                            .with_port(*port),
+                        )
+                    } else {
+                        Some(report.errors.remove(0))
+                    };
+                    BatchKillOutcome {
+                        target: target.clone(),
+                        status,
+                        killed,
+                        error,
+                    }
+                }
+                Err(e) => failed(target, e),
+            }
+        }
+    }
+}
+
+fn failed(target: &KillTarget, error: ScanError) -> BatchKillOutcome {
+    BatchKillOutcome {
+        target: target.clone(),
+        status: status_for(&error),
+        killed: Vec::new(),
+        error: Some(error),
+    }
+}
+
+fn status_for(error: &ScanError) -> TargetStatus {
+    match error.kind {
+        // A changed identity means the process the user saw is gone too
+        ScanErrorKind::ProcessNotFound | ScanErrorKind::ProcessChanged => TargetStatus::AlreadyGone,
+        ScanErrorKind::PermissionDenied => TargetStatus::PermissionDenied,
+        ScanErrorKind::Protected => TargetStatus::Protected,
+        _ => TargetStatus::Failed,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_for() {
+        let error = |kind| ScanError::new(kind, "x");
+        assert_eq!(
+            status_for(&error(ScanErrorKind::ProcessNotFound)),
+            TargetStatus::AlreadyGone
+        );
+        assert_eq!(
+            status_for(&error(ScanErrorKind::ProcessChanged)),
+            TargetStatus::AlreadyGone
+        );
+        assert_eq!(
+            status_for(&error(ScanErrorKind::PermissionDenied)),
+            TargetStatus::PermissionDenied
+        );
+        assert_eq!(
+            status_for(&error(ScanErrorKind::Protected)),
+            TargetStatus::Protected
+        );
+        assert_eq!(status_for(&error(ScanErrorKind::Io)), TargetStatus::Failed);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_kill_batch() {
+        use crate::scanner::ProcessIdentity;
+        use std::process::{Child, Command};
+
+        let sleeper = || Command::new("sleep").arg("30").spawn().unwrap();
+        let mut children: Vec<Child> = (0..5).map(|_| sleeper()).collect();
+        // Stands in for a pid reused since the scan: the token won't match
+        let mut reused = sleeper();
+        let reused_pid = reused.id();
+
+        let mut targets: Vec<KillTarget> = children
+            .iter()
+            .map(|child| KillTarget::Pid {
+                pid: child.id(),
+                identity: None,
+            })
+            .collect();
+        targets.push(KillTarget::Pid {
+            pid: reused_pid,
+            identity: Some(ProcessIdentity {
+                pid: reused_pid,
+                start_time: 0,
+            }),
+        });
+        targets.push(KillTarget::Pid {
+            pid: std::process::id(),
+            identity: None,
+        });
+
+        let strategy = KillStrategy {
+            grace_period_ms: 2000,
+            poll_interval_ms: 10,
+        };
+        let outcomes = kill_batch(&targets, &strategy, false);
+        for child in &mut children {
+            child.wait().unwrap();
+        }
+        reused.kill().unwrap();
+        reused.wait().unwrap();
+
+        let statuses: Vec<TargetStatus> = outcomes.iter().map(|o| o.status).collect();
+        assert_eq!(&statuses[..5], &[TargetStatus::Killed; 5]);
+        assert_eq!(statuses[5], TargetStatus::AlreadyGone);
+        assert_eq!(statuses[6], TargetStatus::Protected);
+        assert_eq!(outcomes[0].killed, vec![children[0].id()]);
+    }
+}
@@ -0,0 +1,92 @@
+//! The built-in protection that not even root may override. `protect` applies
+//! it in the app and the privileged helper (src/bin/unbind-helper.rs)
+//! includes this file by path and applies it again with what it reads itself,
+//! so it must stay std + libc only.
+
+/// System processes that take the machine, the session or remote access down
+/// with them: init, sshd, display servers and compositors, container daemons
+pub const BUILTIN_NAMES: &[&str] = &[
+    "systemd",
+    "init",
+    "launchd",
+    "sshd",
+    "Xorg",
+    "Xwayland",
+    "gnome-shell",
+    "kwin_wayland",
+    "kwin_x11",
+    "gdm",
+    "sddm",
+    "lightdm",
+    "dbus-daemon",
+    "dbus-broker",
+    "WindowServer",
+    "loginwindow",
+    "dockerd",
+    "containerd",
+    "System",
+    "smss.exe",
+    "csrss.exe",
+    "wininit.exe",
+    "winlogon.exe",
+    "services.exe",
+    "lsass.exe",
+];
+
+/// Why `pid` itself may not be signalled, before anything is known about the
+/// process: the caller, init, core Windows processes, or the leader of the
+/// caller's login session
+pub fn pid_reason(pid: u32, own_pid: u32, session_leader: Option<u32>) -> Option<String> {
+    if pid == own_pid {
+        return Some("it is Unbind itself".to_string());
+    }
+    if cfg!(unix) && pid == 1 {
+        return Some("it is the init process".to_string());
+    }
+    // System Idle Process and System
+    if cfg!(windows) && pid <= 4 {
+        return Some("it is a core system process".to_string());
+    }
+    if session_leader == Some(pid) {
+        return Some("it leads Unbind's login session".to_string());
+    }
+    None
+}
+
+/// Why a process may not be signalled given its kind and names (see
+/// `candidate_names`)
+pub fn process_reason(kernel_thread: bool, names: &[String]) -> Option<String> {
+    if kernel_thread {
+        return Some("it is a kernel thread".to_string());
+    }
+    names
+        .iter()
+        .find(|name| BUILTIN_NAMES.contains(&name.as_str()))
+        .map(|name| format!("{} is a system process", name))
+}
+
+/// The short name plus the executable's file name, since Linux truncates
+/// comm to 15 characters
+pub fn candidate_names(name: &str, exe: Option<&str>) -> Vec<String> {
+    let mut names = vec![name.to_string()];
+    if let Some(base) = exe.and_then(|exe| exe.rsplit(['/', '\\']).next()) {
+        if !base.is_empty() && !names.iter().any(|name| name == base) {
+            names.push(base.to_string());
+        }
+    }
+    names
+}
+
+/// Leader of the session the calling process runs in (login shell or
+/// desktop session)
+#[cfg(unix)]
+pub fn session_leader() -> Option<u32> {
+    // SAFETY: getsid(0) only queries the calling process
+    let sid = unsafe { libc::getsid(0) };
+    u32::try_from(sid).ok().filter(|&sid| sid > 1)
+}
+
+#[cfg(not(unix))]
+pub fn session_leader() -> Option<u32> {
+    None
+}
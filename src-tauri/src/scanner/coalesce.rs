@@ -0,0 +1,161 @@
+use super::{PortInfo, ScanError, ScanErrorKind, ScanResult};
+use std::sync::{Condvar, Mutex, MutexGuard, PoisonError};
+
+/// Lets concurrent callers share one scan: a caller arriving while a scan
+/// is running waits for it and gets a copy of its result instead of
+/// starting another (the watcher and a manual refresh often collide).
+/// There is no way to abandon a shared scan: every tool it runs is bounded by
+/// `exec::output_within`, which kills the child on timeout, so the scan and
+/// its waiters are always released within that bound.
+pub struct SharedScan {
+    state: Mutex<FlightState>,
+    landed: Condvar,
+}
+
+struct FlightState {
+    running: bool,
+    /// Scans finished so far; waiters watch for it to move
+    completed: u64,
+    last: Option<ScanResult<Vec<PortInfo>>>,
+}
+
+impl SharedScan {
+    pub const fn new() -> Self {
+        SharedScan {
+            state: Mutex::new(FlightState {
+                running: false,
+                completed: 0,
+                last: None,
+            }),
+            landed: Condvar::new(),
+        }
+    }
+
+    pub fn run(
+        &self,
+        scan: impl FnOnce() -> ScanResult<Vec<PortInfo>>,
+    ) -> ScanResult<Vec<PortInfo>> {
+        let mut state = self.lock();
+        if state.running {
+            let awaited = state.completed + 1;
+            let state = self
+                .landed
+                .wait_while(state, |state| state.completed < awaited)
+                .unwrap_or_else(PoisonError::into_inner);
+            return state.last.clone().unwrap_or_else(|| Err(lost_scan()));
+        }
+        state.running = true;
+        drop(state);
+
+        // Publishes from Drop so waiters are released even if the scan panics
+        let mut landing = Landing {
+            flight: self,
+            result: None,
+        };
+        let result = scan();
+        landing.result = Some(result.clone());
+        result
+    }
+
+    fn lock(&self) -> MutexGuard<'_, FlightState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+struct Landing<'a> {
+    flight: &'a SharedScan,
+    result: Option<ScanResult<Vec<PortInfo>>>,
+}
+
+impl Drop for Landing<'_> {
+    fn drop(&mut self) {
+        let mut state = self.flight.lock();
+        state.running = false;
+        state.completed += 1;
+        state.last = Some(self.result.take().unwrap_or_else(|| Err(lost_scan())));
+        self.flight.landed.notify_all();
+    }
+}
+
+fn lost_scan() -> ScanError {
+    ScanError::new(
+        ScanErrorKind::Other,
+        "The shared scan ended without a result",
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn test_concurrent_callers_share_one_scan() {
+        let flight = Arc::new(SharedScan::new());
+        let scans = Arc::new(AtomicU32::new(0));
+
+        let slow_scan = {
+            let scans = Arc::clone(&scans);
+            move || {
+                scans.fetch_add(1, Ordering::SeqCst);
+                thread::sleep(Duration::from_millis(400));
+                Ok(vec![PortInfo::new(3000, "tcp", "0.0.0.0".to_string(), vec![])])
+            }
+        };
+
+        let leader = {
+            let flight = Arc::clone(&flight);
+            thread::spawn(move || flight.run(slow_scan))
+        };
+        thread::sleep(Duration::from_millis(100));
+        let followers: Vec<_> = (0..3)
+            .map(|_| {
+                let flight = Arc::clone(&flight);
+                let scans = Arc::clone(&scans);
+                thread::spawn(move || {
+                    flight.run(|| {
+                        scans.fetch_add(1, Ordering::SeqCst);
+                        Ok(Vec::new())
+                    })
+                })
+            })
+            .collect();
+
+        assert_eq!(leader.join().unwrap().unwrap().len(), 1);
+        for follower in followers {
+            assert_eq!(follower.join().unwrap().unwrap()[0].port, 3000);
+        }
+        assert_eq!(scans.load(Ordering::SeqCst), 1);
+
+        // Once it has landed, the next caller scans afresh
+        assert!(flight.run(|| Ok(Vec::new())).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_waiters_survive_a_panicking_scan() {
+        let flight = Arc::new(SharedScan::new());
+        let leader = {
+            let flight = Arc::clone(&flight);
+            thread::spawn(move || {
+                flight.run(|| {
+                    thread::sleep(Duration::from_millis(300));
+                    panic!("scanner bug")
+                })
+            })
+        };
+        thread::sleep(Duration::from_millis(100));
+        let follower = {
+            let flight = Arc::clone(&flight);
+            thread::spawn(move || flight.run(|| Ok(Vec::new())))
+        };
+
+        assert!(leader.join().is_err());
+        assert_eq!(
+            follower.join().unwrap().unwrap_err().kind,
+            ScanErrorKind::Other
+        );
+    }
+}
@@ -0,0 +1,384 @@
+use super::docker::{self, ApiContainer};
+use super::{
+    procfs, ContainerInfo, ContainerRuntime, PortInfo, ScanError, ScanErrorKind, ScanResult,
+};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// How long the daemons' container lists are reused across scans
+const API_CACHE_TTL: Duration = Duration::from_secs(5);
+
+/// How a listening process relates to a container
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Membership {
+    /// Runs inside the container, or is its conmon monitor: the id is known
+    Member {
+        runtime: ContainerRuntime,
+        id: String,
+        name: Option<String>,
+    },
+    /// Forwards a published port from the host (docker-proxy, rootlessport,
+    /// rootlesskit); the container is whichever one publishes that port
+    Forwarder,
+}
+
+type ApiList = Vec<(ContainerRuntime, ApiContainer)>;
+
+static API_CACHE: Mutex<Option<(Instant, ApiList)>> = Mutex::new(None);
+
+/// Fill in `container` for listeners that belong to or forward for one.
+/// The engine APIs are only asked when some listener looks containerized.
+pub fn attach_containers(ports: &mut [PortInfo]) {
+    let mut memberships: HashMap<u32, Option<Membership>> = HashMap::new();
+    for info in ports.iter() {
+        memberships
+            .entry(info.pid)
+            .or_insert_with(|| membership(info.pid));
+    }
+    if memberships.values().all(Option::is_none) {
+        return;
+    }
+
+    let containers = cached_containers();
+    for info in ports.iter_mut() {
+        info.container = match memberships.get(&info.pid) {
+            Some(Some(membership)) => resolve(membership, info, &containers),
+            _ => None,
+        };
+    }
+}
+
+/// Stop a container through its engine's API, trying the sockets of its
+/// runtime first. Docker and Podman only: containerd has no Engine API.
+pub fn stop_container(container: &ContainerInfo, timeout_secs: u32) -> ScanResult<()> {
+    if container.runtime == ContainerRuntime::Containerd {
+        return Err(ScanError::new(
+            ScanErrorKind::Other,
+            format!(
+                "Container {} is managed by containerd; stop it with crictl or nerdctl",
+                short_id(&container.id)
+            ),
+        ));
+    }
+
+    let mut sockets = docker::sockets();
+    sockets.sort_by_key(|(runtime, _)| *runtime != container.runtime);
+    let mut last_error = None;
+    for (_, socket) in sockets {
+        match docker::stop(&socket, &container.id, timeout_secs) {
+            Ok(()) => {
+                invalidate_cache();
+                return Ok(());
+            }
+            // No daemon on this socket, or not its container: try the next
+            Err(e)
+                if matches!(
+                    e.kind,
+                    ScanErrorKind::ToolMissing | ScanErrorKind::ProcessNotFound
+                ) =>
+            {
+                last_error = Some(e)
+            }
+            Err(e) => return Err(e),
+        }
+    }
+    Err(last_error.unwrap_or_else(|| {
+        ScanError::new(
+            ScanErrorKind::ToolMissing,
+            "No Docker or Podman API socket found",
+        )
+    }))
+}
+
+fn membership(pid: u32) -> Option<Membership> {
+    procfs::read_cgroup_path(pid)
+        .and_then(|path| membership_from_cgroup(&path))
+        .or_else(|| membership_from_cmdline(&procfs::read_cmdline(pid)))
+}
+
+/// Container scopes as the engines name them with the systemd cgroup driver
+const SCOPE_PREFIXES: &[(&str, ContainerRuntime)] = &[
+    ("docker-", ContainerRuntime::Docker),
+    ("libpod-conmon-", ContainerRuntime::Podman),
+    ("libpod-", ContainerRuntime::Podman),
+    ("cri-containerd-", ContainerRuntime::Containerd),
+];
+
+/// `.../docker-<id>.scope`, `.../libpod-<id>.scope`... or, with the
+/// cgroupfs driver, `/docker/<id>`
+fn membership_from_cgroup(path: &str) -> Option<Membership> {
+    let components: Vec<&str> = path.split('/').filter(|c| !c.is_empty()).collect();
+    for (index, component) in components.iter().enumerate().rev() {
+        let unit = component.strip_suffix(".scope").unwrap_or(component);
+        let scoped = SCOPE_PREFIXES.iter().find_map(|(prefix, runtime)| {
+            let id = unit.strip_prefix(prefix)?;
+            is_container_id(id).then_some((*runtime, id))
+        });
+        let plain = (index > 0 && components[index - 1] == "docker" && is_container_id(component))
+            .then_some((ContainerRuntime::Docker, *component));
+
+        if let Some((runtime, id)) = scoped.or(plain) {
+            return Some(Membership::Member {
+                runtime,
+                id: id.to_string(),
+                name: None,
+            });
+        }
+    }
+    None
+}
+
+fn membership_from_cmdline(argv: &[String]) -> Option<Membership> {
+    let program = argv.first()?.rsplit('/').next()?;
+    match program {
+        "docker-proxy" | "rootlesskit" | "rootlessport" => Some(Membership::Forwarder),
+        // Podman's monitor holds the published ports of rootful containers
+        "conmon" => Some(Membership::Member {
+            runtime: ContainerRuntime::Podman,
+            id: flag_value(argv, &["-c", "--cid"])?,
+            name: flag_value(argv, &["-n", "--name"]),
+        }),
+        _ => None,
+    }
+}
+
+/// Value of a command-line flag given as `-c <v>`, `--cid <v>` or `--cid=<v>`
+fn flag_value(argv: &[String], flags: &[&str]) -> Option<String> {
+    argv.iter().enumerate().find_map(|(index, arg)| {
+        flags.iter().find_map(|flag| {
+            if arg == flag {
+                argv.get(index + 1).cloned()
+            } else {
+                arg.strip_prefix(flag)?
+                    .strip_prefix('=')
+                    .map(str::to_string)
+            }
+        })
+    })
+}
+
+fn is_container_id(id: &str) -> bool {
+    id.len() == 64 && id.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+fn short_id(id: &str) -> &str {
+    id.get(..12).unwrap_or(id)
+}
+
+fn resolve(
+    membership: &Membership,
+    info: &PortInfo,
+    containers: &ApiList,
+) -> Option<ContainerInfo> {
+    match membership {
+        Membership::Member { runtime, id, name } => {
+            let listed = containers
+                .iter()
+                .map(|(_, container)| container)
+                .find(|container| container.id.starts_with(id.as_str()));
+            Some(ContainerInfo {
+                id: id.clone(),
+                name: listed.and_then(ApiContainer::name).or_else(|| name.clone()),
+                image: listed.and_then(image_of),
+                runtime: *runtime,
+            })
+        }
+        Membership::Forwarder => containers
+            .iter()
+            .find(|(_, container)| container.publishes(info.port, &info.protocol))
+            .map(|(runtime, container)| ContainerInfo {
+                id: container.id.clone(),
+                name: container.name(),
+                image: image_of(container),
+                runtime: *runtime,
+            }),
+    }
+}
+
+fn image_of(container: &ApiContainer) -> Option<String> {
+    Some(container.image.clone()).filter(|image| !image.is_empty())
+}
+
+/// Containers from every reachable engine, refreshed once stale
+fn cached_containers() -> ApiList {
+    let Ok(mut cache) = API_CACHE.lock() else {
+        return Vec::new();
+    };
+    if let Some((fetched, containers)) = cache.as_ref() {
+        if fetched.elapsed() < API_CACHE_TTL {
+            return containers.clone();
+        }
+    }
+    let containers: ApiList = docker::sockets()
+        .into_iter()
+        .filter_map(|(runtime, socket)| {
+            docker::list(&socket)
+                .map_err(|e| log::debug!("{}", e.message))
+                .ok()
+                .map(|list| (runtime, list))
+        })
+        .flat_map(|(runtime, list)| list.into_iter().map(move |c| (runtime, c)))
+        .collect();
+    *cache = Some((Instant::now(), containers.clone()));
+    containers
+}
+
+fn invalidate_cache() {
+    if let Ok(mut cache) = API_CACHE.lock() {
+        *cache = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::docker::ApiPort;
+
+    const ID: &str = "3f2a9c6e0b7d41c5a8e9f0d1c2b3a4958677a6b5c4d3e2f1a0b9c8d7e6f5a4b3";
+
+    fn member(runtime: ContainerRuntime, name: Option<&str>) -> Option<Membership> {
+        Some(Membership::Member {
+            runtime,
+            id: ID.to_string(),
+            name: name.map(str::to_string),
+        })
+    }
+
+    fn argv(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn test_membership_from_cgroup() {
+        assert_eq!(
+            membership_from_cgroup(&format!("/system.slice/docker-{}.scope", ID)),
+            member(ContainerRuntime::Docker, None)
+        );
+        assert_eq!(
+            membership_from_cgroup(&format!("/docker/{}", ID)),
+            member(ContainerRuntime::Docker, None)
+        );
+        assert_eq!(
+            membership_from_cgroup(&format!(
+                "/user.slice/user-1000.slice/user@1000.service/user.slice/libpod-{}.scope/container",
+                ID
+            )),
+            member(ContainerRuntime::Podman, None)
+        );
+        assert_eq!(
+            membership_from_cgroup(&format!("/machine.slice/libpod-conmon-{}.scope", ID)),
+            member(ContainerRuntime::Podman, None)
+        );
+        assert_eq!(
+            membership_from_cgroup(&format!(
+                "/kubepods.slice/kubepods-besteffort.slice/kubepods-besteffort-pod1.slice/cri-containerd-{}.scope",
+                ID
+            )),
+            member(ContainerRuntime::Containerd, None)
+        );
+        assert_eq!(membership_from_cgroup("/system.slice/docker.service"), None);
+        assert_eq!(
+            membership_from_cgroup("/system.slice/docker-short.scope"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_membership_from_cmdline() {
+        let proxy = argv(&[
+            "/usr/bin/docker-proxy",
+            "-proto",
+            "tcp",
+            "-host-ip",
+            "0.0.0.0",
+            "-host-port",
+            "8080",
+            "-container-ip",
+            "172.17.0.2",
+            "-container-port",
+            "80",
+        ]);
+        assert_eq!(membership_from_cmdline(&proxy), Some(Membership::Forwarder));
+        assert_eq!(
+            membership_from_cmdline(&argv(&["rootlessport"])),
+            Some(Membership::Forwarder)
+        );
+
+        let conmon = argv(&[
+            "/usr/bin/conmon",
+            "--api-version",
+            "1",
+            "-c",
+            ID,
+            "-u",
+            ID,
+            "-n",
+            "web",
+        ]);
+        assert_eq!(
+            membership_from_cmdline(&conmon),
+            member(ContainerRuntime::Podman, Some("web"))
+        );
+        let cid = format!("--cid={}", ID);
+        assert_eq!(
+            membership_from_cmdline(&argv(&["conmon", &cid])),
+            member(ContainerRuntime::Podman, None)
+        );
+
+        assert_eq!(membership_from_cmdline(&argv(&["node", "server.js"])), None);
+        assert_eq!(membership_from_cmdline(&[]), None);
+    }
+
+    #[test]
+    fn test_resolve() {
+        let api = ApiContainer {
+            id: ID.to_string(),
+            names: vec!["/web".to_string()],
+            image: "nginx:1.27".to_string(),
+            ports: vec![ApiPort {
+                public_port: Some(8080),
+                protocol: "tcp".to_string(),
+            }],
+        };
+        let containers = vec![(ContainerRuntime::Docker, api)];
+        let listener = PortInfo::new(8080, "tcp", "0.0.0.0:8080".to_string(), Vec::new());
+        let web = ContainerInfo {
+            id: ID.to_string(),
+            name: Some("web".to_string()),
+            image: Some("nginx:1.27".to_string()),
+            runtime: ContainerRuntime::Docker,
+        };
+
+        let proxy = Membership::Forwarder;
+        assert_eq!(resolve(&proxy, &listener, &containers), Some(web.clone()));
+        let other = PortInfo::new(9090, "tcp", "0.0.0.0:9090".to_string(), Vec::new());
+        assert_eq!(resolve(&proxy, &other, &containers), None);
+
+        let inside = member(ContainerRuntime::Docker, None).unwrap();
+        assert_eq!(resolve(&inside, &listener, &containers), Some(web));
+        // Without the API, cgroup and conmon data still identify it
+        let conmon = member(ContainerRuntime::Podman, Some("db")).unwrap();
+        assert_eq!(
+            resolve(&conmon, &listener, &Vec::new()),
+            Some(ContainerInfo {
+                id: ID.to_string(),
+                name: Some("db".to_string()),
+                image: None,
+                runtime: ContainerRuntime::Podman,
+            })
+        );
+    }
+
+    #[test]
+    fn test_containerd_cannot_be_stopped() {
+        let container = ContainerInfo {
+            id: ID.to_string(),
+            name: None,
+            image: None,
+            runtime: ContainerRuntime::Containerd,
+        };
+        let error = stop_container(&container, 10).unwrap_err();
+        assert!(error.message.contains("3f2a9c6e0b7d"));
+    }
+}
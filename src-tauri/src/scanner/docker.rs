@@ -0,0 +1,321 @@
+use super::{ContainerRuntime, ScanError, ScanErrorKind, ScanResult};
+use serde::Deserialize;
+use std::env;
+use std::io::{self, Read, Write};
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Listing containers is quick; a daemon that takes longer is stuck
+const LIST_TIMEOUT: Duration = Duration::from_secs(3);
+/// Headroom on top of the stop grace period for the daemon to answer
+const STOP_MARGIN: Duration = Duration::from_secs(10);
+
+/// A running container as reported by `GET /containers/json`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ApiContainer {
+    pub id: String,
+    /// Names with the leading '/' the API puts on them
+    #[serde(default)]
+    pub names: Vec<String>,
+    #[serde(default)]
+    pub image: String,
+    #[serde(default)]
+    pub ports: Vec<ApiPort>,
+}
+
+/// A port mapping; `public_port` is only set for ports published on the host
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ApiPort {
+    #[serde(default)]
+    pub public_port: Option<u16>,
+    /// "tcp", "udp" or "sctp"
+    #[serde(rename = "Type")]
+    pub protocol: String,
+}
+
+impl ApiContainer {
+    pub fn name(&self) -> Option<String> {
+        self.names
+            .first()
+            .map(|name| name.trim_start_matches('/').to_string())
+    }
+
+    pub fn publishes(&self, port: u16, protocol: &str) -> bool {
+        self.ports
+            .iter()
+            .any(|p| p.public_port == Some(port) && p.protocol == protocol)
+    }
+}
+
+/// Engine API sockets to try, most specific first: $DOCKER_HOST, rootful
+/// and rootless Docker, then Podman's Docker-compatible service
+pub fn sockets() -> Vec<(ContainerRuntime, PathBuf)> {
+    let mut sockets = Vec::new();
+    if let Some(path) = env::var("DOCKER_HOST")
+        .ok()
+        .and_then(|host| host.strip_prefix("unix://").map(PathBuf::from))
+    {
+        sockets.push((ContainerRuntime::Docker, path));
+    }
+    let runtime_dir = env::var_os("XDG_RUNTIME_DIR").map(PathBuf::from);
+    sockets.push((
+        ContainerRuntime::Docker,
+        PathBuf::from("/var/run/docker.sock"),
+    ));
+    if let Some(dir) = &runtime_dir {
+        sockets.push((ContainerRuntime::Docker, dir.join("docker.sock")));
+    }
+    sockets.push((
+        ContainerRuntime::Podman,
+        PathBuf::from("/run/podman/podman.sock"),
+    ));
+    if let Some(dir) = &runtime_dir {
+        sockets.push((ContainerRuntime::Podman, dir.join("podman/podman.sock")));
+    }
+    sockets
+}
+
+/// Running containers known to the daemon behind `socket`
+pub fn list(socket: &Path) -> ScanResult<Vec<ApiContainer>> {
+    let (status, body) = request(socket, "GET", "/containers/json", LIST_TIMEOUT)?;
+    if status != 200 {
+        return Err(api_error(status, &body, "list containers"));
+    }
+    serde_json::from_slice(&body).map_err(|e| {
+        ScanError::new(
+            ScanErrorKind::ParseFailure,
+            format!("Unexpected container list from {}: {}", socket.display(), e),
+        )
+    })
+}
+
+/// Ask the daemon to stop a container: SIGTERM (or the image's StopSignal),
+/// then SIGKILL after `timeout_secs`. Stopping a stopped container is fine.
+pub fn stop(socket: &Path, id: &str, timeout_secs: u32) -> ScanResult<()> {
+    if id.is_empty()
+        || !id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-'))
+    {
+        return Err(ScanError::new(
+            ScanErrorKind::Other,
+            format!("Invalid container id: {}", id),
+        ));
+    }
+    let path = format!("/containers/{}/stop?t={}", id, timeout_secs);
+    let timeout = Duration::from_secs(u64::from(timeout_secs)) + STOP_MARGIN;
+    let (status, body) = request(socket, "POST", &path, timeout)?;
+    match status {
+        // Stopped, or was not running
+        204 | 304 => Ok(()),
+        _ => Err(api_error(status, &body, &format!("stop container {}", id))),
+    }
+}
+
+/// One HTTP/1.0 request, so the daemon answers without chunking and closes
+fn request(
+    socket: &Path,
+    method: &str,
+    path: &str,
+    timeout: Duration,
+) -> ScanResult<(u16, Vec<u8>)> {
+    let mut stream = UnixStream::connect(socket).map_err(|e| connect_error(socket, e))?;
+    stream.set_read_timeout(Some(timeout))?;
+    stream.set_write_timeout(Some(LIST_TIMEOUT))?;
+    write!(
+        stream,
+        "{} {} HTTP/1.0\r\nHost: localhost\r\nContent-Length: 0\r\n\r\n",
+        method, path
+    )?;
+
+    let mut raw = Vec::new();
+    stream.read_to_end(&mut raw)?;
+    parse_response(&raw).ok_or_else(|| {
+        ScanError::new(
+            ScanErrorKind::ParseFailure,
+            format!("Malformed HTTP response from {}", socket.display()),
+        )
+    })
+}
+
+fn connect_error(socket: &Path, err: io::Error) -> ScanError {
+    match err.kind() {
+        io::ErrorKind::NotFound | io::ErrorKind::ConnectionRefused => ScanError::new(
+            ScanErrorKind::ToolMissing,
+            format!("No container engine is listening on {}", socket.display()),
+        ),
+        _ => ScanError::from(err),
+    }
+}
+
+/// Status code and body of a raw HTTP response, de-chunked if needed
+fn parse_response(raw: &[u8]) -> Option<(u16, Vec<u8>)> {
+    let header_end = raw.windows(4).position(|w| w == b"\r\n\r\n")?;
+    let head = std::str::from_utf8(&raw[..header_end]).ok()?;
+    let mut lines = head.split("\r\n");
+    let status = lines.next()?.split_whitespace().nth(1)?.parse().ok()?;
+    let chunked = lines.any(|line| {
+        line.split_once(':').is_some_and(|(name, value)| {
+            name.trim().eq_ignore_ascii_case("transfer-encoding")
+                && value.trim().eq_ignore_ascii_case("chunked")
+        })
+    });
+
+    let body = &raw[header_end + 4..];
+    let body = if chunked {
+        decode_chunked(body)?
+    } else {
+        body.to_vec()
+    };
+    Some((status, body))
+}
+
+fn decode_chunked(mut body: &[u8]) -> Option<Vec<u8>> {
+    let mut decoded = Vec::new();
+    loop {
+        let line_end = body.windows(2).position(|w| w == b"\r\n")?;
+        let size_field = std::str::from_utf8(&body[..line_end]).ok()?;
+        // Chunk extensions (";name=value") carry nothing we need
+        let size_hex = size_field.split(';').next()?.trim();
+        let size = usize::from_str_radix(size_hex, 16).ok()?;
+        body = &body[line_end + 2..];
+        if size == 0 {
+            return Some(decoded);
+        }
+        decoded.extend_from_slice(body.get(..size)?);
+        body = body.get(size + 2..)?;
+    }
+}
+
+/// Turn an error status into a ScanError, using the API's {"message": ...}
+fn api_error(status: u16, body: &[u8], action: &str) -> ScanError {
+    let message = serde_json::from_slice::<serde_json::Value>(body)
+        .ok()
+        .and_then(|value| value.get("message")?.as_str().map(str::to_string))
+        .unwrap_or_else(|| String::from_utf8_lossy(body).trim().to_string());
+    let kind = match status {
+        401 | 403 => ScanErrorKind::PermissionDenied,
+        // The container (and so its processes) is gone
+        404 => ScanErrorKind::ProcessNotFound,
+        _ => ScanErrorKind::CommandFailed,
+    };
+    ScanError::new(
+        kind,
+        format!("Could not {} (HTTP {}): {}", action, status, message),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::net::UnixListener;
+    use std::thread;
+
+    /// Serve one canned response on a fresh socket; the request line comes
+    /// back through the join handle
+    fn stub_daemon(response: &'static str) -> (PathBuf, thread::JoinHandle<String>) {
+        let dir = env::temp_dir().join(format!(
+            "unbind-docker-{}-{:?}",
+            std::process::id(),
+            thread::current().id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("docker.sock");
+        let listener = UnixListener::bind(&path).unwrap();
+
+        let handle = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0u8; 1024];
+            while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                let n = stream.read(&mut buf).unwrap();
+                if n == 0 {
+                    break;
+                }
+                request.extend_from_slice(&buf[..n]);
+            }
+            stream.write_all(response.as_bytes()).unwrap();
+            let request = String::from_utf8_lossy(&request).into_owned();
+            request.lines().next().unwrap_or_default().to_string()
+        });
+        (path, handle)
+    }
+
+    #[test]
+    fn test_list_against_stub() {
+        let (socket, daemon) = stub_daemon(
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nTransfer-Encoding: chunked\r\n\r\n\
+             4f\r\n[{\"Id\":\"3f2a9c\",\"Names\":[\"/web\"],\"Image\":\"nginx:1.27\",\"Ports\":[{\"IP\":\"0.0.0.0\",\r\n\
+             33\r\n\"PrivatePort\":80,\"PublicPort\":8080,\"Type\":\"tcp\"}]}]\r\n\
+             0\r\n\r\n",
+        );
+        let containers = list(&socket).unwrap();
+        assert_eq!(daemon.join().unwrap(), "GET /containers/json HTTP/1.0");
+
+        assert_eq!(containers.len(), 1);
+        let web = &containers[0];
+        assert_eq!(web.id, "3f2a9c");
+        assert_eq!(web.name().as_deref(), Some("web"));
+        assert_eq!(web.image, "nginx:1.27");
+        assert!(web.publishes(8080, "tcp"));
+        assert!(!web.publishes(80, "tcp"));
+        assert!(!web.publishes(8080, "udp"));
+    }
+
+    #[test]
+    fn test_stop_against_stub() {
+        let (socket, daemon) = stub_daemon("HTTP/1.0 204 No Content\r\n\r\n");
+        stop(&socket, "3f2a9c", 5).unwrap();
+        assert_eq!(
+            daemon.join().unwrap(),
+            "POST /containers/3f2a9c/stop?t=5 HTTP/1.0"
+        );
+
+        let (socket, daemon) = stub_daemon(
+            "HTTP/1.0 404 Not Found\r\nContent-Type: application/json\r\n\r\n\
+             {\"message\":\"No such container: 3f2a9c\"}",
+        );
+        let error = stop(&socket, "3f2a9c", 5).unwrap_err();
+        daemon.join().unwrap();
+        assert_eq!(error.kind, ScanErrorKind::ProcessNotFound);
+        assert!(error.message.contains("No such container: 3f2a9c"));
+    }
+
+    #[test]
+    fn test_stop_rejects_odd_ids() {
+        let socket = Path::new("/nonexistent/docker.sock");
+        assert_eq!(
+            stop(socket, "../../info", 5).unwrap_err().kind,
+            ScanErrorKind::Other
+        );
+        assert_eq!(stop(socket, "", 5).unwrap_err().kind, ScanErrorKind::Other);
+        assert_eq!(
+            stop(socket, "3f2a9c", 5).unwrap_err().kind,
+            ScanErrorKind::ToolMissing
+        );
+    }
+
+    #[test]
+    fn test_parse_response() {
+        assert_eq!(
+            parse_response(b"HTTP/1.0 200 OK\r\nContent-Length: 2\r\n\r\n[]"),
+            Some((200, b"[]".to_vec()))
+        );
+        assert_eq!(
+            parse_response(
+                b"HTTP/1.1 200 OK\r\ntransfer-encoding: Chunked\r\n\r\n2;x=y\r\n[]\r\n0\r\n\r\n"
+            ),
+            Some((200, b"[]".to_vec()))
+        );
+        assert_eq!(parse_response(b"HTTP/1.1 200 OK\r\n"), None);
+        // Truncated chunk
+        assert_eq!(
+            parse_response(b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\nff\r\n[]"),
+            None
+        );
+    }
+}
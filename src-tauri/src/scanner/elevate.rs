@@ -0,0 +1,117 @@
+use super::{exec, procfs, ProcessIdentity, ScanError, ScanErrorKind, ScanResult, Signal};
+use std::path::Path;
+use std::process::Command;
+use std::time::Duration;
+
+/// Where the deb/rpm packages install the helper; the polkit action's
+/// exec.path annotation names the same file
+const HELPER_PATH: &str = "/usr/libexec/unbind/unbind-helper";
+
+// Exit codes of src/bin/unbind-helper.rs
+const EXIT_NOT_FOUND: i32 = 3;
+const EXIT_CHANGED: i32 = 4;
+const EXIT_REFUSED: i32 = 5;
+/// pkexec blocks on the polkit password dialog, so allow for a person typing
+const AUTH_TIMEOUT: Duration = Duration::from_secs(120);
+/// pkexec: the user dismissed the dialog or polkit said no
+const PKEXEC_NOT_AUTHORIZED: i32 = 126;
+
+/// Ask the privileged helper to deliver `sig` to `pid`, through pkexec so
+/// polkit prompts for an admin password. The helper re-checks the start time
+/// itself, so the request can't be redirected to a recycled pid.
+pub fn send_signal(pid: u32, sig: Signal, identity: Option<&ProcessIdentity>) -> ScanResult<()> {
+    if !matches!(sig, Signal::Term | Signal::Kill) {
+        return Err(ScanError::new(
+            ScanErrorKind::Other,
+            format!("{} cannot be sent with elevated privileges", sig.name()),
+        )
+        .with_pid(pid));
+    }
+    if !Path::new(HELPER_PATH).is_file() {
+        return Err(ScanError::new(
+            ScanErrorKind::ToolMissing,
+            format!("The privileged helper is not installed ({})", HELPER_PATH),
+        )
+        .with_pid(pid));
+    }
+
+    let start_time = match identity {
+        Some(identity) => identity.start_time,
+        None => procfs::read_stat(pid)?.start_time,
+    };
+
+    let output = exec::output_within(
+        Command::new("pkexec")
+            .arg(HELPER_PATH)
+            .arg("signal")
+            .arg(pid.to_string())
+            .arg(sig.name())
+            .arg(start_time.to_string()),
+        "pkexec",
+        AUTH_TIMEOUT,
+    )?;
+
+    if output.status.success() {
+        return Ok(());
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    Err(helper_error(pid, output.status.code(), &stderr))
+}
+
+fn helper_error(pid: u32, code: Option<i32>, stderr: &str) -> ScanError {
+    let kind = match code {
+        Some(EXIT_NOT_FOUND) => ScanErrorKind::ProcessNotFound,
+        Some(EXIT_CHANGED) => ScanErrorKind::ProcessChanged,
+        Some(EXIT_REFUSED) => ScanErrorKind::Protected,
+        Some(PKEXEC_NOT_AUTHORIZED) => ScanErrorKind::PermissionDenied,
+        // Bad arguments, a failed kill, or pkexec without an agent (127)
+        _ => ScanErrorKind::CommandFailed,
+    };
+    let message = match code {
+        Some(PKEXEC_NOT_AUTHORIZED) => "Authentication was cancelled or denied".to_string(),
+        _ => {
+            let detail = stderr.trim();
+            let detail = detail.strip_prefix("unbind-helper: ").unwrap_or(detail);
+            format!("Privileged signal failed: {}", detail)
+        }
+    };
+    ScanError::new(kind, message)
+        .with_pid(pid)
+        .with_exit_status(code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_helper_error() {
+        let error = helper_error(
+            42,
+            Some(EXIT_CHANGED),
+            "unbind-helper: process 42 is not the one that was scanned\n",
+        );
+        assert_eq!(error.kind, ScanErrorKind::ProcessChanged);
+        assert_eq!(error.exit_status, Some(EXIT_CHANGED));
+        assert_eq!(
+            error.message,
+            "Privileged signal failed: process 42 is not the one that was scanned"
+        );
+
+        let kind = |code| helper_error(42, code, "").kind;
+        assert_eq!(kind(Some(EXIT_NOT_FOUND)), ScanErrorKind::ProcessNotFound);
+        assert_eq!(kind(Some(EXIT_REFUSED)), ScanErrorKind::Protected);
+        assert_eq!(
+            kind(Some(PKEXEC_NOT_AUTHORIZED)),
+            ScanErrorKind::PermissionDenied
+        );
+        assert_eq!(kind(Some(127)), ScanErrorKind::CommandFailed);
+        assert_eq!(kind(None), ScanErrorKind::CommandFailed);
+    }
+
+    #[test]
+    fn test_only_term_and_kill() {
+        let error = send_signal(std::process::id(), Signal::Stop, None).unwrap_err();
+        assert_eq!(error.kind, ScanErrorKind::Other);
+    }
+}
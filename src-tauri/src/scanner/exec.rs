@@ -0,0 +1,134 @@
+use super::{ScanError, ScanErrorKind, ScanResult};
+use std::io::Read;
+use std::process::{Child, Command, Output, Stdio};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// Listing sockets or processes takes well under a second; a tool still
+/// running after this is stuck (a hung NFS mount under lsof, a wedged ss)
+pub const TOOL_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How often to check whether the child has exited
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Like `Command::output` with `TOOL_TIMEOUT`
+pub fn output(command: &mut Command, tool: &str) -> ScanResult<Output> {
+    output_within(command, tool, TOOL_TIMEOUT)
+}
+
+/// Like `Command::output`, but the child is killed if it hasn't exited
+/// within `timeout`, which is then reported as a `Timeout` error
+pub fn output_within(command: &mut Command, tool: &str, timeout: Duration) -> ScanResult<Output> {
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| ScanError::spawn(tool, e))?;
+
+    // Drain both pipes while waiting so a chatty tool can't fill one and block
+    let stdout = drain(child.stdout.take());
+    let stderr = drain(child.stderr.take());
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if Instant::now() >= deadline {
+            kill(&mut child);
+            // The readers finish once the pipes close; nobody waits for them
+            return Err(ScanError::new(
+                ScanErrorKind::Timeout,
+                format!(
+                    "{} did not finish within {:?} and was killed",
+                    tool, timeout
+                ),
+            ));
+        }
+        thread::sleep(POLL_INTERVAL);
+    };
+
+    Ok(Output {
+        status,
+        stdout: collect(stdout),
+        stderr: collect(stderr),
+    })
+}
+
+fn drain(pipe: Option<impl Read + Send + 'static>) -> Option<JoinHandle<Vec<u8>>> {
+    let mut pipe = pipe?;
+    Some(thread::spawn(move || {
+        let mut buffer = Vec::new();
+        let _ = pipe.read_to_end(&mut buffer);
+        buffer
+    }))
+}
+
+fn collect(reader: Option<JoinHandle<Vec<u8>>>) -> Vec<u8> {
+    reader
+        .and_then(|reader| reader.join().ok())
+        .unwrap_or_default()
+}
+
+/// Kill and reap a child that overran its deadline
+fn kill(child: &mut Child) {
+    if let Err(e) = child.kill() {
+        log::warn!("Failed to kill timed out child {}: {}", child.id(), e);
+        return;
+    }
+    let _ = child.wait();
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_output_collects_both_streams() {
+        let output = output(
+            Command::new("sh").args(["-c", "echo out; echo err >&2; exit 3"]),
+            "sh",
+        )
+        .unwrap();
+        assert_eq!(output.status.code(), Some(3));
+        assert_eq!(output.stdout, b"out\n");
+        assert_eq!(output.stderr, b"err\n");
+    }
+
+    #[test]
+    fn test_output_kills_on_timeout() {
+        let started = Instant::now();
+        let error = output_within(
+            Command::new("sleep").arg("30"),
+            "sleep",
+            Duration::from_millis(200),
+        )
+        .unwrap_err();
+
+        assert_eq!(error.kind, ScanErrorKind::Timeout);
+        assert!(error.message.contains("sleep did not finish"));
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_output_large_stdout_does_not_block() {
+        // More than a pipe buffer's worth, so an undrained pipe would deadlock
+        let output = output(
+            Command::new("sh").args(["-c", "head -c 1000000 /dev/zero"]),
+            "sh",
+        )
+        .unwrap();
+        assert_eq!(output.stdout.len(), 1_000_000);
+    }
+
+    #[test]
+    fn test_output_missing_tool() {
+        let error = output(
+            &mut Command::new("unbind-no-such-tool"),
+            "unbind-no-such-tool",
+        )
+        .unwrap_err();
+        assert_eq!(error.kind, ScanErrorKind::ToolMissing);
+    }
+}
@@ -0,0 +1,203 @@
+use super::{
+    kill_process, listeners_on, port_in_use, process_running, protect, protocol_port_owners,
+    scan_listeners, terminate_process, wait_until, FreePortReport, FreedProcess, KillStage,
+    KillStrategy, ScanErrorKind, ScanResult,
+};
+use std::collections::HashMap;
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, TcpListener, UdpSocket};
+use std::time::{Duration, Instant};
+
+/// How long to keep re-scanning for the port after the last signal
+const PORT_RELEASE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Terminate every process holding `port` with `strategy`, then re-scan until
+/// the port is released or the timeout hits. Protected owners are left
+/// alone (and reported) unless `force` is set.
+pub fn free_port(
+    port: u16,
+    protocol: Option<&str>,
+    strategy: &KillStrategy,
+    force: bool,
+) -> ScanResult<FreePortReport> {
+    let started = Instant::now();
+    let poll = Duration::from_millis(strategy.poll_interval_ms.max(10));
+    let owners = protocol_port_owners(&scan_listeners()?, port, protocol);
+    let names: HashMap<u32, String> = owners.iter().map(|o| (o.pid, o.name.clone())).collect();
+
+    let mut stages: HashMap<u32, KillStage> = HashMap::new();
+    let mut errors = Vec::new();
+
+    // Ask every owner at once so their grace periods overlap
+    let mut asked = Vec::new();
+    for owner in &owners {
+        if let Err(e) = protect::check(owner.pid, force) {
+            errors.push(e.with_port(port));
+            continue;
+        }
+        match terminate_process(owner.pid) {
+            Ok(()) => asked.push(owner.pid),
+            Err(e) if e.kind == ScanErrorKind::ProcessNotFound => {
+                stages.insert(owner.pid, KillStage::AlreadyExited);
+            }
+            // Windows console processes refuse a polite taskkill; force it below
+            Err(_) if cfg!(windows) => asked.push(owner.pid),
+            Err(e) => errors.push(e.with_port(port)),
+        }
+    }
+
+    let grace_deadline = started + Duration::from_millis(strategy.grace_period_ms);
+    wait_until(grace_deadline, poll, || {
+        asked.iter().all(|&pid| !process_running(pid))
+    });
+
+    for pid in asked {
+        if !process_running(pid) {
+            stages.insert(pid, KillStage::Terminated);
+            continue;
+        }
+        match kill_process(pid) {
+            Ok(()) => {
+                stages.insert(pid, KillStage::Killed);
+            }
+            Err(e) if e.kind == ScanErrorKind::ProcessNotFound => {
+                stages.insert(pid, KillStage::Terminated);
+            }
+            Err(e) => errors.push(e.with_port(port)),
+        }
+    }
+
+    // Sockets close once the kernel tears the process down; watch the port
+    // itself, which also catches supervisors that respawn the server. Only
+    // the final check resolves owners, to name any survivors.
+    wait_until(Instant::now() + PORT_RELEASE_TIMEOUT, poll, || {
+        port_in_use(port, protocol).is_ok_and(|in_use| !in_use)
+    });
+
+    let (released, survivors) = match scan_listeners() {
+        Ok(ports) => (
+            listeners_on(&ports, port, protocol).next().is_none(),
+            protocol_port_owners(&ports, port, protocol),
+        ),
+        Err(_) => (false, Vec::new()),
+    };
+
+    let mut killed: Vec<FreedProcess> = stages
+        .into_iter()
+        .filter(|&(pid, _)| !process_running(pid))
+        .map(|(pid, stage)| FreedProcess {
+            pid,
+            name: names.get(&pid).cloned().unwrap_or_default(),
+            stage,
+        })
+        .collect();
+    killed.sort_by_key(|freed| freed.pid);
+
+    Ok(FreePortReport {
+        port,
+        protocol: protocol.map(str::to_string),
+        killed,
+        survivors,
+        errors,
+        released,
+        bindable: bindable(port, protocol),
+        elapsed_ms: started.elapsed().as_millis() as u64,
+    })
+}
+
+/// Try to bind the port ourselves. None when we can't tell: a protocol std
+/// can't bind, or a privileged port we aren't allowed to bind anyway.
+fn bindable(port: u16, protocol: Option<&str>) -> Option<bool> {
+    let tcp = || bind_both(|addr| TcpListener::bind((addr, port)).map(drop));
+    let udp = || bind_both(|addr| UdpSocket::bind((addr, port)).map(drop));
+    match protocol {
+        Some("tcp") => tcp(),
+        Some("udp") => udp(),
+        None => match (tcp(), udp()) {
+            (Some(true), Some(true)) => Some(true),
+            (Some(false), _) | (_, Some(false)) => Some(false),
+            _ => None,
+        },
+        Some(_) => None,
+    }
+}
+
+/// Bind the IPv4 and the IPv6 wildcard in turn: a leftover listener on
+/// `[::1]` (Node's `localhost`) doesn't stop a bind on 0.0.0.0, and some
+/// systems keep the two families apart. A host without IPv6 is judged on
+/// IPv4 alone.
+fn bind_both(bind: impl Fn(IpAddr) -> io::Result<()>) -> Option<bool> {
+    let v4 = bind_result(bind(Ipv4Addr::UNSPECIFIED.into()));
+    let v6 = bind_result(bind(Ipv6Addr::UNSPECIFIED.into()));
+    match (v4, v6) {
+        (Some(false), _) | (_, Some(false)) => Some(false),
+        (Some(true), _) => Some(true),
+        (None, _) => None,
+    }
+}
+
+fn bind_result(result: io::Result<()>) -> Option<bool> {
+    match result {
+        Ok(()) => Some(true),
+        Err(e) if e.kind() == io::ErrorKind::AddrInUse => Some(false),
+        Err(_) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bindable() {
+        let listener = TcpListener::bind((Ipv4Addr::UNSPECIFIED, 0)).unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        assert_eq!(bindable(port, Some("tcp")), Some(false));
+        assert_eq!(bindable(port, None), Some(false));
+        assert_eq!(bindable(port, Some("raw")), None);
+
+        drop(listener);
+        assert_eq!(bindable(port, Some("tcp")), Some(true));
+    }
+
+    #[test]
+    fn test_bindable_sees_ipv6_only_listener() {
+        // No IPv6 loopback in this environment
+        let Ok(listener) = TcpListener::bind((Ipv6Addr::LOCALHOST, 0)) else {
+            return;
+        };
+        let port = listener.local_addr().unwrap().port();
+        assert_eq!(bindable(port, Some("tcp")), Some(false));
+
+        drop(listener);
+        assert_eq!(bindable(port, Some("tcp")), Some(true));
+    }
+
+    #[test]
+    fn test_bind_both() {
+        let in_use = || Err(io::Error::from(io::ErrorKind::AddrInUse));
+        let no_ipv6 = |addr: IpAddr| match addr {
+            IpAddr::V4(_) => Ok(()),
+            IpAddr::V6(_) => Err(io::Error::from(io::ErrorKind::AddrNotAvailable)),
+        };
+        let v6_taken = |addr: IpAddr| match addr {
+            IpAddr::V4(_) => Ok(()),
+            IpAddr::V6(_) => Err(io::Error::from(io::ErrorKind::AddrInUse)),
+        };
+
+        assert_eq!(bind_both(|_| Ok(())), Some(true));
+        assert_eq!(bind_both(|_| in_use()), Some(false));
+        assert_eq!(bind_both(v6_taken), Some(false));
+        assert_eq!(bind_both(no_ipv6), Some(true));
+    }
+
+    #[test]
+    fn test_bind_result() {
+        assert_eq!(bind_result(Ok(())), Some(true));
+        let in_use = io::Error::from(io::ErrorKind::AddrInUse);
+        assert_eq!(bind_result(Err(in_use)), Some(false));
+        let denied = io::Error::from(io::ErrorKind::PermissionDenied);
+        assert_eq!(bind_result(Err(denied)), None);
+    }
+}
@@ -1,28 +1,177 @@
-use super::{PortInfo, ScanError, ScanResult};
+use super::backend::{find_in_path, Capabilities, ScanBackend};
+use super::protect::ProcessFacts;
+use super::{
+    exec, listeners_on, netlink, netns, procfs, signal, PortInfo, ProcessOwner, ScanError,
+    ScanErrorKind, ScanResult,
+};
 use std::collections::HashMap;
 use std::fs;
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::path::Path;
 use std::process::Command;
+use std::time::{Duration, Instant};
+
+/// Linux scan backends in default order: native netlink first (no subprocess,
+/// no text parsing), then the ss command, then parsing /proc/net directly
+pub fn backends() -> Vec<Box<dyn ScanBackend>> {
+    vec![
+        Box::new(NetlinkBackend),
+        Box::new(SsBackend),
+        Box::new(ProcBackend),
+    ]
+}
+
+struct NetlinkBackend;
+
+impl ScanBackend for NetlinkBackend {
+    fn name(&self) -> &'static str {
+        "netlink"
+    }
+
+    fn check_available(&self) -> Result<(), String> {
+        netlink::probe().map_err(|e| format!("NETLINK_SOCK_DIAG unavailable: {}", e))
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            pids: true,
+            addresses: true,
+            udp: true,
+        }
+    }
+
+    fn scan(&self) -> ScanResult<Vec<PortInfo>> {
+        scan_with_netlink()
+    }
+
+    fn port_in_use(&self, port: u16, protocol: Option<&str>) -> ScanResult<bool> {
+        Ok(netlink::dump_listening()?
+            .iter()
+            .any(|socket| socket.port == port && protocol.map_or(true, |p| p == socket.protocol)))
+    }
+}
+
+struct SsBackend;
+
+impl ScanBackend for SsBackend {
+    fn name(&self) -> &'static str {
+        "ss"
+    }
+
+    fn check_available(&self) -> Result<(), String> {
+        if find_in_path("ss") {
+            Ok(())
+        } else {
+            Err("ss is not installed (iproute2)".to_string())
+        }
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            pids: true,
+            addresses: true,
+            udp: true,
+        }
+    }
+
+    fn scan(&self) -> ScanResult<Vec<PortInfo>> {
+        scan_with_ss()
+    }
+
+    fn port_in_use(&self, port: u16, protocol: Option<&str>) -> ScanResult<bool> {
+        // Without -p, ss doesn't walk /proc for owners
+        let kinds = match protocol {
+            None => "-tu",
+            Some("tcp") => "-t",
+            Some("udp") => "-u",
+            // ss can't filter on UDP-Lite; let the next backend answer
+            // rather than report the port free
+            Some(other) => {
+                return Err(ScanError::new(
+                    ScanErrorKind::UnsupportedOs,
+                    format!("ss can't check {} ports", other),
+                ))
+            }
+        };
+        let filter = format!(":{}", port);
+        let output = exec::output(
+            Command::new("ss").args(["-H", "-l", "-n", kinds, "sport", "=", &filter]),
+            "ss",
+        )?;
+        if !output.status.success() {
+            return Err(
+                ScanError::new(ScanErrorKind::CommandFailed, "ss command failed")
+                    .with_exit_status(output.status.code()),
+            );
+        }
+        Ok(output.stdout.iter().any(|b| !b.is_ascii_whitespace()))
+    }
+}
+
+struct ProcBackend;
+
+impl ScanBackend for ProcBackend {
+    fn name(&self) -> &'static str {
+        "proc"
+    }
 
-/// Scan listening ports on Linux using /proc filesystem and ss command
-pub fn scan_ports() -> ScanResult<Vec<PortInfo>> {
-    // Try ss command first (more reliable)
-    match scan_with_ss() {
-        Ok(ports) => Ok(ports),
-        Err(_) => {
-            // Fallback to parsing /proc/net directly
-            scan_with_proc()
+    fn check_available(&self) -> Result<(), String> {
+        if Path::new("/proc/net/tcp").exists() {
+            Ok(())
+        } else {
+            Err("/proc/net is not mounted".to_string())
         }
     }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            pids: true,
+            addresses: true,
+            udp: true,
+        }
+    }
+
+    fn scan(&self) -> ScanResult<Vec<PortInfo>> {
+        scan_with_proc()
+    }
+
+    fn port_in_use(&self, port: u16, protocol: Option<&str>) -> ScanResult<bool> {
+        // Owners aren't needed, so skip the /proc/<pid>/fd walk
+        let ports = proc_net_listeners(Path::new("/proc"), &SocketIndex::default());
+        let in_use = listeners_on(&ports, port, protocol).next().is_some();
+        Ok(in_use)
+    }
+}
+
+/// Scan using NETLINK_SOCK_DIAG (inet_diag) and resolve owners through /proc
+fn scan_with_netlink() -> ScanResult<Vec<PortInfo>> {
+    let started = Instant::now();
+    let sockets = netlink::dump_listening()?;
+    let index = SocketIndex::build();
+
+    let mut ports: Vec<PortInfo> = sockets
+        .into_iter()
+        .map(|socket| {
+            let owners = index.lookup(socket.inode);
+            PortInfo::new(socket.port, socket.protocol, socket.address, owners)
+        })
+        .collect();
+    ports.extend(other_namespaces(&index));
+
+    log_scan_timing("netlink scan", started.elapsed(), ports.len());
+    Ok(ports)
 }
 
 /// Scan using ss command
 fn scan_with_ss() -> ScanResult<Vec<PortInfo>> {
-    let output = Command::new("ss")
-        .args(["-tlnp", "-H"]) // TCP, listening, numeric, processes, no header
-        .output()?;
+    // TCP, listening, numeric, processes, no header
+    let output = exec::output(Command::new("ss").args(["-tlnp", "-H"]), "ss")?;
 
     if !output.status.success() {
-        return Err(ScanError::from("ss command failed"));
+        return Err(
+            ScanError::new(ScanErrorKind::CommandFailed, "ss command failed")
+                .with_exit_status(output.status.code()),
+        );
     }
 
     let stdout = String::from_utf8_lossy(&output.stdout);
@@ -35,9 +184,8 @@ fn scan_with_ss() -> ScanResult<Vec<PortInfo>> {
     }
 
     // Also get UDP
-    let udp_output = Command::new("ss")
-        .args(["-ulnp", "-H"]) // UDP, listening, numeric, processes, no header
-        .output()?;
+    // UDP, listening, numeric, processes, no header
+    let udp_output = exec::output(Command::new("ss").args(["-ulnp", "-H"]), "ss")?;
 
     if udp_output.status.success() {
         let udp_stdout = String::from_utf8_lossy(&udp_output.stdout);
@@ -49,6 +197,11 @@ fn scan_with_ss() -> ScanResult<Vec<PortInfo>> {
         }
     }
 
+    // ss resolves owners itself, so the namespace scan needs its own index
+    if netns::enabled() {
+        ports.extend(netns::scan_other_namespaces(&SocketIndex::build()));
+    }
+
     Ok(ports)
 }
 
@@ -64,21 +217,14 @@ fn parse_ss_line(line: &str) -> Option<PortInfo> {
     let local_addr = parts[3];
     let (address, port) = parse_address_port(local_addr)?;
 
-    // Get process info from the last part
-    let (pid, process_name) = if let Some(users_part) = parts.get(5) {
-        parse_users_field(users_part)
+    // Get process info from the rest of the line
+    let owners = if parts.len() > 5 {
+        parse_users_field(&parts[5..].join(" "))
     } else {
-        (0, "unknown".to_string())
+        Vec::new()
     };
 
-    Some(PortInfo {
-        port,
-        pid,
-        process_name,
-        protocol: "tcp".to_string(),
-        local_address: address,
-        state: "LISTEN".to_string(),
-    })
+    Some(PortInfo::new(port, "tcp", address, owners))
 }
 
 /// Parse address:port format
@@ -99,77 +245,120 @@ fn parse_address_port(addr_port: &str) -> Option<(String, u16)> {
     }
 }
 
-/// Parse users field from ss: users:(("process",pid=123,fd=4))
-fn parse_users_field(field: &str) -> (u32, String) {
-    // Extract process name
-    let process_name = field
-        .split("((\"")
-        .nth(1)
-        .and_then(|s| s.split('"').next())
-        .unwrap_or("unknown")
-        .to_string();
-
-    // Extract PID
-    let pid = field
-        .split("pid=")
-        .nth(1)
-        .and_then(|s| s.split(',').next())
-        .and_then(|s| s.split(')').next())
-        .and_then(|s| s.parse().ok())
-        .unwrap_or(0);
-
-    (pid, process_name)
+/// Parse users field from ss: users:(("nginx",pid=100,fd=6),("nginx",pid=101,fd=6))
+/// Forked servers list one tuple per process sharing the socket.
+fn parse_users_field(field: &str) -> Vec<ProcessOwner> {
+    field
+        .split("(\"")
+        .skip(1)
+        .filter_map(|entry| {
+            let (name, rest) = entry.split_once('"')?;
+            let attrs = rest.split(')').next()?;
+
+            let mut pid = None;
+            let mut fd = None;
+            for attr in attrs.split(',') {
+                if let Some(value) = attr.strip_prefix("pid=") {
+                    pid = value.parse().ok();
+                } else if let Some(value) = attr.strip_prefix("fd=") {
+                    fd = value.parse().ok();
+                }
+            }
+
+            Some(ProcessOwner {
+                pid: pid?,
+                name: name.to_string(),
+                fd,
+                usage: None,
+            })
+        })
+        .collect()
 }
 
+/// Tables under <proc root>/net read by the fallback scanner, as (file, protocol).
+/// The v6 tables are reported under the plain protocol name like ss does.
+const PROC_NET_TABLES: [(&str, &str); 8] = [
+    ("tcp", "tcp"),
+    ("tcp6", "tcp"),
+    ("udp", "udp"),
+    ("udp6", "udp"),
+    ("udplite", "udplite"),
+    ("udplite6", "udplite"),
+    ("raw", "raw"),
+    ("raw6", "raw"),
+];
+
+/// __SO_ACCEPTCON: set in /proc/net/unix flags for sockets in listen()
+const UNIX_ACCEPTCON: u32 = 0x0001_0000;
+
 /// Fallback: scan using /proc/net
 fn scan_with_proc() -> ScanResult<Vec<PortInfo>> {
-    let mut ports = Vec::new();
-    let pid_to_name = build_pid_name_map();
+    let started = Instant::now();
+    let index = SocketIndex::build();
+    let mut ports = proc_net_listeners(Path::new("/proc"), &index);
+    ports.extend(other_namespaces(&index));
 
-    // Parse TCP
-    if let Ok(content) = fs::read_to_string("/proc/net/tcp") {
-        for line in content.lines().skip(1) {
-            if let Some(port_info) = parse_proc_net_line(line, "tcp", &pid_to_name) {
-                ports.push(port_info);
-            }
-        }
-    }
+    log_scan_timing("/proc scan", started.elapsed(), ports.len());
+    Ok(ports)
+}
 
-    // Parse TCP6
-    if let Ok(content) = fs::read_to_string("/proc/net/tcp6") {
-        for line in content.lines().skip(1) {
-            if let Some(port_info) = parse_proc_net_line(line, "tcp", &pid_to_name) {
-                ports.push(port_info);
-            }
-        }
+/// Listeners in other network namespaces, if that is enabled, with owners
+/// from the index the host scan already built
+fn other_namespaces(index: &SocketIndex) -> Vec<PortInfo> {
+    if netns::enabled() {
+        netns::scan_other_namespaces(index)
+    } else {
+        Vec::new()
     }
+}
 
-    // Parse UDP
-    if let Ok(content) = fs::read_to_string("/proc/net/udp") {
-        for line in content.lines().skip(1) {
-            if let Some(port_info) = parse_proc_net_line(line, "udp", &pid_to_name) {
-                ports.push(port_info);
+/// Every listener in the net tables under `root`: /proc for our own network
+/// namespace, /proc/<pid> for the namespace that process is in
+pub(super) fn proc_net_listeners(root: &Path, index: &SocketIndex) -> Vec<PortInfo> {
+    let mut ports = Vec::new();
+    for (table, protocol) in PROC_NET_TABLES {
+        if let Ok(content) = fs::read_to_string(root.join("net").join(table)) {
+            for line in content.lines().skip(1) {
+                if let Some(port_info) = parse_proc_net_line(line, protocol, index) {
+                    ports.push(port_info);
+                }
             }
         }
     }
+    ports
+}
 
-    Ok(ports)
+/// Listening unix-domain sockets from /proc/net/unix. Kept out of the port
+/// scan, whatever backend serves it, since they have no port.
+pub fn scan_unix_sockets() -> ScanResult<Vec<PortInfo>> {
+    let index = SocketIndex::build();
+    let content = fs::read_to_string("/proc/net/unix")?;
+    Ok(content
+        .lines()
+        .skip(1)
+        .filter_map(|line| parse_proc_unix_line(line, &index))
+        .collect())
 }
 
-/// Parse a line from /proc/net/tcp or /proc/net/udp
-fn parse_proc_net_line(
-    line: &str,
-    protocol: &str,
-    pid_map: &HashMap<u32, String>,
-) -> Option<PortInfo> {
+/// Hex socket state that counts as "listening" for a /proc/net table.
+/// TCP uses LISTEN (0A); UDP, UDP-Lite and raw sockets never listen, a bound
+/// but unconnected socket sits in CLOSE (07), which ss reports as UNCONN.
+fn listening_state(protocol: &str) -> &'static str {
+    match protocol {
+        "tcp" => "0A",
+        _ => "07",
+    }
+}
+
+/// Parse a line from /proc/net/{tcp,udp,udplite,raw}[6]
+fn parse_proc_net_line(line: &str, protocol: &str, index: &SocketIndex) -> Option<PortInfo> {
     let parts: Vec<&str> = line.split_whitespace().collect();
     if parts.len() < 4 {
         return None;
     }
 
-    // State: 0A = LISTEN
     let state = parts.get(3)?;
-    if *state != "0A" {
+    if *state != listening_state(protocol) {
         return None; // Only listening sockets
     }
 
@@ -180,92 +369,226 @@ fn parse_proc_net_line(
         return None;
     }
 
+    let local_address = decode_proc_address(addr_parts[0])?;
     let port = u16::from_str_radix(addr_parts[1], 16).ok()?;
     let inode = parts.get(9)?.parse::<u64>().ok()?;
 
-    // Find PID by inode
-    let (pid, process_name) = find_pid_by_inode(inode, pid_map);
+    let owners = index.lookup(inode);
 
-    Some(PortInfo {
-        port,
-        pid,
-        process_name,
-        protocol: protocol.to_string(),
-        local_address: "0.0.0.0".to_string(),
-        state: "LISTEN".to_string(),
-    })
+    Some(PortInfo::new(port, protocol, local_address, owners))
 }
 
-/// Build a map of PID to process name
-fn build_pid_name_map() -> HashMap<u32, String> {
-    let mut map = HashMap::new();
-
-    if let Ok(entries) = fs::read_dir("/proc") {
-        for entry in entries.flatten() {
-            let name = entry.file_name();
-            if let Some(name_str) = name.to_str() {
-                if let Ok(pid) = name_str.parse::<u32>() {
-                    let comm_path = format!("/proc/{}/comm", pid);
-                    if let Ok(comm) = fs::read_to_string(&comm_path) {
-                        map.insert(pid, comm.trim().to_string());
-                    }
-                }
-            }
+/// Parse a line from /proc/net/unix, keeping only sockets in listen()
+/// Format: Num RefCount Protocol Flags Type St Inode [Path]
+fn parse_proc_unix_line(line: &str, index: &SocketIndex) -> Option<PortInfo> {
+    let parts: Vec<&str> = line.split_whitespace().collect();
+    if parts.len() < 7 {
+        return None;
+    }
+
+    let flags = u32::from_str_radix(parts[3], 16).ok()?;
+    if flags & UNIX_ACCEPTCON == 0 {
+        return None;
+    }
+
+    let inode = parts[6].parse::<u64>().ok()?;
+    // Unnamed sockets have no path; abstract ones are shown with a leading '@'
+    let path = if parts.len() > 7 {
+        parts[7..].join(" ")
+    } else {
+        "unnamed".to_string()
+    };
+
+    let owners = index.lookup(inode);
+
+    Some(PortInfo::new(0, "unix", path, owners))
+}
+
+/// Decode a /proc/net hex address into the same format ss prints
+/// IPv4: "0100007F" -> "127.0.0.1", IPv6: 32 hex chars -> "[::1]"
+fn decode_proc_address(hex: &str) -> Option<String> {
+    // The kernel prints each 32-bit word of the address as it sits in memory,
+    // so the bytes of every word come back in host (little-endian) order
+    let mut octets = Vec::with_capacity(16);
+    for chunk in hex.as_bytes().chunks(8) {
+        let word = std::str::from_utf8(chunk).ok()?;
+        if word.len() != 8 {
+            return None;
+        }
+        octets.extend_from_slice(&u32::from_str_radix(word, 16).ok()?.to_ne_bytes());
+    }
+
+    match octets.len() {
+        4 => Some(Ipv4Addr::new(octets[0], octets[1], octets[2], octets[3]).to_string()),
+        16 => {
+            let mut bytes = [0u8; 16];
+            bytes.copy_from_slice(&octets);
+            Some(format!("[{}]", Ipv6Addr::from(bytes)))
         }
+        _ => None,
     }
+}
 
-    map
+/// Socket inode -> owning process, built from a single pass over /proc/<pid>/fd
+#[derive(Debug, Default)]
+pub(super) struct SocketIndex {
+    /// inode -> every (pid, fd) holding it
+    sockets: HashMap<u64, Vec<(u32, u32)>>,
+    /// pid -> comm
+    names: HashMap<u32, String>,
 }
 
-/// Find PID by socket inode
-fn find_pid_by_inode(inode: u64, pid_map: &HashMap<u32, String>) -> (u32, String) {
-    let socket_pattern = format!("socket:[{}]", inode);
-
-    if let Ok(entries) = fs::read_dir("/proc") {
-        for entry in entries.flatten() {
-            let name = entry.file_name();
-            if let Some(name_str) = name.to_str() {
-                if let Ok(pid) = name_str.parse::<u32>() {
-                    let fd_path = format!("/proc/{}/fd", pid);
-                    if let Ok(fd_entries) = fs::read_dir(&fd_path) {
-                        for fd_entry in fd_entries.flatten() {
-                            if let Ok(link) = fs::read_link(fd_entry.path()) {
-                                if link.to_string_lossy() == socket_pattern {
-                                    let name = pid_map
-                                        .get(&pid)
-                                        .cloned()
-                                        .unwrap_or_else(|| "unknown".to_string());
-                                    return (pid, name);
-                                }
-                            }
+impl SocketIndex {
+    /// Walk every process once, recording its name and the socket inodes it holds
+    pub(super) fn build() -> Self {
+        let started = Instant::now();
+        let mut index = SocketIndex::default();
+
+        if let Ok(entries) = fs::read_dir("/proc") {
+            for entry in entries.flatten() {
+                let pid: u32 = match entry.file_name().to_str().and_then(|s| s.parse().ok()) {
+                    Some(pid) => pid,
+                    None => continue,
+                };
+
+                if let Ok(comm) = fs::read_to_string(format!("/proc/{}/comm", pid)) {
+                    index.names.insert(pid, comm.trim().to_string());
+                }
+
+                // Unreadable for other users' processes without privileges; skip those
+                let fd_entries = match fs::read_dir(format!("/proc/{}/fd", pid)) {
+                    Ok(fd_entries) => fd_entries,
+                    Err(_) => continue,
+                };
+                for fd_entry in fd_entries.flatten() {
+                    let fd = match fd_entry.file_name().to_str().and_then(|s| s.parse().ok()) {
+                        Some(fd) => fd,
+                        None => continue,
+                    };
+                    if let Ok(link) = fs::read_link(fd_entry.path()) {
+                        if let Some(inode) = parse_socket_link(&link.to_string_lossy()) {
+                            index.sockets.entry(inode).or_default().push((pid, fd));
                         }
                     }
                 }
             }
         }
+
+        log::debug!(
+            "Indexed {} socket inodes across {} processes in {:?}",
+            index.sockets.len(),
+            index.names.len(),
+            started.elapsed()
+        );
+        index
     }
 
-    (0, "unknown".to_string())
+    /// Resolve an inode to every process holding it, ordered by pid so the
+    /// parent of a forked server comes first. Empty when not found.
+    fn lookup(&self, inode: u64) -> Vec<ProcessOwner> {
+        let mut holders = self.sockets.get(&inode).cloned().unwrap_or_default();
+        holders.sort_unstable();
+
+        holders
+            .into_iter()
+            .map(|(pid, fd)| ProcessOwner {
+                pid,
+                name: self
+                    .names
+                    .get(&pid)
+                    .cloned()
+                    .unwrap_or_else(|| "unknown".to_string()),
+                fd: Some(fd),
+                usage: None,
+            })
+            .collect()
+    }
 }
 
-/// Kill a process by PID on Linux
-pub fn kill_process(pid: u32) -> ScanResult<()> {
-    let output = Command::new("kill")
-        .args(["-9", &pid.to_string()])
-        .output()?;
+/// Parse a /proc/<pid>/fd link target of the form socket:[12345]
+fn parse_socket_link(target: &str) -> Option<u64> {
+    target
+        .strip_prefix("socket:[")?
+        .strip_suffix(']')?
+        .parse()
+        .ok()
+}
+
+/// Scans slower than this are logged as warnings so regressions stand out
+const SLOW_SCAN_THRESHOLD: Duration = Duration::from_millis(500);
 
-    if output.status.success() {
-        Ok(())
+fn log_scan_timing(label: &str, elapsed: Duration, count: usize) {
+    if elapsed > SLOW_SCAN_THRESHOLD {
+        log::warn!("{} took {:?} for {} ports", label, elapsed, count);
     } else {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        Err(ScanError::from(format!("Failed to kill process: {}", stderr)))
+        log::debug!("{} took {:?} for {} ports", label, elapsed, count);
     }
 }
 
+/// Kill a process by PID on Linux
+pub fn kill_process(pid: u32) -> ScanResult<()> {
+    signal::send(pid, libc::SIGKILL)
+}
+
+/// Ask a process to exit (SIGTERM)
+pub fn terminate_process(pid: u32) -> ScanResult<()> {
+    signal::send(pid, libc::SIGTERM)
+}
+
+/// Whether a process is still alive; a zombie has exited and released its sockets
+pub fn process_running(pid: u32) -> bool {
+    signal::exists(pid) && procfs::read_stat(pid).is_ok_and(|stat| stat.state != 'Z')
+}
+
+/// What the protection policy checks: name, executable, owner, kernel thread
+pub fn process_facts(pid: u32) -> Option<ProcessFacts> {
+    let stat = procfs::read_stat(pid).ok()?;
+    Some(ProcessFacts {
+        exe: fs::read_link(format!("/proc/{}/exe", pid))
+            .ok()
+            .map(|path| path.to_string_lossy().into_owned()),
+        uid: procfs::read_uid(pid),
+        kernel_thread: stat.is_kernel_thread(),
+        name: stat.comm,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_backends_see_port_in_use() {
+        let listener = std::net::TcpListener::bind(("127.0.0.1", 0)).unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        for backend in backends() {
+            if backend.check_available().is_err() {
+                continue;
+            }
+            let name = backend.name();
+            assert!(backend.port_in_use(port, None).unwrap(), "{}", name);
+            assert!(backend.port_in_use(port, Some("tcp")).unwrap(), "{}", name);
+            assert!(!backend.port_in_use(port, Some("udp")).unwrap(), "{}", name);
+        }
+
+        drop(listener);
+        for backend in backends() {
+            if backend.check_available().is_ok() {
+                assert!(
+                    !backend.port_in_use(port, None).unwrap(),
+                    "{}",
+                    backend.name()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_ss_defers_protocols_it_cannot_check() {
+        assert!(SsBackend.port_in_use(5000, Some("udplite")).is_err());
+    }
+
     #[test]
     fn test_parse_address_port_ipv4() {
         let result = parse_address_port("0.0.0.0:3000");
@@ -316,39 +639,66 @@ mod tests {
 
     #[test]
     fn test_parse_users_field_simple() {
-        let (pid, name) = parse_users_field(r#"users:(("node",pid=1234,fd=3))"#);
-        assert_eq!(pid, 1234);
-        assert_eq!(name, "node");
+        let owners = parse_users_field(r#"users:(("node",pid=1234,fd=3))"#);
+        assert_eq!(owners.len(), 1);
+        assert_eq!(owners[0].pid, 1234);
+        assert_eq!(owners[0].name, "node");
+        assert_eq!(owners[0].fd, Some(3));
     }
 
     #[test]
     fn test_parse_users_field_with_hyphen() {
-        let (pid, name) = parse_users_field(r#"users:(("my-app",pid=5678,fd=4))"#);
-        assert_eq!(pid, 5678);
-        assert_eq!(name, "my-app");
+        let owners = parse_users_field(r#"users:(("my-app",pid=5678,fd=4))"#);
+        assert_eq!(owners[0].pid, 5678);
+        assert_eq!(owners[0].name, "my-app");
     }
 
     #[test]
     fn test_parse_users_field_multiple_processes() {
-        // Takes the first process name
-        let (pid, name) =
+        let owners =
             parse_users_field(r#"users:(("node",pid=1234,fd=3),("worker",pid=1235,fd=4))"#);
-        assert_eq!(pid, 1234);
-        assert_eq!(name, "node");
+        assert_eq!(
+            owners,
+            vec![
+                ProcessOwner {
+                    pid: 1234,
+                    name: "node".to_string(),
+                    fd: Some(3),
+                    usage: None,
+                },
+                ProcessOwner {
+                    pid: 1235,
+                    name: "worker".to_string(),
+                    fd: Some(4),
+                    usage: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_users_field_forked_server() {
+        let owners = parse_users_field(
+            r#"users:(("nginx",pid=2003,fd=6),("nginx",pid=2002,fd=6),("nginx",pid=2001,fd=6))"#,
+        );
+        let pids: Vec<u32> = owners.iter().map(|o| o.pid).collect();
+        assert_eq!(pids, vec![2003, 2002, 2001]);
+        assert!(owners.iter().all(|o| o.name == "nginx"));
     }
 
     #[test]
     fn test_parse_users_field_empty() {
-        let (pid, name) = parse_users_field("");
-        assert_eq!(pid, 0);
-        assert_eq!(name, "unknown");
+        assert!(parse_users_field("").is_empty());
     }
 
     #[test]
     fn test_parse_users_field_malformed() {
-        let (pid, name) = parse_users_field("not_valid_format");
-        assert_eq!(pid, 0);
-        assert_eq!(name, "unknown");
+        assert!(parse_users_field("not_valid_format").is_empty());
+    }
+
+    #[test]
+    fn test_parse_users_field_missing_pid() {
+        assert!(parse_users_field(r#"users:(("node",fd=3))"#).is_empty());
     }
 
     #[test]
@@ -379,6 +729,16 @@ mod tests {
         assert_eq!(info.local_address, "[::]");
     }
 
+    #[test]
+    fn test_parse_ss_line_multiple_owners() {
+        let line = "LISTEN 0 511 0.0.0.0:80 0.0.0.0:* users:((\"nginx\",pid=2002,fd=6),(\"nginx\",pid=2001,fd=6))";
+        let info = parse_ss_line(line).unwrap();
+
+        assert_eq!(info.pid, 2002);
+        assert_eq!(info.owners.len(), 2);
+        assert_eq!(info.owners[1].pid, 2001);
+    }
+
     #[test]
     fn test_parse_ss_line_no_process() {
         // Line without process info (e.g., kernel socket)
@@ -399,6 +759,229 @@ mod tests {
         assert!(result.is_none());
     }
 
+    fn index_with(inode: u64, pid: u32, name: &str) -> SocketIndex {
+        let mut index = SocketIndex::default();
+        index.sockets.insert(inode, vec![(pid, 7)]);
+        index.names.insert(pid, name.to_string());
+        index
+    }
+
+    #[test]
+    fn test_parse_socket_link() {
+        assert_eq!(parse_socket_link("socket:[12345]"), Some(12345));
+        assert_eq!(parse_socket_link("pipe:[12345]"), None);
+        assert_eq!(parse_socket_link("/dev/null"), None);
+        assert_eq!(parse_socket_link("socket:[abc]"), None);
+    }
+
+    #[test]
+    fn test_socket_index_lookup() {
+        let index = index_with(4242, 1234, "node");
+        assert_eq!(
+            index.lookup(4242),
+            vec![ProcessOwner {
+                pid: 1234,
+                name: "node".to_string(),
+                fd: Some(7),
+                usage: None,
+            }]
+        );
+        assert!(index.lookup(1).is_empty());
+    }
+
+    #[test]
+    fn test_socket_index_lookup_shared_socket() {
+        let mut index = index_with(4242, 2002, "gunicorn");
+        index.names.insert(2001, "gunicorn".to_string());
+        index.sockets.insert(4242, vec![(2002, 5), (2001, 5)]);
+
+        let pids: Vec<u32> = index.lookup(4242).iter().map(|o| o.pid).collect();
+        assert_eq!(pids, vec![2001, 2002]);
+    }
+
+    #[test]
+    fn test_parse_proc_net_line_listen() {
+        let index = index_with(4242, 1234, "node");
+        let line = "   0: 00000000:0BB8 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 4242 1 0000000000000000 100 0 0 10 0";
+        let info = parse_proc_net_line(line, "tcp", &index).unwrap();
+
+        assert_eq!(info.port, 3000);
+        assert_eq!(info.pid, 1234);
+        assert_eq!(info.process_name, "node");
+        assert_eq!(info.protocol, "tcp");
+        assert_eq!(info.local_address, "0.0.0.0");
+    }
+
+    #[test]
+    fn test_parse_proc_net_line_loopback_v6() {
+        let index = SocketIndex::default();
+        let line = "   0: 00000000000000000000000001000000:1F90 00000000000000000000000000000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 99 1 0000000000000000 100 0 0 10 0";
+        let info = parse_proc_net_line(line, "tcp", &index).unwrap();
+
+        assert_eq!(info.port, 8080);
+        assert_eq!(info.local_address, "[::1]");
+        assert_eq!(info.pid, 0);
+    }
+
+    #[test]
+    fn test_parse_proc_net_line_udp_unconnected() {
+        let index = index_with(5353, 800, "avahi-daemon");
+        let line = "  100: 00000000:14E9 00000000:0000 07 00000000:00000000 00:00000000 00000000   107        0 5353 2 0000000000000000 0";
+        let info = parse_proc_net_line(line, "udp", &index).unwrap();
+
+        assert_eq!(info.port, 5353);
+        assert_eq!(info.protocol, "udp");
+        assert_eq!(info.process_name, "avahi-daemon");
+        assert_eq!(info.state, "LISTEN");
+    }
+
+    #[test]
+    fn test_parse_proc_net_line_udp_connected_skipped() {
+        let index = SocketIndex::default();
+        let line = "  101: 0100007F:9C40 0100007F:0035 01 00000000:00000000 00:00000000 00000000  1000        0 6000 2 0000000000000000 0";
+        assert!(parse_proc_net_line(line, "udp", &index).is_none());
+    }
+
+    #[test]
+    fn test_parse_proc_net_line_tcp_close_skipped() {
+        let index = SocketIndex::default();
+        let line = "   2: 00000000:0BB8 00000000:0000 07 00000000:00000000 00:00000000 00000000  1000        0 4244 1 0000000000000000 100 0 0 10 0";
+        assert!(parse_proc_net_line(line, "tcp", &index).is_none());
+    }
+
+    #[test]
+    fn test_parse_proc_net_line_raw() {
+        let index = SocketIndex::default();
+        let line = "    1: 00000000000000000000000000000000:003A 00000000000000000000000000000000:0000 07 00000000:00000000 00:00000000 00000000     0        0 7001 2 0000000000000000 0";
+        let info = parse_proc_net_line(line, "raw", &index).unwrap();
+
+        // For raw sockets the "port" column holds the IP protocol (58 = ICMPv6)
+        assert_eq!(info.port, 58);
+        assert_eq!(info.protocol, "raw");
+        assert_eq!(info.local_address, "[::]");
+    }
+
+    #[test]
+    fn test_proc_net_listeners_reads_under_root() {
+        let root = std::env::temp_dir().join(format!("unbind-procroot-{}", std::process::id()));
+        fs::create_dir_all(root.join("net")).unwrap();
+        fs::write(
+            root.join("net/tcp"),
+            "  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode\n   0: 0100007F:1F90 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 4242 1 0000000000000000 100 0 0 10 0\n",
+        )
+        .unwrap();
+
+        let index = index_with(4242, 1234, "caddy");
+        let ports = proc_net_listeners(&root, &index);
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(ports.len(), 1);
+        assert_eq!(ports[0].port, 8080);
+        assert_eq!(ports[0].local_address, "127.0.0.1");
+        assert_eq!(ports[0].process_name, "caddy");
+    }
+
+    #[test]
+    fn test_parse_proc_unix_line_listening() {
+        let index = index_with(23456, 900, "dockerd");
+        let line = "0000000000000000: 00000002 00000000 00010000 0001 01 23456 /run/docker.sock";
+        let info = parse_proc_unix_line(line, &index).unwrap();
+
+        assert_eq!(info.port, 0);
+        assert_eq!(info.pid, 900);
+        assert_eq!(info.process_name, "dockerd");
+        assert_eq!(info.protocol, "unix");
+        assert_eq!(info.local_address, "/run/docker.sock");
+    }
+
+    #[test]
+    fn test_parse_proc_unix_line_abstract() {
+        let index = SocketIndex::default();
+        let line = "0000000000000000: 00000002 00000000 00010000 0001 01 777 @/tmp/.X11-unix/X0";
+        let info = parse_proc_unix_line(line, &index).unwrap();
+        assert_eq!(info.local_address, "@/tmp/.X11-unix/X0");
+    }
+
+    #[test]
+    fn test_parse_proc_unix_line_connected_skipped() {
+        let index = SocketIndex::default();
+        let line = "0000000000000000: 00000003 00000000 00000000 0001 03 24000 /run/systemd/journal/stdout";
+        assert!(parse_proc_unix_line(line, &index).is_none());
+    }
+
+    #[test]
+    fn test_parse_proc_unix_line_header_skipped() {
+        let index = SocketIndex::default();
+        let line = "Num       RefCount Protocol Flags    Type St Inode Path";
+        assert!(parse_proc_unix_line(line, &index).is_none());
+    }
+
+    #[test]
+    fn test_decode_proc_address_ipv4() {
+        assert_eq!(decode_proc_address("00000000").as_deref(), Some("0.0.0.0"));
+        assert_eq!(
+            decode_proc_address("0100007F").as_deref(),
+            Some("127.0.0.1")
+        );
+        assert_eq!(
+            decode_proc_address("0101A8C0").as_deref(),
+            Some("192.168.1.1")
+        );
+    }
+
+    #[test]
+    fn test_decode_proc_address_ipv6() {
+        assert_eq!(
+            decode_proc_address("00000000000000000000000000000000").as_deref(),
+            Some("[::]")
+        );
+        assert_eq!(
+            decode_proc_address("00000000000000000000000001000000").as_deref(),
+            Some("[::1]")
+        );
+        assert_eq!(
+            decode_proc_address("000080FE00000000FF0F7F6B3A6E9DFE").as_deref(),
+            Some("[fe80::6b7f:fff:fe9d:6e3a]")
+        );
+    }
+
+    #[test]
+    fn test_decode_proc_address_ipv4_mapped() {
+        assert_eq!(
+            decode_proc_address("0000000000000000FFFF00000100007F").as_deref(),
+            Some("[::ffff:127.0.0.1]")
+        );
+    }
+
+    #[test]
+    fn test_decode_proc_address_invalid() {
+        assert!(decode_proc_address("").is_none());
+        assert!(decode_proc_address("0100007").is_none());
+        assert!(decode_proc_address("ZZZZZZZZ").is_none());
+        assert!(decode_proc_address("0100007F00").is_none());
+    }
+
+    #[test]
+    fn test_parse_proc_net_line_not_listening() {
+        let index = SocketIndex::default();
+        let line = "   1: 0100007F:0BB8 0100007F:D431 01 00000000:00000000 00:00000000 00000000  1000        0 4243 1 0000000000000000 20 4 30 10 -1";
+        assert!(parse_proc_net_line(line, "tcp", &index).is_none());
+    }
+
+    #[test]
+    fn test_socket_index_finds_own_listener() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let index = SocketIndex::build();
+        let own_pid = std::process::id();
+
+        assert!(index
+            .sockets
+            .values()
+            .flatten()
+            .any(|&(pid, _)| pid == own_pid));
+        drop(listener);
+    }
+
     #[test]
     fn test_parse_ss_line_empty() {
         let result = parse_ss_line("");
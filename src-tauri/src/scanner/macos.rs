@@ -1,6 +1,45 @@
-use super::{PortInfo, ScanError, ScanResult};
+use super::backend::{find_in_path, Capabilities, ScanBackend};
+use super::project::ProcessContext;
+use super::protect::ProcessFacts;
+use super::{exec, signal, PortInfo, ProcessOwner, ProcessState, ScanResult};
+use std::collections::HashMap;
+use std::ffi::CStr;
+use std::path::PathBuf;
 use std::process::Command;
 
+/// macOS has a single scan backend: lsof
+pub fn backends() -> Vec<Box<dyn ScanBackend>> {
+    vec![Box::new(LsofBackend)]
+}
+
+struct LsofBackend;
+
+impl ScanBackend for LsofBackend {
+    fn name(&self) -> &'static str {
+        "lsof"
+    }
+
+    fn check_available(&self) -> Result<(), String> {
+        if find_in_path("lsof") {
+            Ok(())
+        } else {
+            Err("lsof is not installed".to_string())
+        }
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            pids: true,
+            addresses: true,
+            udp: true,
+        }
+    }
+
+    fn scan(&self) -> ScanResult<Vec<PortInfo>> {
+        scan_ports()
+    }
+}
+
 /// Scan listening ports on macOS using lsof
 pub fn scan_ports() -> ScanResult<Vec<PortInfo>> {
     // Use lsof to list all listening TCP and UDP ports
@@ -8,9 +47,10 @@ pub fn scan_ports() -> ScanResult<Vec<PortInfo>> {
     // -P = inhibit port number to port name conversion
     // -n = inhibit host name conversion
     // -sTCP:LISTEN = only TCP in LISTEN state
-    let tcp_output = Command::new("lsof")
-        .args(["-i", "-P", "-n", "-sTCP:LISTEN"])
-        .output()?;
+    let tcp_output = exec::output(
+        Command::new("lsof").args(["-i", "-P", "-n", "-sTCP:LISTEN"]),
+        "lsof",
+    )?;
 
     let mut ports = Vec::new();
 
@@ -25,9 +65,7 @@ pub fn scan_ports() -> ScanResult<Vec<PortInfo>> {
     }
 
     // Also get UDP listeners
-    let udp_output = Command::new("lsof")
-        .args(["-i", "UDP", "-P", "-n"])
-        .output()?;
+    let udp_output = exec::output(Command::new("lsof").args(["-i", "UDP", "-P", "-n"]), "lsof")?;
 
     if udp_output.status.success() {
         let stdout = String::from_utf8_lossy(&udp_output.stdout);
@@ -38,9 +76,20 @@ pub fn scan_ports() -> ScanResult<Vec<PortInfo>> {
         }
     }
 
-    // Deduplicate by port
+    // lsof prints one row per (process, fd): merge rows for the same port,
+    // keeping every owning process (forked servers share one listener)
     ports.sort_by_key(|p| p.port);
-    ports.dedup_by_key(|p| p.port);
+    ports.dedup_by(|dup, kept| {
+        if dup.port != kept.port {
+            return false;
+        }
+        for owner in dup.owners.drain(..) {
+            if !kept.owners.contains(&owner) {
+                kept.owners.push(owner);
+            }
+        }
+        true
+    });
 
     Ok(ports)
 }
@@ -56,6 +105,11 @@ fn parse_lsof_line(line: &str, protocol: &str) -> Option<PortInfo> {
 
     let process_name = parts[0].to_string();
     let pid: u32 = parts[1].parse().ok()?;
+    // FD column looks like "22u": descriptor number plus access mode
+    let fd = parts[3]
+        .trim_end_matches(|c: char| !c.is_ascii_digit())
+        .parse()
+        .ok();
 
     // The NAME field contains the address and port
     // Format: *:port or localhost:port or IP:port
@@ -70,14 +124,14 @@ fn parse_lsof_line(line: &str, protocol: &str) -> Option<PortInfo> {
     // Extract address
     let local_address = extract_address_from_name(name_clean);
 
-    Some(PortInfo {
-        port,
+    let owner = ProcessOwner {
         pid,
-        process_name,
-        protocol: protocol.to_string(),
-        local_address,
-        state: "LISTEN".to_string(),
-    })
+        name: process_name,
+        fd,
+        usage: None,
+    };
+
+    Some(PortInfo::new(port, protocol, local_address, vec![owner]))
 }
 
 /// Extract port from lsof NAME field
@@ -118,17 +172,173 @@ fn extract_address_from_name(name: &str) -> String {
 
 /// Kill a process by PID on macOS
 pub fn kill_process(pid: u32) -> ScanResult<()> {
-    let output = Command::new("kill")
-        .args(["-9", &pid.to_string()])
-        .output()?;
-
-    if output.status.success() {
-        Ok(())
-    } else {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        Err(ScanError::from(format!(
-            "Failed to kill process: {}",
-            stderr
-        )))
+    signal::send(pid, libc::SIGKILL)
+}
+
+/// Ask a process to exit (SIGTERM)
+pub fn terminate_process(pid: u32) -> ScanResult<()> {
+    signal::send(pid, libc::SIGTERM)
+}
+
+pub fn process_running(pid: u32) -> bool {
+    signal::exists(pid)
+}
+
+/// Run state of each pid from `ps -o stat`; pids that exited are left out
+pub fn process_states(pids: &[u32]) -> HashMap<u32, ProcessState> {
+    let list: Vec<String> = pids
+        .iter()
+        .filter(|&&pid| pid != 0)
+        .map(|pid| pid.to_string())
+        .collect();
+    if list.is_empty() {
+        return HashMap::new();
     }
+
+    let output = match exec::output(
+        Command::new("ps").args(["-o", "pid=,stat=", "-p", &list.join(",")]),
+        "ps",
+    ) {
+        Ok(output) => output,
+        Err(_) => return HashMap::new(),
+    };
+
+    // Each line: "  4242 Ss" (state letter followed by modifier flags)
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.split_whitespace();
+            let pid = parts.next()?.parse().ok()?;
+            let code = parts.next()?.chars().next()?;
+            Some((pid, ProcessState::from_code(code)))
+        })
+        .collect()
+}
+
+/// Start time of a process in microseconds since the epoch, for identity tokens
+pub fn process_start_time(pid: u32) -> Option<u64> {
+    let target = libc::c_int::try_from(pid).ok().filter(|&pid| pid > 0)?;
+    // SAFETY: proc_bsdinfo is plain data; proc_pidinfo writes at most `size` bytes into it
+    let mut info: libc::proc_bsdinfo = unsafe { std::mem::zeroed() };
+    let size = std::mem::size_of::<libc::proc_bsdinfo>() as libc::c_int;
+    let written = unsafe {
+        libc::proc_pidinfo(
+            target,
+            libc::PROC_PIDTBSDINFO,
+            0,
+            &mut info as *mut libc::proc_bsdinfo as *mut libc::c_void,
+            size,
+        )
+    };
+    if written != size {
+        return None;
+    }
+    Some(info.pbi_start_tvsec * 1_000_000 + info.pbi_start_tvusec)
+}
+
+/// What the protection policy checks, from `ps` (comm is the full path on macOS)
+pub fn process_facts(pid: u32) -> Option<ProcessFacts> {
+    let output = exec::output(
+        Command::new("ps").args(["-o", "uid=,comm=", "-p", &pid.to_string()]),
+        "ps",
+    )
+    .ok()?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let (uid, comm) = stdout.trim().split_once(char::is_whitespace)?;
+    let comm = comm.trim();
+
+    Some(ProcessFacts {
+        name: comm.rsplit('/').next().unwrap_or(comm).to_string(),
+        exe: comm.starts_with('/').then(|| comm.to_string()),
+        uid: uid.parse().ok(),
+        kernel_thread: false,
+    })
+}
+
+/// Working directory and argv of a process, for project detection. Both
+/// are only readable for the user's own processes unless running as root.
+pub fn process_context(pid: u32) -> Option<ProcessContext> {
+    let target = libc::c_int::try_from(pid).ok().filter(|&pid| pid > 0)?;
+    Some(ProcessContext {
+        cwd: process_cwd(target),
+        argv: process_argv(target),
+    })
+}
+
+fn process_cwd(pid: libc::c_int) -> Option<PathBuf> {
+    // SAFETY: proc_vnodepathinfo is plain data; proc_pidinfo writes at most `size` bytes into it
+    let mut info: libc::proc_vnodepathinfo = unsafe { std::mem::zeroed() };
+    let size = std::mem::size_of::<libc::proc_vnodepathinfo>() as libc::c_int;
+    let written = unsafe {
+        libc::proc_pidinfo(
+            pid,
+            libc::PROC_PIDVNODEPATHINFO,
+            0,
+            &mut info as *mut libc::proc_vnodepathinfo as *mut libc::c_void,
+            size,
+        )
+    };
+    if written != size {
+        return None;
+    }
+    // vip_path is a MAXPATHLEN buffer split into rows for old compilers
+    let path = &info.pvi_cdir.vip_path;
+    let bytes: Vec<u8> = path.iter().flatten().map(|&c| c as u8).collect();
+    let path = CStr::from_bytes_until_nul(&bytes).ok()?.to_str().ok()?;
+    (!path.is_empty()).then(|| PathBuf::from(path))
+}
+
+/// argv from sysctl KERN_PROCARGS2; empty when the process can't be read
+fn process_argv(pid: libc::c_int) -> Vec<String> {
+    let mut mib = [libc::CTL_KERN, libc::KERN_PROCARGS2, pid];
+    let mut size: libc::size_t = 0;
+    // SAFETY: a null buffer asks sysctl for the size only
+    let sized = unsafe {
+        libc::sysctl(
+            mib.as_mut_ptr(),
+            mib.len() as libc::c_uint,
+            std::ptr::null_mut(),
+            &mut size,
+            std::ptr::null_mut(),
+            0,
+        )
+    };
+    if sized != 0 || size == 0 {
+        return Vec::new();
+    }
+    let mut buffer = vec![0u8; size];
+    // SAFETY: sysctl writes at most `size` bytes into the buffer and updates `size`
+    let read = unsafe {
+        libc::sysctl(
+            mib.as_mut_ptr(),
+            mib.len() as libc::c_uint,
+            buffer.as_mut_ptr() as *mut libc::c_void,
+            &mut size,
+            std::ptr::null_mut(),
+            0,
+        )
+    };
+    if read != 0 {
+        return Vec::new();
+    }
+    buffer.truncate(size);
+    parse_procargs(&buffer).unwrap_or_default()
+}
+
+/// KERN_PROCARGS2 layout: argc as a native int, the exec path, NUL padding,
+/// then argc NUL-terminated arguments followed by the environment
+fn parse_procargs(buffer: &[u8]) -> Option<Vec<String>> {
+    let argc_bytes: [u8; 4] = buffer.get(..4)?.try_into().ok()?;
+    let argc = usize::try_from(i32::from_ne_bytes(argc_bytes)).ok()?;
+    let rest = buffer.get(4..)?;
+    let exec_end = rest.iter().position(|&b| b == 0)?;
+    let args_start = exec_end + rest[exec_end..].iter().position(|&b| b != 0)?;
+
+    Some(
+        rest[args_start..]
+            .split(|&b| b == 0)
+            .take(argc)
+            .map(|arg| String::from_utf8_lossy(arg).into_owned())
+            .collect(),
+    )
 }
@@ -1,69 +1,1272 @@
 use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::thread;
+use std::time::{Duration, Instant};
 
+pub use backend::{BackendInfo, ScanDiagnostics};
+pub use settings::ScannerSettings;
+
+mod backend;
+mod batch;
+mod builtin;
+mod coalesce;
+#[cfg(target_os = "linux")]
+mod container;
+#[cfg(target_os = "linux")]
+mod docker;
+#[cfg(target_os = "linux")]
+mod elevate;
+mod exec;
+mod free;
 #[cfg(target_os = "linux")]
 mod linux;
 #[cfg(target_os = "macos")]
 mod macos;
+#[cfg(target_os = "linux")]
+mod netlink;
+#[cfg(target_os = "linux")]
+mod netns;
+#[cfg(target_os = "linux")]
+mod procfs;
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+mod project;
+mod protect;
+mod services;
+mod settings;
+#[cfg(unix)]
+mod signal;
+#[cfg(target_os = "linux")]
+mod systemd;
+#[cfg(target_os = "linux")]
+mod tree;
+#[cfg(target_os = "linux")]
+mod usage;
 #[cfg(target_os = "windows")]
 mod windows;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PortInfo {
     pub port: u16,
+    /// First owning process, kept for callers that only handle one
     pub pid: u32,
     pub process_name: String,
     pub protocol: String,
     pub local_address: String,
     pub state: String,
+    /// Every process holding the socket (forked servers share one listener)
+    #[serde(default)]
+    pub owners: Vec<ProcessOwner>,
+    /// Token to pass back to kill commands so a reused pid is refused
+    #[serde(default)]
+    pub identity: Option<ProcessIdentity>,
+    /// Run state of `pid` (Linux and macOS)
+    #[serde(default)]
+    pub process_state: Option<ProcessState>,
+    /// Resource usage of `pid`, sampled with the scan (Linux only)
+    #[serde(default)]
+    pub usage: Option<ProcessUsage>,
+    /// The systemd unit that will restart `pid` if it is killed (Linux only)
+    #[serde(default)]
+    pub systemd_unit: Option<SystemdUnit>,
+    /// The container `pid` runs in or forwards a published port for (Linux only)
+    #[serde(default)]
+    pub container: Option<ContainerInfo>,
+    /// The network namespace the socket lives in, None for the one unbind
+    /// runs in (Linux, with namespace scanning enabled)
+    #[serde(default)]
+    pub net_namespace: Option<NetNamespace>,
+    /// The development project `pid` was started from (Linux and macOS)
+    #[serde(default)]
+    pub project: Option<DevProject>,
+    /// What usually listens on this port, from the built-in registry or the
+    /// user's overrides file
+    #[serde(default)]
+    pub service_hint: Option<ServiceHint>,
+}
+
+impl PortInfo {
+    /// Build a listening entry, taking pid/process_name from the first owner
+    pub fn new(
+        port: u16,
+        protocol: &str,
+        local_address: String,
+        owners: Vec<ProcessOwner>,
+    ) -> Self {
+        let (pid, process_name) = owners
+            .first()
+            .map(|owner| (owner.pid, owner.name.clone()))
+            .unwrap_or_else(|| (0, "unknown".to_string()));
+
+        PortInfo {
+            port,
+            pid,
+            process_name,
+            protocol: protocol.to_string(),
+            local_address,
+            state: "LISTEN".to_string(),
+            owners,
+            identity: None,
+            process_state: None,
+            usage: None,
+            systemd_unit: None,
+            container: None,
+            net_namespace: None,
+            project: None,
+            service_hint: None,
+        }
+    }
+}
+
+/// A pid pinned to one process instance by its start time. Start times are
+/// only comparable on the same machine and boot: clock ticks since boot on
+/// Linux, microseconds since the epoch on macOS.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProcessIdentity {
+    pub pid: u32,
+    pub start_time: u64,
+}
+
+/// Run state of a process, e.g. `stopped` after SIGSTOP
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProcessState {
+    Running,
+    Sleeping,
+    /// Uninterruptible wait, usually on I/O
+    DiskSleep,
+    /// Suspended by SIGSTOP or a debugger
+    Stopped,
+    Zombie,
+    Idle,
+    Unknown,
+}
+
+impl ProcessState {
+    /// Decode the state letter shared by /proc/<pid>/stat and `ps -o stat`
+    pub fn from_code(code: char) -> Self {
+        match code {
+            'R' => ProcessState::Running,
+            'S' => ProcessState::Sleeping,
+            'D' | 'U' => ProcessState::DiskSleep,
+            'T' | 't' => ProcessState::Stopped,
+            'Z' => ProcessState::Zombie,
+            'I' => ProcessState::Idle,
+            _ => ProcessState::Unknown,
+        }
+    }
+}
+
+/// A signal to deliver, named as on unix. Windows only has `SIGTERM`
+/// (close request) and `SIGKILL` (forced termination).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Signal {
+    #[serde(rename = "SIGHUP")]
+    Hup,
+    #[serde(rename = "SIGINT")]
+    Int,
+    #[serde(rename = "SIGQUIT")]
+    Quit,
+    #[serde(rename = "SIGTERM")]
+    Term,
+    #[serde(rename = "SIGKILL")]
+    Kill,
+    #[serde(rename = "SIGUSR1")]
+    Usr1,
+    #[serde(rename = "SIGUSR2")]
+    Usr2,
+    #[serde(rename = "SIGSTOP")]
+    Stop,
+    #[serde(rename = "SIGCONT")]
+    Cont,
+}
+
+impl Signal {
+    pub fn name(self) -> &'static str {
+        match self {
+            Signal::Hup => "SIGHUP",
+            Signal::Int => "SIGINT",
+            Signal::Quit => "SIGQUIT",
+            Signal::Term => "SIGTERM",
+            Signal::Kill => "SIGKILL",
+            Signal::Usr1 => "SIGUSR1",
+            Signal::Usr2 => "SIGUSR2",
+            Signal::Stop => "SIGSTOP",
+            Signal::Cont => "SIGCONT",
+        }
+    }
+}
+
+/// A container behind a listener: the process runs inside it, or is the
+/// proxy (docker-proxy, rootlessport, conmon) holding its published port
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContainerInfo {
+    /// Full container id
+    pub id: String,
+    /// Names and images come from the engine API, when it is reachable
+    pub name: Option<String>,
+    pub image: Option<String>,
+    pub runtime: ContainerRuntime,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ContainerRuntime {
+    Docker,
+    Podman,
+    Containerd,
+}
+
+/// A systemd unit behind a listener: the service a process belongs to, or
+/// for a socket-activated port still held by systemd, the `.socket` unit
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SystemdUnit {
+    /// Full unit name, e.g. "nginx.service" or "cups.socket"
+    pub name: String,
+    /// uid of the user manager running it (`systemctl --user`), None for
+    /// system units
+    pub user: Option<u32>,
+}
+
+/// A network namespace other than unbind's own, e.g. one made by `ip netns
+/// add`, a rootless container's, or a VPN client's
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NetNamespace {
+    /// Inode of the namespace, as shown in `/proc/<pid>/ns/net` ("net:[inode]")
+    pub inode: u64,
+    /// Name under /run/netns, for namespaces managed with `ip netns`
+    pub name: Option<String>,
+    /// A process inside the namespace, through which its sockets were read
+    pub pid: u32,
+}
+
+/// The project a listener was started from, found by walking up from its
+/// argv and cwd to the nearest package.json, Cargo.toml, go.mod, .git...
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DevProject {
+    /// From the manifest (package.json "name", [package] name...), else the
+    /// root directory's name
+    pub name: String,
+    /// Absolute path of the project root
+    pub root: String,
+    /// Checked-out branch, or the short commit for a detached HEAD
+    pub git_branch: Option<String>,
+    /// Dev server or tool, e.g. "Vite", "Next.js", "cargo run", "uvicorn"
+    pub framework: Option<String>,
+}
+
+/// Resource usage of a process at scan time
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProcessUsage {
+    /// Share of one CPU since the previous scan; None on the first sighting
+    pub cpu_percent: Option<f64>,
+    pub rss_bytes: u64,
+    pub threads: u32,
+    /// None when we may not list the process's descriptors
+    pub open_fds: Option<u32>,
+}
+
+/// A process holding a socket, with the descriptor it holds it through
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProcessOwner {
+    pub pid: u32,
+    pub name: String,
+    pub fd: Option<u32>,
+    /// Resource usage of this process, sampled with the scan (Linux only)
+    #[serde(default)]
+    pub usage: Option<ProcessUsage>,
+}
+
+/// What went wrong, serialized as a stable snake_case code the frontend can
+/// switch on (e.g. offer "retry with elevation" for `permission_denied`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScanErrorKind {
+    /// Not allowed to inspect or signal the target (EPERM/EACCES)
+    PermissionDenied,
+    /// The process exited before we could act on it (ESRCH)
+    ProcessNotFound,
+    /// The pid now belongs to a different process than the one scanned
+    ProcessChanged,
+    /// Refused by the protection policy; retry with `force` to override
+    Protected,
+    /// A required external tool (ss, lsof, netstat...) is not installed
+    ToolMissing,
+    /// An external tool ran but exited with a failure status
+    CommandFailed,
+    /// An external tool hung and was killed
+    Timeout,
+    /// Output from the OS or a tool could not be understood
+    ParseFailure,
+    /// No scanner for this operating system
+    UnsupportedOs,
+    /// Any other I/O error
+    Io,
+    Other,
+}
+
+/// Everything we know about one process, for the detail view
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProcessDetails {
+    pub pid: u32,
+    /// Short name (15-char comm on Linux)
+    pub name: String,
+    /// Full argv
+    pub cmdline: Vec<String>,
+    /// Executable path
+    pub exe: Option<String>,
+    /// Working directory
+    pub cwd: Option<String>,
+    pub uid: Option<u32>,
+    pub user: Option<String>,
+    pub ppid: Option<u32>,
+    /// Start time in Unix epoch milliseconds
+    pub start_time_ms: Option<u64>,
+}
+
+/// One process in a tree, with its children (empty for ancestors)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProcessNode {
+    pub pid: u32,
+    pub ppid: u32,
+    pub pgrp: u32,
+    pub name: String,
+    pub children: Vec<ProcessNode>,
+}
+
+/// A process with its parent chain (nearest first) and everything below it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProcessTree {
+    pub ancestors: Vec<ProcessNode>,
+    pub root: ProcessNode,
+}
+
+/// What "kill the whole tree" covers
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TreeKillMode {
+    /// The process and all its descendants
+    #[default]
+    Subtree,
+    /// Every process in its process group (e.g. everything `npm run dev` started)
+    ProcessGroup,
 }
 
+/// Result of signalling one process during a tree kill
 #[derive(Debug, Serialize, Deserialize)]
+pub struct KillOutcome {
+    pub pid: u32,
+    pub name: String,
+    pub killed: bool,
+    pub error: Option<ScanError>,
+}
+
+/// How long a graceful kill waits before escalating
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct KillStrategy {
+    /// Time allowed after SIGTERM before sending SIGKILL
+    #[serde(default = "default_grace_period_ms")]
+    pub grace_period_ms: u64,
+    /// How often to check whether the process and its port are gone
+    #[serde(default = "default_poll_interval_ms")]
+    pub poll_interval_ms: u64,
+}
+
+fn default_grace_period_ms() -> u64 {
+    3000
+}
+
+fn default_poll_interval_ms() -> u64 {
+    100
+}
+
+impl Default for KillStrategy {
+    fn default() -> Self {
+        KillStrategy {
+            grace_period_ms: default_grace_period_ms(),
+            poll_interval_ms: default_poll_interval_ms(),
+        }
+    }
+}
+
+/// Which stage of a graceful kill ended the process
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KillStage {
+    /// Gone before we sent anything
+    AlreadyExited,
+    /// Exited on SIGTERM within the grace period
+    Terminated,
+    /// Needed SIGKILL
+    Killed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KillReport {
+    pub pid: u32,
+    pub stage: KillStage,
+    pub elapsed_ms: u64,
+    /// Whether the port was free afterwards, when one was given
+    pub port_released: Option<bool>,
+}
+
+/// A process that exited while freeing a port, and what it took
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FreedProcess {
+    pub pid: u32,
+    pub name: String,
+    pub stage: KillStage,
+}
+
+/// Outcome of `free_port`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FreePortReport {
+    pub port: u16,
+    pub protocol: Option<String>,
+    pub killed: Vec<FreedProcess>,
+    /// Owners still holding the port at the end, including respawned servers
+    pub survivors: Vec<ProcessOwner>,
+    /// Failures to signal an owner (permission denied...)
+    pub errors: Vec<ScanError>,
+    /// The final scan shows nothing on the port
+    pub released: bool,
+    /// Whether we could bind the port ourselves; None if we couldn't tell
+    pub bindable: Option<bool>,
+    pub elapsed_ms: u64,
+}
+
+/// A user-configured protection pattern. Every field that is set must
+/// match; `name` and `path` accept `*` and `?` wildcards.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProtectionRule {
+    /// Process name (or executable file name)
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Full executable path
+    #[serde(default)]
+    pub path: Option<String>,
+    #[serde(default)]
+    pub uid: Option<u32>,
+}
+
+/// A service commonly found on a port, e.g. "PostgreSQL" for 5432/tcp
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ServiceHint {
+    pub name: String,
+    pub description: Option<String>,
+    /// Came from the user's overrides file rather than the built-in registry
+    pub user_defined: bool,
+}
+
+/// One entry of the user's service overrides file (a JSON array of these)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ServiceEntry {
+    pub port: u16,
+    /// "tcp" or "udp"; None applies to both
+    #[serde(default)]
+    pub protocol: Option<String>,
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+/// Processes kill commands refuse to touch without `force`. Init, Unbind
+/// itself, its session leader and kernel threads are always protected too.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProtectionPolicy {
+    pub builtin_names: Vec<String>,
+    pub rules: Vec<ProtectionRule>,
+}
+
+/// One entry of a batch kill: a process, or every owner of a port
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum KillTarget {
+    Pid {
+        pid: u32,
+        #[serde(default)]
+        identity: Option<ProcessIdentity>,
+    },
+    Port {
+        port: u16,
+        #[serde(default)]
+        protocol: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TargetStatus {
+    Killed,
+    /// Exited (or its pid was reused) before we got to it
+    AlreadyGone,
+    PermissionDenied,
+    /// Refused by the protection policy
+    Protected,
+    Failed,
+}
+
+/// Result for one target of a batch kill
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BatchKillOutcome {
+    pub target: KillTarget,
+    pub status: TargetStatus,
+    /// Pids that exited
+    pub killed: Vec<u32>,
+    pub error: Option<ScanError>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ScanError {
+    pub kind: ScanErrorKind,
     pub message: String,
+    /// OS error number, when the failure came from a syscall
+    pub errno: Option<i32>,
+    /// Exit status of the external tool that failed
+    pub exit_status: Option<i32>,
+    /// The process the operation targeted
+    pub pid: Option<u32>,
+    /// The port the operation targeted
+    pub port: Option<u16>,
 }
 
-impl From<std::io::Error> for ScanError {
-    fn from(err: std::io::Error) -> Self {
+impl ScanError {
+    pub fn new(kind: ScanErrorKind, message: impl Into<String>) -> Self {
         ScanError {
-            message: err.to_string(),
+            kind,
+            message: message.into(),
+            errno: None,
+            exit_status: None,
+            pid: None,
+            port: None,
         }
     }
+
+    pub fn with_pid(mut self, pid: u32) -> Self {
+        self.pid = Some(pid);
+        self
+    }
+
+    pub fn with_port(mut self, port: u16) -> Self {
+        self.port = Some(port);
+        self
+    }
+
+    pub fn with_exit_status(mut self, status: Option<i32>) -> Self {
+        self.exit_status = status;
+        self
+    }
+
+    /// Failure to spawn an external tool; NotFound means it isn't installed
+    pub fn spawn(tool: &str, err: std::io::Error) -> Self {
+        if err.kind() == std::io::ErrorKind::NotFound {
+            let mut error = ScanError::new(
+                ScanErrorKind::ToolMissing,
+                format!("{} is not installed", tool),
+            );
+            error.errno = err.raw_os_error();
+            error
+        } else {
+            ScanError::from(err)
+        }
+    }
+
+    /// Failure reported by a kill tool (kill, taskkill), classified from its stderr
+    // Only taskkill still reports failures as text; unix uses kill(2) errno
+    #[cfg_attr(unix, allow(dead_code))]
+    fn kill_failed(pid: u32, exit_status: Option<i32>, stderr: &str) -> Self {
+        let lower = stderr.to_lowercase();
+        let kind = if lower.contains("no such process") || lower.contains("not found") {
+            ScanErrorKind::ProcessNotFound
+        } else if lower.contains("not permitted") || lower.contains("access is denied") {
+            ScanErrorKind::PermissionDenied
+        } else {
+            ScanErrorKind::CommandFailed
+        };
+
+        ScanError::new(kind, format!("Failed to kill process: {}", stderr.trim()))
+            .with_pid(pid)
+            .with_exit_status(exit_status)
+    }
+}
+
+impl From<std::io::Error> for ScanError {
+    fn from(err: std::io::Error) -> Self {
+        let kind = match err.kind() {
+            std::io::ErrorKind::PermissionDenied => ScanErrorKind::PermissionDenied,
+            std::io::ErrorKind::InvalidData => ScanErrorKind::ParseFailure,
+            _ if cfg!(unix) && err.raw_os_error() == Some(ESRCH) => ScanErrorKind::ProcessNotFound,
+            _ => ScanErrorKind::Io,
+        };
+        let mut error = ScanError::new(kind, err.to_string());
+        error.errno = err.raw_os_error();
+        error
+    }
 }
 
 impl From<String> for ScanError {
     fn from(err: String) -> Self {
-        ScanError { message: err }
+        ScanError::new(ScanErrorKind::Other, err)
     }
 }
 
 impl From<&str> for ScanError {
     fn from(err: &str) -> Self {
-        ScanError {
-            message: err.to_string(),
-        }
+        ScanError::new(ScanErrorKind::Other, err)
     }
 }
 
+/// errno for "no such process", identical on Linux and macOS
+const ESRCH: i32 = 3;
+
 pub type ScanResult<T> = Result<T, ScanError>;
 
-/// Scan all listening ports on the system
+/// Scan backends for this OS, in default fallback order
+fn platform_backends() -> Vec<Box<dyn backend::ScanBackend>> {
+    #[cfg(target_os = "linux")]
+    {
+        linux::backends()
+    }
+    #[cfg(target_os = "macos")]
+    {
+        macos::backends()
+    }
+    #[cfg(target_os = "windows")]
+    {
+        windows::backends()
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+    {
+        Vec::new()
+    }
+}
+
+/// The scan callers are currently sharing, if any
+static SCAN_FLIGHT: coalesce::SharedScan = coalesce::SharedScan::new();
+
+/// Scan all listening ports on the system. Callers that arrive while a
+/// scan is running share its result.
 pub fn scan_ports() -> ScanResult<Vec<PortInfo>> {
+    SCAN_FLIGHT.run(scan_ports_now)
+}
+
+fn scan_ports_now() -> ScanResult<Vec<PortInfo>> {
+    let mut ports = backend::run_scan(platform_backends())?;
+    attach_process_info(&mut ports);
+    services::attach_hints(&mut ports);
+    Ok(ports)
+}
+
+/// Fill in state and `usage` for each entry's pid, and `usage` for every
+/// other owner, sampling each distinct pid once
+fn attach_process_info(ports: &mut [PortInfo]) {
+    let pids: Vec<u32> = ports
+        .iter()
+        .flat_map(|info| info.owners.iter().map(|owner| owner.pid).chain([info.pid]))
+        .collect();
     #[cfg(target_os = "linux")]
     {
-        linux::scan_ports()
+        let samples = usage::sample(&pids);
+        for info in ports.iter_mut() {
+            for owner in info.owners.iter_mut() {
+                owner.usage = samples.get(&owner.pid).map(|sample| sample.usage.clone());
+            }
+            if let Some(sample) = samples.get(&info.pid) {
+                info.usage = Some(sample.usage.clone());
+                info.process_state = Some(sample.state);
+                info.identity = Some(ProcessIdentity {
+                    pid: info.pid,
+                    start_time: sample.start_time,
+                });
+            }
+        }
+        systemd::attach_units(ports);
+        container::attach_containers(ports);
+        project::attach_projects(ports, |pid| {
+            Some(project::ProcessContext {
+                cwd: procfs::read_cwd(pid),
+                argv: procfs::read_cmdline(pid),
+            })
+        });
     }
     #[cfg(target_os = "macos")]
     {
-        macos::scan_ports()
+        let states = macos::process_states(&pids);
+        for info in ports.iter_mut() {
+            info.process_state = states.get(&info.pid).copied();
+            info.identity = macos::process_start_time(info.pid).map(|start_time| ProcessIdentity {
+                pid: info.pid,
+                start_time,
+            });
+        }
+        project::attach_projects(ports, macos::process_context);
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+    {
+        let _ = (ports, pids);
+    }
+}
+
+/// Listening unix-domain sockets (docker.sock, postgres...), with the same
+/// process details as a port scan. Linux only.
+pub fn unix_sockets() -> ScanResult<Vec<PortInfo>> {
+    #[cfg(target_os = "linux")]
+    {
+        let mut sockets = linux::scan_unix_sockets()?;
+        attach_process_info(&mut sockets);
+        Ok(sockets)
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        Err(ScanError::new(
+            ScanErrorKind::UnsupportedOs,
+            "Unix sockets are only listed on Linux",
+        ))
+    }
+}
+
+/// The bare listener list for kills and port checks: no process details,
+/// and the diagnostics of the last scan the user asked for stay in place
+fn scan_listeners() -> ScanResult<Vec<PortInfo>> {
+    backend::run_scan_unrecorded(platform_backends())
+}
+
+/// Every backend for this OS with its availability and capabilities
+pub fn scan_backends() -> Vec<BackendInfo> {
+    backend::describe(
+        &platform_backends(),
+        backend::preferred_backend().as_deref(),
+    )
+}
+
+/// Select the backend tried first on each scan (None restores the default
+/// order) and save the choice
+pub fn set_scan_backend(name: Option<String>) -> ScanResult<()> {
+    backend::set_preferred_backend(&platform_backends(), name.clone())?;
+    settings::update(|settings| settings.scan_backend = name)
+}
+
+/// Read the saved scanner settings (a missing file is fine) and apply them
+pub fn load_scanner_settings(path: &Path) -> ScanResult<()> {
+    let settings = settings::load(path)?;
+    if let Err(e) = apply_namespace_scanning(settings.namespace_scanning) {
+        log::warn!("Ignoring saved namespace scanning setting: {}", e.message);
+    }
+    backend::set_preferred_backend(&platform_backends(), settings.scan_backend)
+}
+
+/// The scanner settings in effect
+pub fn scanner_settings() -> ScannerSettings {
+    settings::current()
+}
+
+/// Also scan the network namespaces other processes run in (Linux only) and
+/// save the choice
+pub fn set_namespace_scanning(enabled: bool) -> ScanResult<()> {
+    apply_namespace_scanning(enabled)?;
+    settings::update(|settings| settings.namespace_scanning = enabled)
+}
+
+fn apply_namespace_scanning(enabled: bool) -> ScanResult<()> {
+    #[cfg(target_os = "linux")]
+    {
+        netns::set_enabled(enabled);
+        Ok(())
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        if enabled {
+            return Err(ScanError::new(
+                ScanErrorKind::UnsupportedOs,
+                "Network namespaces are only scanned on Linux",
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Which backend served the last scan, why others were skipped, and timings
+pub fn scan_diagnostics() -> Option<ScanDiagnostics> {
+    backend::last_diagnostics()
+}
+
+/// Entries for `port` (optionally one protocol). Only port-based protocols
+/// count: unix sockets have no port and raw sockets carry an IP protocol
+/// number there (58 for ICMPv6), so neither may match. The same number in
+/// another network namespace is a different port, so only our own namespace
+/// counts.
+pub fn listeners_on<'a>(
+    ports: &'a [PortInfo],
+    port: u16,
+    protocol: Option<&'a str>,
+) -> impl Iterator<Item = &'a PortInfo> {
+    ports.iter().filter(move |p| {
+        p.port == port
+            && matches!(p.protocol.as_str(), "tcp" | "udp" | "udplite")
+            && p.net_namespace.is_none()
+            && protocol.map_or(true, |proto| p.protocol == proto)
+    })
+}
+
+/// Every distinct process holding `port`, across address families and protocols
+pub fn port_owners(ports: &[PortInfo], port: u16) -> Vec<ProcessOwner> {
+    protocol_port_owners(ports, port, None)
+}
+
+/// Like `port_owners`, limited to one protocol ("tcp", "udp"...) if given
+pub fn protocol_port_owners(
+    ports: &[PortInfo],
+    port: u16,
+    protocol: Option<&str>,
+) -> Vec<ProcessOwner> {
+    let mut owners: Vec<ProcessOwner> = Vec::new();
+
+    for info in listeners_on(ports, port, protocol) {
+        for owner in &info.owners {
+            if owner.pid != 0 && !owners.iter().any(|o| o.pid == owner.pid) {
+                owners.push(owner.clone());
+            }
+        }
+    }
+
+    owners
+}
+
+/// Kill `pid` and every other process sharing a socket on `port`.
+/// Returns the PIDs that were killed.
+pub fn kill_port_owners(pid: u32, port: u16, force: bool) -> ScanResult<Vec<u32>> {
+    let ports = scan_listeners()?;
+    let mut targets = vec![pid];
+    for owner in port_owners(&ports, port) {
+        if !targets.contains(&owner.pid) {
+            targets.push(owner.pid);
+        }
+    }
+
+    // Keep going after a failure so one stuck worker doesn't shield the rest
+    let mut killed = Vec::new();
+    let mut failures: Vec<ScanError> = Vec::new();
+    for target in targets {
+        match protect::check(target, force).and_then(|()| kill_process(target)) {
+            Ok(()) => killed.push(target),
+            Err(e) => failures.push(e),
+        }
+    }
+
+    match failures.first() {
+        None => Ok(killed),
+        Some(first) => {
+            let details: Vec<String> = failures
+                .iter()
+                .map(|e| format!("{}: {}", e.pid.unwrap_or_default(), e.message.trim()))
+                .collect();
+            let mut error = ScanError::new(
+                first.kind,
+                format!(
+                    "Killed {:?} but port {} still has owners: {}",
+                    killed,
+                    port,
+                    details.join("; ")
+                ),
+            )
+            .with_port(port)
+            .with_exit_status(first.exit_status);
+            error.errno = first.errno;
+            error.pid = first.pid;
+            Err(error)
+        }
+    }
+}
+
+/// Full metadata (argv, exe, cwd, user, parent, start time) for a process
+pub fn process_details(pid: u32) -> ScanResult<ProcessDetails> {
+    #[cfg(target_os = "linux")]
+    {
+        procfs::process_details(pid)
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        Err(ScanError::new(
+            ScanErrorKind::UnsupportedOs,
+            "Process details are only available on Linux",
+        )
+        .with_pid(pid))
+    }
+}
+
+/// Parent chain and descendants of a process
+pub fn process_tree(pid: u32) -> ScanResult<ProcessTree> {
+    #[cfg(target_os = "linux")]
+    {
+        tree::process_tree(pid)
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        Err(ScanError::new(
+            ScanErrorKind::UnsupportedOs,
+            "Process trees are only available on Linux",
+        )
+        .with_pid(pid))
+    }
+}
+
+/// Kill a process with its subtree or process group, leaves first
+pub fn kill_process_tree(
+    pid: u32,
+    mode: TreeKillMode,
+    force: bool,
+) -> ScanResult<Vec<KillOutcome>> {
+    #[cfg(target_os = "linux")]
+    {
+        tree::kill_tree(pid, mode, force)
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = (mode, force);
+        Err(ScanError::new(
+            ScanErrorKind::UnsupportedOs,
+            "Killing process trees is only available on Linux",
+        )
+        .with_pid(pid))
+    }
+}
+
+/// SIGTERM, wait up to the grace period for the process (and `port`, if
+/// given) to go away, then SIGKILL. Protected processes need `force`.
+pub fn graceful_kill(
+    pid: u32,
+    port: Option<u16>,
+    strategy: &KillStrategy,
+    force: bool,
+) -> ScanResult<KillReport> {
+    protect::check(pid, force)?;
+    let started = Instant::now();
+    let poll = Duration::from_millis(strategy.poll_interval_ms.max(10));
+    let report = |stage| KillReport {
+        pid,
+        stage,
+        elapsed_ms: started.elapsed().as_millis() as u64,
+        port_released: port.map(port_is_free),
+    };
+
+    if !process_running(pid) {
+        return Ok(report(KillStage::AlreadyExited));
+    }
+
+    match terminate_process(pid) {
+        Ok(()) => {}
+        Err(e) if e.kind == ScanErrorKind::ProcessNotFound => {
+            return Ok(report(KillStage::AlreadyExited))
+        }
+        // Windows console processes refuse a polite taskkill; force it below
+        Err(_) if cfg!(windows) => {}
+        Err(e) => return Err(e),
+    }
+
+    let deadline = started + Duration::from_millis(strategy.grace_period_ms);
+    let exited = wait_until(deadline, poll, || {
+        !process_running(pid) && port.map_or(true, port_is_free)
+    });
+    if exited || !process_running(pid) {
+        return Ok(report(KillStage::Terminated));
+    }
+
+    kill_process(pid)?;
+    wait_until(Instant::now() + Duration::from_secs(1), poll, || {
+        !process_running(pid)
+    });
+    Ok(report(KillStage::Killed))
+}
+
+/// Make `port` free: terminate every owner (all families, optionally one
+/// protocol) with `strategy`, then wait for the port to be released
+pub fn free_port(
+    port: u16,
+    protocol: Option<&str>,
+    strategy: &KillStrategy,
+    force: bool,
+) -> ScanResult<FreePortReport> {
+    free::free_port(port, protocol, strategy, force)
+}
+
+/// Kill many pids/ports concurrently, one outcome per target in input order
+pub fn kill_batch(
+    targets: &[KillTarget],
+    strategy: &KillStrategy,
+    force: bool,
+) -> Vec<BatchKillOutcome> {
+    batch::kill_batch(targets, strategy, force)
+}
+
+/// Poll `done` until it holds or `deadline` passes
+fn wait_until(deadline: Instant, poll: Duration, mut done: impl FnMut() -> bool) -> bool {
+    loop {
+        if done() {
+            return true;
+        }
+        let now = Instant::now();
+        if now >= deadline {
+            return false;
+        }
+        thread::sleep(poll.min(deadline - now));
+    }
+}
+
+/// Nothing listens on `port` any more; a failed check counts as "don't know, no"
+fn port_is_free(port: u16) -> bool {
+    port_in_use(port, None).is_ok_and(|in_use| !in_use)
+}
+
+/// Cheap check for a listener on `port`, for polling: the backend skips
+/// owner lookup where it can, and nothing is enriched or recorded
+fn port_in_use(port: u16, protocol: Option<&str>) -> ScanResult<bool> {
+    backend::port_in_use(platform_backends(), port, protocol)
+}
+
+/// Ask a process to exit without forcing it
+fn terminate_process(pid: u32) -> ScanResult<()> {
+    #[cfg(target_os = "linux")]
+    {
+        linux::terminate_process(pid)
+    }
+    #[cfg(target_os = "macos")]
+    {
+        macos::terminate_process(pid)
     }
     #[cfg(target_os = "windows")]
     {
-        windows::scan_ports()
+        windows::terminate_process(pid)
     }
     #[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
     {
-        Err(ScanError::from("Unsupported operating system"))
+        Err(ScanError::new(
+            ScanErrorKind::UnsupportedOs,
+            "Unsupported operating system",
+        ))
+    }
+}
+
+fn process_running(pid: u32) -> bool {
+    #[cfg(target_os = "linux")]
+    {
+        linux::process_running(pid)
     }
+    #[cfg(target_os = "macos")]
+    {
+        macos::process_running(pid)
+    }
+    #[cfg(target_os = "windows")]
+    {
+        windows::process_running(pid)
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+    {
+        let _ = pid;
+        false
+    }
+}
+
+/// Refuse to act on `pid` if it is no longer the process the token was
+/// taken from. No token means the caller accepts the pid as is.
+pub fn verify_identity(pid: u32, identity: Option<&ProcessIdentity>) -> ScanResult<()> {
+    let Some(identity) = identity else {
+        return Ok(());
+    };
+    let changed = || {
+        ScanError::new(
+            ScanErrorKind::ProcessChanged,
+            format!(
+                "Process {} is no longer the one that was scanned; refresh and try again",
+                pid
+            ),
+        )
+        .with_pid(pid)
+    };
+    if identity.pid != pid {
+        return Err(changed());
+    }
+
+    if process_start_time(pid)? == identity.start_time {
+        Ok(())
+    } else {
+        Err(changed())
+    }
+}
+
+/// Start time in the unit `ProcessIdentity` uses on this OS
+fn process_start_time(pid: u32) -> ScanResult<u64> {
+    #[cfg(target_os = "linux")]
+    {
+        Ok(procfs::read_stat(pid)?.start_time)
+    }
+    #[cfg(target_os = "macos")]
+    {
+        macos::process_start_time(pid).ok_or_else(|| {
+            ScanError::new(
+                ScanErrorKind::ProcessNotFound,
+                format!("Process {} not found", pid),
+            )
+            .with_pid(pid)
+        })
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+    {
+        Err(ScanError::new(
+            ScanErrorKind::UnsupportedOs,
+            "Process identity cannot be verified on this operating system",
+        )
+        .with_pid(pid))
+    }
+}
+
+/// Deliver `sig` to a process (reload, interrupt, suspend/resume...)
+pub fn send_signal(pid: u32, sig: Signal, force: bool) -> ScanResult<()> {
+    protect::check(pid, force)?;
+    #[cfg(unix)]
+    {
+        signal::send(pid, signal::raw(sig))
+    }
+    #[cfg(target_os = "windows")]
+    {
+        match sig {
+            Signal::Kill => windows::kill_process(pid),
+            Signal::Term => windows::terminate_process(pid),
+            other => Err(ScanError::new(
+                ScanErrorKind::UnsupportedOs,
+                format!("{} is not available on Windows", other.name()),
+            )
+            .with_pid(pid)),
+        }
+    }
+    #[cfg(not(any(unix, target_os = "windows")))]
+    {
+        Err(ScanError::new(
+            ScanErrorKind::UnsupportedOs,
+            format!("Cannot send {} on this operating system", sig.name()),
+        )
+        .with_pid(pid))
+    }
+}
+
+/// Deliver SIGTERM or SIGKILL to a process we may not signal ourselves,
+/// through the pkexec helper (Linux). The admin password prompt comes from
+/// polkit; the protection policy still applies first.
+pub fn send_signal_elevated(
+    pid: u32,
+    sig: Signal,
+    identity: Option<&ProcessIdentity>,
+    force: bool,
+) -> ScanResult<()> {
+    protect::check(pid, force)?;
+    #[cfg(target_os = "linux")]
+    {
+        elevate::send_signal(pid, sig, identity)
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = identity;
+        Err(ScanError::new(
+            ScanErrorKind::UnsupportedOs,
+            format!(
+                "Sending {} with elevated privileges is only supported on Linux",
+                sig.name()
+            ),
+        )
+        .with_pid(pid))
+    }
+}
+
+/// Stop a systemd service or socket unit (Linux). Stopping a `.socket` also
+/// stops the service it activated, so the port is actually released.
+pub fn stop_systemd_unit(unit: &SystemdUnit) -> ScanResult<()> {
+    #[cfg(target_os = "linux")]
+    {
+        systemd::stop_unit(unit)
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        Err(systemd_unsupported(unit))
+    }
+}
+
+/// Stop a container through the Docker/Podman Engine API (Linux): the
+/// engine signals it and waits `timeout_secs` before killing it, keeping
+/// its own state consistent, unlike killing docker-proxy or conmon
+pub fn stop_container(container: &ContainerInfo, timeout_secs: u32) -> ScanResult<()> {
+    #[cfg(target_os = "linux")]
+    {
+        container::stop_container(container, timeout_secs)
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = timeout_secs;
+        Err(ScanError::new(
+            ScanErrorKind::UnsupportedOs,
+            format!(
+                "Cannot stop container {}: container detection is only available on Linux",
+                container.id
+            ),
+        ))
+    }
+}
+
+/// Restart a systemd service or socket unit (Linux)
+pub fn restart_systemd_unit(unit: &SystemdUnit) -> ScanResult<()> {
+    #[cfg(target_os = "linux")]
+    {
+        systemd::restart_unit(unit)
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        Err(systemd_unsupported(unit))
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn systemd_unsupported(unit: &SystemdUnit) -> ScanError {
+    ScanError::new(
+        ScanErrorKind::UnsupportedOs,
+        format!(
+            "Cannot manage {}: systemd is only available on Linux",
+            unit.name
+        ),
+    )
+}
+
+/// The built-in and user protection rules
+pub fn protection_policy() -> ProtectionPolicy {
+    protect::policy()
+}
+
+/// The service usually found on `port`, overrides first (None: any protocol)
+pub fn lookup_service(port: u16, protocol: Option<&str>) -> Option<ServiceHint> {
+    services::lookup(port, protocol)
+}
+
+/// Load the user's service overrides file (missing is fine); returns the
+/// number of entries read
+pub fn load_service_overrides(path: &Path) -> ScanResult<usize> {
+    services::load_overrides(path)
+}
+
+/// Re-read the overrides file after it was edited
+pub fn reload_service_overrides() -> ScanResult<usize> {
+    services::reload_overrides()
+}
+
+/// Replace the user-configured protection rules (from settings) and save them
+pub fn set_protection_rules(rules: Vec<ProtectionRule>) -> ScanResult<()> {
+    protect::set_rules(rules)
+}
+
+/// Load the saved protection rules (a missing file is fine); returns the
+/// number of rules read
+pub fn load_protection_rules(path: &Path) -> ScanResult<usize> {
+    protect::load_rules(path)
+}
+
+/// Err(Protected) if the policy covers `pid` and `force` isn't set
+pub fn check_protected(pid: u32, force: bool) -> ScanResult<()> {
+    protect::check(pid, force)
 }
 
 /// Kill a process by PID
@@ -82,7 +1285,10 @@ pub fn kill_process(pid: u32) -> ScanResult<()> {
     }
     #[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
     {
-        Err(ScanError::from("Unsupported operating system"))
+        Err(ScanError::new(
+            ScanErrorKind::UnsupportedOs,
+            "Unsupported operating system",
+        ))
     }
 }
 
@@ -99,6 +1305,15 @@ mod tests {
             protocol: "tcp".to_string(),
             local_address: "0.0.0.0".to_string(),
             state: "LISTEN".to_string(),
+            owners: vec![],
+            identity: None,
+            process_state: None,
+            usage: None,
+            systemd_unit: None,
+            container: None,
+            net_namespace: None,
+            project: None,
+            service_hint: None,
         };
 
         let json = serde_json::to_string(&port_info).unwrap();
@@ -136,6 +1351,15 @@ mod tests {
             protocol: "tcp".to_string(),
             local_address: "0.0.0.0".to_string(),
             state: "LISTEN".to_string(),
+            owners: vec![],
+            identity: None,
+            process_state: None,
+            usage: None,
+            systemd_unit: None,
+            container: None,
+            net_namespace: None,
+            project: None,
+            service_hint: None,
         };
 
         let cloned = original.clone();
@@ -144,17 +1368,191 @@ mod tests {
         assert_eq!(original.process_name, cloned.process_name);
     }
 
+    fn owner(pid: u32, name: &str) -> ProcessOwner {
+        ProcessOwner {
+            pid,
+            name: name.to_string(),
+            fd: Some(6),
+            usage: None,
+        }
+    }
+
+    #[test]
+    fn test_port_info_new_uses_first_owner() {
+        let info = PortInfo::new(
+            80,
+            "tcp",
+            "0.0.0.0".to_string(),
+            vec![owner(100, "nginx"), owner(101, "nginx")],
+        );
+        assert_eq!(info.pid, 100);
+        assert_eq!(info.process_name, "nginx");
+        assert_eq!(info.owners.len(), 2);
+        assert_eq!(info.state, "LISTEN");
+    }
+
+    #[test]
+    fn test_port_info_new_without_owners() {
+        let info = PortInfo::new(111, "tcp", "0.0.0.0".to_string(), vec![]);
+        assert_eq!(info.pid, 0);
+        assert_eq!(info.process_name, "unknown");
+    }
+
+    #[test]
+    fn test_port_info_deserialization_without_owners() {
+        let json = r#"{"port":1,"pid":2,"process_name":"x","protocol":"tcp","local_address":"::","state":"LISTEN"}"#;
+        let port_info: PortInfo = serde_json::from_str(json).unwrap();
+        assert!(port_info.owners.is_empty());
+    }
+
+    #[test]
+    fn test_port_owners_merges_families() {
+        let ports = vec![
+            PortInfo::new(
+                80,
+                "tcp",
+                "0.0.0.0".to_string(),
+                vec![owner(100, "nginx"), owner(101, "nginx")],
+            ),
+            PortInfo::new(
+                80,
+                "tcp",
+                "[::]".to_string(),
+                vec![owner(100, "nginx"), owner(102, "nginx")],
+            ),
+            PortInfo::new(443, "tcp", "0.0.0.0".to_string(), vec![owner(100, "nginx")]),
+            PortInfo::new(80, "udp", "0.0.0.0".to_string(), vec![]),
+        ];
+
+        let pids: Vec<u32> = port_owners(&ports, 80).iter().map(|o| o.pid).collect();
+        assert_eq!(pids, vec![100, 101, 102]);
+    }
+
+    #[test]
+    fn test_port_owners_ignores_unix_and_unknown() {
+        let ports = vec![
+            PortInfo::new(
+                0,
+                "unix",
+                "/run/docker.sock".to_string(),
+                vec![owner(900, "dockerd")],
+            ),
+            PortInfo::new(0, "tcp", "0.0.0.0".to_string(), vec![owner(0, "unknown")]),
+        ];
+        assert!(port_owners(&ports, 0).is_empty());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_attach_process_info_samples_every_owner() {
+        let own = std::process::id();
+        let parent = std::os::unix::process::parent_id();
+        let mut ports = vec![PortInfo::new(
+            8000,
+            "tcp",
+            "0.0.0.0".to_string(),
+            vec![owner(own, "unbind"), owner(parent, "cargo")],
+        )];
+
+        attach_process_info(&mut ports);
+        assert!(ports[0].usage.is_some());
+        assert!(ports[0].owners.iter().all(|owner| owner.usage.is_some()));
+    }
+
+    #[test]
+    fn test_port_owners_ignores_raw_sockets() {
+        // Raw ICMPv6 socket: "port" 58 is the IP protocol number
+        let ports = vec![
+            PortInfo::new(
+                58,
+                "raw",
+                "[::]".to_string(),
+                vec![owner(700, "NetworkManager")],
+            ),
+            PortInfo::new(58, "udp", "0.0.0.0".to_string(), vec![owner(701, "app")]),
+        ];
+
+        let pids: Vec<u32> = port_owners(&ports, 58).iter().map(|o| o.pid).collect();
+        assert_eq!(pids, vec![701]);
+        assert!(protocol_port_owners(&ports, 58, Some("raw")).is_empty());
+    }
+
+    #[test]
+    fn test_port_owners_ignores_other_namespaces() {
+        let mut namespaced =
+            PortInfo::new(80, "tcp", "0.0.0.0".to_string(), vec![owner(500, "caddy")]);
+        namespaced.net_namespace = Some(NetNamespace {
+            inode: 4026532301,
+            name: Some("blue".to_string()),
+            pid: 500,
+        });
+        let ports = vec![
+            namespaced,
+            PortInfo::new(80, "tcp", "0.0.0.0".to_string(), vec![owner(100, "nginx")]),
+        ];
+
+        let pids: Vec<u32> = port_owners(&ports, 80).iter().map(|o| o.pid).collect();
+        assert_eq!(pids, vec![100]);
+    }
+
     #[test]
     fn test_scan_error_from_io_error() {
         let io_error = std::io::Error::new(std::io::ErrorKind::NotFound, "File not found");
         let scan_error: ScanError = io_error.into();
         assert!(scan_error.message.contains("File not found"));
+        assert_eq!(scan_error.kind, ScanErrorKind::Io);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_scan_error_from_os_errors() {
+        let eperm: ScanError = std::io::Error::from_raw_os_error(1).into();
+        assert_eq!(eperm.kind, ScanErrorKind::PermissionDenied);
+        assert_eq!(eperm.errno, Some(1));
+
+        let esrch: ScanError = std::io::Error::from_raw_os_error(ESRCH).into();
+        assert_eq!(esrch.kind, ScanErrorKind::ProcessNotFound);
+        assert_eq!(esrch.errno, Some(ESRCH));
+    }
+
+    #[test]
+    fn test_scan_error_spawn_missing_tool() {
+        let err = std::io::Error::new(std::io::ErrorKind::NotFound, "No such file");
+        let error = ScanError::spawn("ss", err);
+        assert_eq!(error.kind, ScanErrorKind::ToolMissing);
+        assert_eq!(error.message, "ss is not installed");
+    }
+
+    #[test]
+    fn test_scan_error_kill_failed_classification() {
+        let gone = ScanError::kill_failed(42, Some(1), "kill: (42) - No such process\n");
+        assert_eq!(gone.kind, ScanErrorKind::ProcessNotFound);
+        assert_eq!(gone.pid, Some(42));
+        assert_eq!(gone.exit_status, Some(1));
+        assert_eq!(
+            gone.message,
+            "Failed to kill process: kill: (42) - No such process"
+        );
+
+        let denied = ScanError::kill_failed(1, Some(1), "kill: (1) - Operation not permitted");
+        assert_eq!(denied.kind, ScanErrorKind::PermissionDenied);
+
+        let windows = ScanError::kill_failed(4, Some(1), "ERROR: Access is denied.");
+        assert_eq!(windows.kind, ScanErrorKind::PermissionDenied);
+
+        let windows_gone =
+            ScanError::kill_failed(9999, Some(128), "ERROR: The process \"9999\" not found.");
+        assert_eq!(windows_gone.kind, ScanErrorKind::ProcessNotFound);
+
+        let other = ScanError::kill_failed(5, Some(2), "usage: kill");
+        assert_eq!(other.kind, ScanErrorKind::CommandFailed);
     }
 
     #[test]
     fn test_scan_error_from_string() {
         let error: ScanError = "Something went wrong".to_string().into();
         assert_eq!(error.message, "Something went wrong");
+        assert_eq!(error.kind, ScanErrorKind::Other);
     }
 
     #[test]
@@ -165,11 +1563,224 @@ mod tests {
 
     #[test]
     fn test_scan_error_serialization() {
-        let error = ScanError {
-            message: "Test error".to_string(),
-        };
+        let error = ScanError::new(ScanErrorKind::PermissionDenied, "Test error")
+            .with_pid(1234)
+            .with_port(3000);
 
         let json = serde_json::to_string(&error).unwrap();
         assert!(json.contains("\"message\":\"Test error\""));
+        assert!(json.contains("\"kind\":\"permission_denied\""));
+        assert!(json.contains("\"pid\":1234"));
+        assert!(json.contains("\"port\":3000"));
+        assert!(json.contains("\"errno\":null"));
+    }
+
+    #[test]
+    fn test_scan_error_kind_codes_are_stable() {
+        let codes: Vec<String> = [
+            ScanErrorKind::PermissionDenied,
+            ScanErrorKind::ProcessNotFound,
+            ScanErrorKind::ProcessChanged,
+            ScanErrorKind::Protected,
+            ScanErrorKind::ToolMissing,
+            ScanErrorKind::CommandFailed,
+            ScanErrorKind::Timeout,
+            ScanErrorKind::ParseFailure,
+            ScanErrorKind::UnsupportedOs,
+            ScanErrorKind::Io,
+            ScanErrorKind::Other,
+        ]
+        .iter()
+        .map(|kind| serde_json::to_string(kind).unwrap())
+        .collect();
+
+        assert_eq!(
+            codes,
+            vec![
+                "\"permission_denied\"",
+                "\"process_not_found\"",
+                "\"process_changed\"",
+                "\"protected\"",
+                "\"tool_missing\"",
+                "\"command_failed\"",
+                "\"timeout\"",
+                "\"parse_failure\"",
+                "\"unsupported_os\"",
+                "\"io\"",
+                "\"other\"",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_kill_strategy_defaults() {
+        let strategy: KillStrategy = serde_json::from_str("{}").unwrap();
+        assert_eq!(strategy.grace_period_ms, 3000);
+        assert_eq!(strategy.poll_interval_ms, 100);
+
+        let strategy: KillStrategy = serde_json::from_str(r#"{"grace_period_ms":500}"#).unwrap();
+        assert_eq!(strategy.grace_period_ms, 500);
+    }
+
+    #[test]
+    fn test_wait_until() {
+        let poll = Duration::from_millis(1);
+        let mut calls = 0;
+        assert!(wait_until(
+            Instant::now() + Duration::from_secs(5),
+            poll,
+            || {
+                calls += 1;
+                calls == 3
+            }
+        ));
+        assert!(!wait_until(Instant::now(), poll, || false));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_graceful_kill_terminates() {
+        let mut child = std::process::Command::new("sleep")
+            .arg("30")
+            .spawn()
+            .unwrap();
+        let strategy = KillStrategy {
+            grace_period_ms: 2000,
+            poll_interval_ms: 10,
+        };
+
+        let report = graceful_kill(child.id(), None, &strategy, false).unwrap();
+        child.wait().unwrap();
+        assert_eq!(report.stage, KillStage::Terminated);
+        assert_eq!(report.port_released, None);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_graceful_kill_escalates() {
+        let mut child = std::process::Command::new("sh")
+            .args(["-c", "trap '' TERM; while :; do sleep 1; done"])
+            .spawn()
+            .unwrap();
+        // Let the shell install its trap
+        thread::sleep(Duration::from_millis(200));
+        let strategy = KillStrategy {
+            grace_period_ms: 200,
+            poll_interval_ms: 10,
+        };
+
+        let report = graceful_kill(child.id(), None, &strategy, false).unwrap();
+        child.wait().unwrap();
+        assert_eq!(report.stage, KillStage::Killed);
+        assert!(report.elapsed_ms >= 200);
+    }
+
+    #[test]
+    fn test_signal_names_round_trip() {
+        for sig in [
+            Signal::Hup,
+            Signal::Int,
+            Signal::Quit,
+            Signal::Term,
+            Signal::Kill,
+            Signal::Usr1,
+            Signal::Usr2,
+            Signal::Stop,
+            Signal::Cont,
+        ] {
+            let json = serde_json::to_string(&sig).unwrap();
+            assert_eq!(json, format!("\"{}\"", sig.name()));
+            assert_eq!(serde_json::from_str::<Signal>(&json).unwrap(), sig);
+        }
+        assert!(serde_json::from_str::<Signal>("\"SIGSEGV\"").is_err());
+    }
+
+    #[test]
+    fn test_process_state_from_code() {
+        assert_eq!(ProcessState::from_code('R'), ProcessState::Running);
+        assert_eq!(ProcessState::from_code('T'), ProcessState::Stopped);
+        assert_eq!(ProcessState::from_code('t'), ProcessState::Stopped);
+        assert_eq!(ProcessState::from_code('U'), ProcessState::DiskSleep);
+        assert_eq!(ProcessState::from_code('?'), ProcessState::Unknown);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_stop_and_continue() {
+        let mut child = std::process::Command::new("sleep")
+            .arg("30")
+            .spawn()
+            .unwrap();
+        let pid = child.id();
+        let state = || ProcessState::from_code(procfs::read_stat(pid).unwrap().state);
+
+        send_signal(pid, Signal::Stop, false).unwrap();
+        assert!(wait_until(
+            Instant::now() + Duration::from_secs(2),
+            Duration::from_millis(10),
+            || state() == ProcessState::Stopped
+        ));
+
+        send_signal(pid, Signal::Cont, false).unwrap();
+        assert!(wait_until(
+            Instant::now() + Duration::from_secs(2),
+            Duration::from_millis(10),
+            || state() != ProcessState::Stopped
+        ));
+
+        send_signal(pid, Signal::Kill, false).unwrap();
+        child.wait().unwrap();
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_verify_identity() {
+        let pid = std::process::id();
+        let start_time = procfs::read_stat(pid).unwrap().start_time;
+        let identity = ProcessIdentity { pid, start_time };
+
+        assert!(verify_identity(pid, None).is_ok());
+        assert!(verify_identity(pid, Some(&identity)).is_ok());
+
+        let reused = ProcessIdentity {
+            pid,
+            start_time: start_time + 1,
+        };
+        let error = verify_identity(pid, Some(&reused)).unwrap_err();
+        assert_eq!(error.kind, ScanErrorKind::ProcessChanged);
+        assert_eq!(error.pid, Some(pid));
+
+        // A token for another pid never vouches for this one
+        let error = verify_identity(pid + 1, Some(&identity)).unwrap_err();
+        assert_eq!(error.kind, ScanErrorKind::ProcessChanged);
+    }
+
+    #[test]
+    fn test_kill_target_deserialization() {
+        let targets: Vec<KillTarget> = serde_json::from_str(
+            r#"[{"pid": 42}, {"port": 3000, "protocol": "tcp"},
+                {"pid": 7, "identity": {"pid": 7, "start_time": 99}}]"#,
+        )
+        .unwrap();
+        assert_eq!(
+            targets,
+            vec![
+                KillTarget::Pid {
+                    pid: 42,
+                    identity: None
+                },
+                KillTarget::Port {
+                    port: 3000,
+                    protocol: Some("tcp".to_string())
+                },
+                KillTarget::Pid {
+                    pid: 7,
+                    identity: Some(ProcessIdentity {
+                        pid: 7,
+                        start_time: 99
+                    })
+                },
+            ]
+        );
     }
 }
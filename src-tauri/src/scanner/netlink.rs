@@ -0,0 +1,359 @@
+use std::io;
+use std::mem;
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+
+// Constants from <linux/netlink.h>, <linux/sock_diag.h> and <linux/tcp_states.h>
+const SOCK_DIAG_BY_FAMILY: u16 = 20;
+const NLMSG_HDR_LEN: usize = 16;
+const INET_DIAG_REQ_V2_LEN: usize = 56;
+const INET_DIAG_MSG_LEN: usize = 72;
+const TCP_LISTEN: u8 = 10;
+const TCP_CLOSE: u8 = 7;
+
+/// A listening socket as reported by the kernel's inet_diag interface
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiagSocket {
+    pub protocol: &'static str,
+    pub address: String,
+    pub port: u16,
+    pub inode: u64,
+    pub uid: u32,
+}
+
+/// Dump all listening TCP sockets and bound UDP sockets (IPv4 and IPv6)
+pub fn dump_listening() -> io::Result<Vec<DiagSocket>> {
+    let fd = open_socket()?;
+    let mut sockets = Vec::new();
+    let mut seq = 0;
+
+    for family in [libc::AF_INET, libc::AF_INET6] {
+        // TCP listeners are in LISTEN, bound UDP sockets are in CLOSE (ss calls it UNCONN)
+        for (protocol, state) in [("tcp", TCP_LISTEN), ("udp", TCP_CLOSE)] {
+            seq += 1;
+            let ip_protocol = if protocol == "tcp" {
+                libc::IPPROTO_TCP
+            } else {
+                libc::IPPROTO_UDP
+            };
+            let request = build_request(family as u8, ip_protocol as u8, 1 << state, seq);
+            send_request(&fd, &request)?;
+            receive_dump(&fd, protocol, &mut sockets)?;
+        }
+    }
+
+    Ok(sockets)
+}
+
+/// Check that the kernel accepts NETLINK_SOCK_DIAG sockets
+pub fn probe() -> io::Result<()> {
+    open_socket().map(drop)
+}
+
+/// Open a NETLINK_SOCK_DIAG socket
+fn open_socket() -> io::Result<OwnedFd> {
+    // SAFETY: plain socket(2) call, the returned descriptor is checked before use
+    let fd = unsafe {
+        libc::socket(
+            libc::AF_NETLINK,
+            libc::SOCK_RAW | libc::SOCK_CLOEXEC,
+            libc::NETLINK_SOCK_DIAG,
+        )
+    };
+    if fd < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    // SAFETY: fd is a freshly opened descriptor that nothing else owns
+    Ok(unsafe { OwnedFd::from_raw_fd(fd) })
+}
+
+/// Build an nlmsghdr + inet_diag_req_v2 dump request
+fn build_request(family: u8, protocol: u8, states: u32, seq: u32) -> Vec<u8> {
+    let total_len = NLMSG_HDR_LEN + INET_DIAG_REQ_V2_LEN;
+    let flags = (libc::NLM_F_REQUEST | libc::NLM_F_DUMP) as u16;
+
+    let mut buf = Vec::with_capacity(total_len);
+    buf.extend_from_slice(&(total_len as u32).to_ne_bytes());
+    buf.extend_from_slice(&SOCK_DIAG_BY_FAMILY.to_ne_bytes());
+    buf.extend_from_slice(&flags.to_ne_bytes());
+    buf.extend_from_slice(&seq.to_ne_bytes());
+    buf.extend_from_slice(&0u32.to_ne_bytes()); // port id, 0 = kernel
+
+    buf.push(family);
+    buf.push(protocol);
+    buf.push(0); // ext: no extra attributes
+    buf.push(0); // pad
+    buf.extend_from_slice(&states.to_ne_bytes());
+    buf.resize(total_len, 0); // inet_diag_sockid: wildcard
+
+    buf
+}
+
+fn send_request(fd: &OwnedFd, request: &[u8]) -> io::Result<()> {
+    // SAFETY: sockaddr_nl is plain old data, all-zero is a valid value
+    let mut addr: libc::sockaddr_nl = unsafe { mem::zeroed() };
+    addr.nl_family = libc::AF_NETLINK as libc::sa_family_t;
+
+    // SAFETY: request and addr outlive the call and their lengths are passed along
+    let sent = unsafe {
+        libc::sendto(
+            fd.as_raw_fd(),
+            request.as_ptr() as *const libc::c_void,
+            request.len(),
+            0,
+            &addr as *const libc::sockaddr_nl as *const libc::sockaddr,
+            mem::size_of::<libc::sockaddr_nl>() as libc::socklen_t,
+        )
+    };
+    if sent < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// Read replies until the kernel signals the end of the dump
+fn receive_dump(fd: &OwnedFd, protocol: &'static str, out: &mut Vec<DiagSocket>) -> io::Result<()> {
+    let mut buf = vec![0u8; 32 * 1024];
+
+    loop {
+        // SAFETY: buf is a valid writable buffer of the given length
+        let received = unsafe {
+            libc::recv(
+                fd.as_raw_fd(),
+                buf.as_mut_ptr() as *mut libc::c_void,
+                buf.len(),
+                0,
+            )
+        };
+        if received < 0 {
+            let err = io::Error::last_os_error();
+            if err.kind() == io::ErrorKind::Interrupted {
+                continue;
+            }
+            return Err(err);
+        }
+        if received == 0 {
+            return Ok(());
+        }
+
+        if parse_messages(&buf[..received as usize], protocol, out)? {
+            return Ok(());
+        }
+    }
+}
+
+/// Parse one datagram of netlink messages. Returns true once NLMSG_DONE is seen.
+fn parse_messages(
+    buf: &[u8],
+    protocol: &'static str,
+    out: &mut Vec<DiagSocket>,
+) -> io::Result<bool> {
+    let mut offset = 0;
+
+    while offset + NLMSG_HDR_LEN <= buf.len() {
+        let msg_len = read_u32_ne(buf, offset) as usize;
+        let msg_type = u16::from_ne_bytes([buf[offset + 4], buf[offset + 5]]);
+        if msg_len < NLMSG_HDR_LEN || offset + msg_len > buf.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "truncated netlink message",
+            ));
+        }
+
+        let payload = &buf[offset + NLMSG_HDR_LEN..offset + msg_len];
+        match msg_type as i32 {
+            libc::NLMSG_DONE => return Ok(true),
+            // Payload starts with a negative errno, 0 means plain ack
+            libc::NLMSG_ERROR if payload.len() >= 4 => {
+                let errno = i32::from_ne_bytes([payload[0], payload[1], payload[2], payload[3]]);
+                if errno != 0 {
+                    return Err(io::Error::from_raw_os_error(-errno));
+                }
+            }
+            _ if msg_type == SOCK_DIAG_BY_FAMILY => {
+                if let Some(socket) = parse_diag_msg(payload, protocol) {
+                    out.push(socket);
+                }
+            }
+            _ => {}
+        }
+
+        // Messages are aligned to 4 bytes (NLMSG_ALIGN)
+        offset += (msg_len + 3) & !3;
+    }
+
+    Ok(false)
+}
+
+/// Parse an inet_diag_msg payload
+fn parse_diag_msg(payload: &[u8], protocol: &'static str) -> Option<DiagSocket> {
+    if payload.len() < INET_DIAG_MSG_LEN {
+        return None;
+    }
+
+    let family = payload[0] as i32;
+    // inet_diag_sockid starts at offset 4, ports are big-endian
+    let port = u16::from_be_bytes([payload[4], payload[5]]);
+    let src = &payload[8..24];
+    let if_index = read_u32_ne(payload, 40);
+    let uid = read_u32_ne(payload, 64);
+    let inode = read_u32_ne(payload, 68) as u64;
+
+    let ip = match family {
+        libc::AF_INET => Ipv4Addr::new(src[0], src[1], src[2], src[3]).to_string(),
+        libc::AF_INET6 => {
+            let mut octets = [0u8; 16];
+            octets.copy_from_slice(src);
+            format!("[{}]", Ipv6Addr::from(octets))
+        }
+        _ => return None,
+    };
+
+    // Match ss, which appends %ifname for sockets bound to a device
+    let address = match interface_name(if_index) {
+        Some(name) => format!("{}%{}", ip, name),
+        None => ip,
+    };
+
+    Some(DiagSocket {
+        protocol,
+        address,
+        port,
+        inode,
+        uid,
+    })
+}
+
+fn interface_name(index: u32) -> Option<String> {
+    if index == 0 {
+        return None;
+    }
+    let mut name = [0 as libc::c_char; libc::IF_NAMESIZE];
+    // SAFETY: name is IF_NAMESIZE bytes long as required by if_indextoname(3)
+    let ptr = unsafe { libc::if_indextoname(index, name.as_mut_ptr()) };
+    if ptr.is_null() {
+        return None;
+    }
+    // SAFETY: on success the buffer holds a NUL-terminated interface name
+    let name = unsafe { std::ffi::CStr::from_ptr(name.as_ptr()) };
+    Some(name.to_string_lossy().into_owned())
+}
+
+fn read_u32_ne(buf: &[u8], offset: usize) -> u32 {
+    u32::from_ne_bytes([
+        buf[offset],
+        buf[offset + 1],
+        buf[offset + 2],
+        buf[offset + 3],
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header(len: usize, msg_type: u16) -> Vec<u8> {
+        let mut buf = Vec::new();
+        buf.extend_from_slice(&(len as u32).to_ne_bytes());
+        buf.extend_from_slice(&msg_type.to_ne_bytes());
+        buf.extend_from_slice(&0u16.to_ne_bytes());
+        buf.extend_from_slice(&1u32.to_ne_bytes());
+        buf.extend_from_slice(&0u32.to_ne_bytes());
+        buf
+    }
+
+    fn diag_msg(family: u8, port: u16, src: &[u8], uid: u32, inode: u32) -> Vec<u8> {
+        let mut buf = header(NLMSG_HDR_LEN + INET_DIAG_MSG_LEN, SOCK_DIAG_BY_FAMILY);
+        let mut payload = vec![0u8; INET_DIAG_MSG_LEN];
+        payload[0] = family;
+        payload[1] = TCP_LISTEN;
+        payload[4..6].copy_from_slice(&port.to_be_bytes());
+        payload[8..8 + src.len()].copy_from_slice(src);
+        payload[64..68].copy_from_slice(&uid.to_ne_bytes());
+        payload[68..72].copy_from_slice(&inode.to_ne_bytes());
+        buf.extend_from_slice(&payload);
+        buf
+    }
+
+    #[test]
+    fn test_build_request_layout() {
+        let req = build_request(
+            libc::AF_INET as u8,
+            libc::IPPROTO_TCP as u8,
+            1 << TCP_LISTEN,
+            7,
+        );
+        assert_eq!(req.len(), NLMSG_HDR_LEN + INET_DIAG_REQ_V2_LEN);
+        assert_eq!(read_u32_ne(&req, 0) as usize, req.len());
+        assert_eq!(u16::from_ne_bytes([req[4], req[5]]), SOCK_DIAG_BY_FAMILY);
+        assert_eq!(read_u32_ne(&req, 8), 7);
+        assert_eq!(req[16], libc::AF_INET as u8);
+        assert_eq!(req[17], libc::IPPROTO_TCP as u8);
+        assert_eq!(read_u32_ne(&req, 20), 1 << TCP_LISTEN);
+    }
+
+    #[test]
+    fn test_parse_ipv4_listener() {
+        let buf = diag_msg(libc::AF_INET as u8, 3000, &[127, 0, 0, 1], 1000, 4242);
+        let mut out = Vec::new();
+        let done = parse_messages(&buf, "tcp", &mut out).unwrap();
+
+        assert!(!done);
+        assert_eq!(
+            out,
+            vec![DiagSocket {
+                protocol: "tcp",
+                address: "127.0.0.1".to_string(),
+                port: 3000,
+                inode: 4242,
+                uid: 1000,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_parse_ipv6_listener() {
+        let mut loopback = [0u8; 16];
+        loopback[15] = 1;
+        let mut buf = diag_msg(libc::AF_INET6 as u8, 443, &[0u8; 16], 0, 1);
+        buf.extend(diag_msg(libc::AF_INET6 as u8, 8080, &loopback, 0, 2));
+        let mut out = Vec::new();
+        parse_messages(&buf, "udp", &mut out).unwrap();
+
+        assert_eq!(out.len(), 2);
+        assert_eq!(out[0].address, "[::]");
+        assert_eq!(out[0].port, 443);
+        assert_eq!(out[1].address, "[::1]");
+        assert_eq!(out[1].protocol, "udp");
+    }
+
+    #[test]
+    fn test_parse_done_stops() {
+        let mut buf = diag_msg(libc::AF_INET as u8, 22, &[0, 0, 0, 0], 0, 1);
+        buf.extend(header(NLMSG_HDR_LEN + 4, libc::NLMSG_DONE as u16));
+        buf.extend_from_slice(&0i32.to_ne_bytes());
+        let mut out = Vec::new();
+
+        assert!(parse_messages(&buf, "tcp", &mut out).unwrap());
+        assert_eq!(out.len(), 1);
+    }
+
+    #[test]
+    fn test_parse_error_message() {
+        let mut buf = header(NLMSG_HDR_LEN + 4, libc::NLMSG_ERROR as u16);
+        buf.extend_from_slice(&(-libc::EPERM).to_ne_bytes());
+        let mut out = Vec::new();
+
+        let err = parse_messages(&buf, "tcp", &mut out).unwrap_err();
+        assert_eq!(err.raw_os_error(), Some(libc::EPERM));
+    }
+
+    #[test]
+    fn test_parse_truncated_message() {
+        let mut buf = diag_msg(libc::AF_INET as u8, 22, &[0, 0, 0, 0], 0, 1);
+        buf.truncate(40);
+        let mut out = Vec::new();
+
+        assert!(parse_messages(&buf, "tcp", &mut out).is_err());
+    }
+}
@@ -0,0 +1,140 @@
+use super::linux::{proc_net_listeners, SocketIndex};
+use super::{NetNamespace, PortInfo};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
+
+/// Opt-in from settings: it costs a walk over /proc/<pid>/ns and a set of
+/// /proc/<pid>/net reads per namespace on every scan
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Where `ip netns add` bind-mounts the namespaces it names
+const NAMED_NETNS_DIR: &str = "/run/netns";
+
+pub fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// Listeners in every network namespace other than ours that holds at least
+/// one process we may inspect, each tagged with its namespace. /proc/<pid>/net
+/// shows the tables of the namespace `pid` is in, so no setns is needed.
+/// Owners come from `index`, the one the host scan built: it covers the fds
+/// of every process, whatever namespace it is in.
+pub fn scan_other_namespaces(index: &SocketIndex) -> Vec<PortInfo> {
+    let started = Instant::now();
+    let namespaces = other_namespaces();
+    if namespaces.is_empty() {
+        return Vec::new();
+    }
+
+    let mut ports = Vec::new();
+    for namespace in &namespaces {
+        let root = PathBuf::from(format!("/proc/{}", namespace.pid));
+        for mut info in proc_net_listeners(&root, index) {
+            info.net_namespace = Some(namespace.clone());
+            ports.push(info);
+        }
+    }
+
+    log::debug!(
+        "Scanned {} other network namespaces in {:?}: {} listeners",
+        namespaces.len(),
+        started.elapsed(),
+        ports.len()
+    );
+    ports
+}
+
+/// Distinct network namespaces other than ours, each represented by the
+/// lowest pid inside it. Reading another process's ns link needs ptrace
+/// access, so without root only the user's own processes are considered.
+/// Named namespaces with no process in them are not reachable this way.
+fn other_namespaces() -> Vec<NetNamespace> {
+    let own = namespace_of(Path::new("/proc/self"));
+    let mut members: BTreeMap<u64, u32> = BTreeMap::new();
+
+    if let Ok(entries) = fs::read_dir("/proc") {
+        for entry in entries.flatten() {
+            let pid: u32 = match entry.file_name().to_str().and_then(|s| s.parse().ok()) {
+                Some(pid) => pid,
+                None => continue,
+            };
+            let inode = match namespace_of(&entry.path()) {
+                Some(inode) if Some(inode) != own => inode,
+                _ => continue,
+            };
+            let lowest = members.entry(inode).or_insert(pid);
+            *lowest = (*lowest).min(pid);
+        }
+    }
+
+    let names = named_namespaces();
+    members
+        .into_iter()
+        .map(|(inode, pid)| NetNamespace {
+            inode,
+            name: names.get(&inode).cloned(),
+            pid,
+        })
+        .collect()
+}
+
+/// Namespace inode of the process whose /proc directory is `proc_dir`
+fn namespace_of(proc_dir: &Path) -> Option<u64> {
+    let link = fs::read_link(proc_dir.join("ns/net")).ok()?;
+    parse_ns_link(&link.to_string_lossy())
+}
+
+/// Parse a /proc/<pid>/ns/net link target of the form net:[4026531840]
+fn parse_ns_link(target: &str) -> Option<u64> {
+    target
+        .strip_prefix("net:[")?
+        .strip_suffix(']')?
+        .parse()
+        .ok()
+}
+
+/// Namespace inode -> name for the bind mounts `ip netns` keeps in /run/netns
+fn named_namespaces() -> HashMap<u64, String> {
+    let entries = match fs::read_dir(NAMED_NETNS_DIR) {
+        Ok(entries) => entries,
+        Err(_) => return HashMap::new(),
+    };
+    entries
+        .flatten()
+        .filter_map(|entry| {
+            // stat follows the bind mount to the nsfs inode
+            let inode = fs::metadata(entry.path()).ok()?.ino();
+            Some((inode, entry.file_name().to_string_lossy().into_owned()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ns_link() {
+        assert_eq!(parse_ns_link("net:[4026531840]"), Some(4026531840));
+        assert_eq!(parse_ns_link("mnt:[4026531840]"), None);
+        assert_eq!(parse_ns_link("net:[]"), None);
+        assert_eq!(parse_ns_link("socket:[123]"), None);
+    }
+
+    #[test]
+    fn test_other_namespaces_excludes_our_own() {
+        let own = namespace_of(Path::new("/proc/self"));
+        assert!(own.is_some());
+        assert!(other_namespaces()
+            .iter()
+            .all(|namespace| Some(namespace.inode) != own));
+    }
+}
@@ -0,0 +1,415 @@
+use super::{ProcessDetails, ScanError, ScanErrorKind, ScanResult};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+/// The fields of /proc/<pid>/stat we use
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProcStat {
+    pub pid: u32,
+    pub comm: String,
+    /// R, S, D, Z, T...
+    pub state: char,
+    pub ppid: u32,
+    /// Process group id
+    pub pgrp: u32,
+    /// Session id; equals pid for a session leader
+    pub session: u32,
+    /// Kernel PF_* flags
+    pub flags: u32,
+    /// User and system CPU time, in clock ticks
+    pub utime: u64,
+    pub stime: u64,
+    pub num_threads: u32,
+    /// Clock ticks after boot at which the process started
+    pub start_time: u64,
+    /// Resident set size, in pages
+    pub rss_pages: u64,
+}
+
+/// PF_KTHREAD: set for kernel threads
+const PF_KTHREAD: u32 = 0x0020_0000;
+
+impl ProcStat {
+    pub fn is_kernel_thread(&self) -> bool {
+        self.flags & PF_KTHREAD != 0
+    }
+}
+
+/// Read and parse /proc/<pid>/stat
+pub fn read_stat(pid: u32) -> ScanResult<ProcStat> {
+    let content =
+        fs::read_to_string(format!("/proc/{}/stat", pid)).map_err(|e| process_error(pid, e))?;
+    parse_stat(&content).ok_or_else(|| {
+        ScanError::new(
+            ScanErrorKind::ParseFailure,
+            format!("Unexpected /proc/{}/stat format", pid),
+        )
+        .with_pid(pid)
+    })
+}
+
+/// Stat of every process we can see, skipping ones that exit mid-walk
+pub fn all_stats() -> Vec<ProcStat> {
+    let entries = match fs::read_dir("/proc") {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+    entries
+        .flatten()
+        .filter_map(|entry| entry.file_name().to_str().and_then(|s| s.parse().ok()))
+        .filter_map(|pid| read_stat(pid).ok())
+        .collect()
+}
+
+/// Parse /proc/<pid>/stat: "pid (comm) state ppid ...". The comm may itself
+/// contain spaces and parentheses, so split on the last ')'.
+fn parse_stat(content: &str) -> Option<ProcStat> {
+    let open = content.find('(')?;
+    let close = content.rfind(')')?;
+    let pid = content[..open].trim().parse().ok()?;
+    let comm = content.get(open + 1..close)?.to_string();
+
+    // Fields after comm, starting at field 3 (state)
+    let fields: Vec<&str> = content.get(close + 1..)?.split_whitespace().collect();
+    let field = |n: usize| fields.get(n - 3).copied();
+
+    Some(ProcStat {
+        pid,
+        comm,
+        state: field(3)?.chars().next()?,
+        ppid: field(4)?.parse().ok()?,
+        pgrp: field(5)?.parse().ok()?,
+        session: field(6)?.parse().ok()?,
+        flags: field(9)?.parse().ok()?,
+        utime: field(14)?.parse().ok()?,
+        stime: field(15)?.parse().ok()?,
+        num_threads: field(20)?.parse().ok()?,
+        start_time: field(22)?.parse().ok()?,
+        rss_pages: field(24)?.parse().ok()?,
+    })
+}
+
+/// Full metadata for one process, tolerating fields we aren't allowed to read
+pub fn process_details(pid: u32) -> ScanResult<ProcessDetails> {
+    let stat = read_stat(pid)?;
+
+    let cmdline = read_cmdline(pid);
+    // exe and cwd need ptrace access: absent for other users' processes
+    let exe = fs::read_link(format!("/proc/{}/exe", pid))
+        .ok()
+        .map(|path| path.to_string_lossy().into_owned());
+    let cwd = read_cwd(pid).map(|path| path.to_string_lossy().into_owned());
+    let uid = read_uid(pid);
+    let user = uid.and_then(user_name);
+
+    Ok(ProcessDetails {
+        pid,
+        name: stat.comm,
+        cmdline,
+        exe,
+        cwd,
+        uid,
+        user,
+        ppid: Some(stat.ppid),
+        start_time_ms: start_time_ms(stat.start_time),
+    })
+}
+
+/// Split /proc/<pid>/cmdline into argv (NUL-separated, NUL-terminated).
+/// Empty arguments are real argv entries and are kept.
+fn parse_cmdline(raw: &[u8]) -> Vec<String> {
+    let raw = raw.strip_suffix(&[0]).unwrap_or(raw);
+    if raw.is_empty() {
+        return Vec::new();
+    }
+    raw.split(|&b| b == 0)
+        .map(|arg| String::from_utf8_lossy(arg).into_owned())
+        .collect()
+}
+
+/// argv of a process; empty for kernel threads, zombies and unreadable ones
+pub fn read_cmdline(pid: u32) -> Vec<String> {
+    fs::read(format!("/proc/{}/cmdline", pid))
+        .map(|raw| parse_cmdline(&raw))
+        .unwrap_or_default()
+}
+
+/// Working directory of a process; needs ptrace access like exe
+pub fn read_cwd(pid: u32) -> Option<PathBuf> {
+    fs::read_link(format!("/proc/{}/cwd", pid)).ok()
+}
+
+/// The process's path in systemd's cgroup hierarchy
+pub fn read_cgroup_path(pid: u32) -> Option<String> {
+    fs::read_to_string(format!("/proc/{}/cgroup", pid))
+        .ok()
+        .and_then(|content| parse_cgroup_path(&content).map(str::to_string))
+}
+
+/// Pick the path from /proc/<pid>/cgroup: the unified hierarchy (`0::/path`)
+/// or, on cgroup v1 and hybrid systems, the `name=systemd` one
+fn parse_cgroup_path(content: &str) -> Option<&str> {
+    let mut unified = None;
+    let mut named = None;
+    for line in content.lines() {
+        let mut parts = line.splitn(3, ':');
+        let (Some(id), Some(controllers), Some(path)) = (parts.next(), parts.next(), parts.next())
+        else {
+            continue;
+        };
+        if controllers == "name=systemd" {
+            named = Some(path);
+        } else if id == "0" && controllers.is_empty() {
+            unified = Some(path);
+        }
+    }
+    named.or(unified)
+}
+
+/// Real uid of a process
+pub fn read_uid(pid: u32) -> Option<u32> {
+    fs::read_to_string(format!("/proc/{}/status", pid))
+        .ok()
+        .and_then(|status| parse_status_uid(&status))
+}
+
+/// Real uid from the "Uid:" line of /proc/<pid>/status
+fn parse_status_uid(status: &str) -> Option<u32> {
+    status
+        .lines()
+        .find_map(|line| line.strip_prefix("Uid:"))
+        .and_then(|rest| rest.split_whitespace().next())
+        .and_then(|uid| uid.parse().ok())
+}
+
+/// Name of a user through NSS, so LDAP, SSSD and systemd-homed accounts
+/// resolve as well as those in /etc/passwd
+fn user_name(uid: u32) -> Option<String> {
+    let mut buf: Vec<libc::c_char> = vec![0; 1024];
+    loop {
+        // SAFETY: passwd is plain data, filled in by getpwuid_r
+        let mut entry: libc::passwd = unsafe { std::mem::zeroed() };
+        let mut found: *mut libc::passwd = std::ptr::null_mut();
+        // SAFETY: buf outlives the call and its real length is passed along
+        let err =
+            unsafe { libc::getpwuid_r(uid, &mut entry, buf.as_mut_ptr(), buf.len(), &mut found) };
+        // Entries with many group members or long fields need a larger buffer
+        if err == libc::ERANGE && buf.len() < 1 << 20 {
+            buf.resize(buf.len() * 2, 0);
+            continue;
+        }
+        if err != 0 || found.is_null() {
+            return None;
+        }
+        // SAFETY: on success pw_name is a NUL-terminated string inside buf
+        let name = unsafe { std::ffi::CStr::from_ptr(entry.pw_name) };
+        return Some(name.to_string_lossy().into_owned());
+    }
+}
+
+/// Resident memory in bytes from the "VmRSS:" line of /proc/<pid>/status
+pub fn read_rss_bytes(pid: u32) -> Option<u64> {
+    fs::read_to_string(format!("/proc/{}/status", pid))
+        .ok()
+        .and_then(|status| parse_status_rss(&status))
+}
+
+fn parse_status_rss(status: &str) -> Option<u64> {
+    status
+        .lines()
+        .find_map(|line| line.strip_prefix("VmRSS:"))
+        .and_then(|rest| rest.split_whitespace().next())
+        .and_then(|kb| kb.parse::<u64>().ok())
+        .map(|kb| kb * 1024)
+}
+
+/// Number of open descriptors in /proc/<pid>/fd (None without access)
+pub fn count_fds(pid: u32) -> Option<u32> {
+    fs::read_dir(format!("/proc/{}/fd", pid))
+        .ok()
+        .map(|entries| entries.count() as u32)
+}
+
+/// Convert a stat start time (ticks since boot) to Unix epoch milliseconds
+fn start_time_ms(start_ticks: u64) -> Option<u64> {
+    let boot_time = fs::read_to_string("/proc/stat")
+        .ok()
+        .and_then(|stat| parse_boot_time(&stat))?;
+    ticks_to_epoch_ms(boot_time, start_ticks, clock_ticks_per_second())
+}
+
+/// "btime" line of /proc/stat: boot time in seconds since the epoch
+fn parse_boot_time(stat: &str) -> Option<u64> {
+    stat.lines()
+        .find_map(|line| line.strip_prefix("btime "))
+        .and_then(|value| value.trim().parse().ok())
+}
+
+fn ticks_to_epoch_ms(boot_time_secs: u64, ticks: u64, ticks_per_second: u64) -> Option<u64> {
+    if ticks_per_second == 0 {
+        return None;
+    }
+    Some(boot_time_secs * 1000 + ticks * 1000 / ticks_per_second)
+}
+
+pub fn page_size() -> u64 {
+    // SAFETY: sysconf has no preconditions
+    let size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+    if size > 0 {
+        size as u64
+    } else {
+        4096
+    }
+}
+
+pub fn clock_ticks_per_second() -> u64 {
+    // SAFETY: sysconf has no preconditions
+    let ticks = unsafe { libc::sysconf(libc::_SC_CLK_TCK) };
+    if ticks > 0 {
+        ticks as u64
+    } else {
+        100
+    }
+}
+
+/// Map a failure to read /proc/<pid>/* to a ScanError for that pid
+fn process_error(pid: u32, err: io::Error) -> ScanError {
+    let error = if err.kind() == io::ErrorKind::NotFound {
+        ScanError::new(
+            ScanErrorKind::ProcessNotFound,
+            format!("Process {} not found", pid),
+        )
+    } else {
+        ScanError::from(err)
+    };
+    error.with_pid(pid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STAT: &str = "4242 (node) S 4200 4242 4200 34816 4242 4194304 12074 0 3 0 152 31 0 0 20 0 11 0 123456 1148067840 16000 18446744073709551615 1 1 0 0 0 0 0 16781312 17922 0 0 0 17 3 0 0 0 0 0";
+
+    #[test]
+    fn test_parse_stat() {
+        let stat = parse_stat(STAT).unwrap();
+        assert_eq!(stat.pid, 4242);
+        assert_eq!(stat.comm, "node");
+        assert_eq!(stat.state, 'S');
+        assert_eq!(stat.ppid, 4200);
+        assert_eq!(stat.pgrp, 4242);
+        assert_eq!(stat.session, 4200);
+        assert_eq!(stat.flags, 4194304);
+        assert!(!stat.is_kernel_thread());
+        assert_eq!(stat.utime, 152);
+        assert_eq!(stat.stime, 31);
+        assert_eq!(stat.num_threads, 11);
+        assert_eq!(stat.start_time, 123456);
+        assert_eq!(stat.rss_pages, 16000);
+    }
+
+    #[test]
+    fn test_parse_stat_comm_with_spaces_and_parens() {
+        let content = STAT.replace("(node)", "(my (weird) proc)");
+        let stat = parse_stat(&content).unwrap();
+        assert_eq!(stat.comm, "my (weird) proc");
+        assert_eq!(stat.ppid, 4200);
+    }
+
+    #[test]
+    fn test_parse_stat_truncated() {
+        assert!(parse_stat("4242 (node) S 4200").is_none());
+        assert!(parse_stat("").is_none());
+    }
+
+    #[test]
+    fn test_parse_cmdline() {
+        let raw = b"node\0/home/me/shop/node_modules/.bin/vite\0--port\x005173\0";
+        assert_eq!(
+            parse_cmdline(raw),
+            vec![
+                "node",
+                "/home/me/shop/node_modules/.bin/vite",
+                "--port",
+                "5173"
+            ]
+        );
+        assert!(parse_cmdline(b"").is_empty());
+        assert_eq!(
+            parse_cmdline(b"xterm\0--title\0\0-e\0top\0"),
+            vec!["xterm", "--title", "", "-e", "top"]
+        );
+        // Rewritten titles (setproctitle) may lack the final NUL
+        assert_eq!(
+            parse_cmdline(b"nginx: master process"),
+            vec!["nginx: master process"]
+        );
+    }
+
+    #[test]
+    fn test_parse_cgroup_path() {
+        assert_eq!(
+            parse_cgroup_path("0::/system.slice/nginx.service\n"),
+            Some("/system.slice/nginx.service")
+        );
+        let hybrid = "12:pids:/system.slice/sshd.service\n\
+                      1:name=systemd:/system.slice/cups.service\n\
+                      0::/\n";
+        assert_eq!(
+            parse_cgroup_path(hybrid),
+            Some("/system.slice/cups.service")
+        );
+        assert_eq!(parse_cgroup_path("4:memory:/docker/abc\n"), None);
+    }
+
+    #[test]
+    fn test_parse_status_uid() {
+        let status = "Name:\tnode\nState:\tS (sleeping)\nUid:\t1000\t1000\t1000\t1000\nGid:\t1000\t1000\t1000\t1000\n";
+        assert_eq!(parse_status_uid(status), Some(1000));
+        assert_eq!(parse_status_uid("Name:\tnode\n"), None);
+    }
+
+    #[test]
+    fn test_parse_status_rss() {
+        let status = "Name:\tnode\nVmPeak:\t 1200000 kB\nVmRSS:\t   64000 kB\nThreads:\t11\n";
+        assert_eq!(parse_status_rss(status), Some(64000 * 1024));
+        // Kernel threads have no VmRSS line
+        assert_eq!(parse_status_rss("Name:\tkthreadd\n"), None);
+    }
+
+    #[test]
+    fn test_user_name() {
+        assert_eq!(user_name(0).as_deref(), Some("root"));
+        assert_eq!(user_name(3_999_999_999), None);
+    }
+
+    #[test]
+    fn test_boot_time_and_ticks() {
+        let stat = "cpu  1 2 3 4\nbtime 1700000000\nprocesses 42\n";
+        assert_eq!(parse_boot_time(stat), Some(1_700_000_000));
+        assert_eq!(
+            ticks_to_epoch_ms(1_700_000_000, 250, 100),
+            Some(1_700_000_002_500)
+        );
+        assert_eq!(ticks_to_epoch_ms(1, 1, 0), None);
+    }
+
+    #[test]
+    fn test_process_details_self() {
+        let details = process_details(std::process::id()).unwrap();
+        assert_eq!(details.pid, std::process::id());
+        assert!(details.exe.is_some());
+        assert!(details.cwd.is_some());
+        assert!(!details.cmdline.is_empty());
+        assert!(details.start_time_ms.is_some());
+    }
+
+    #[test]
+    fn test_process_details_missing_pid() {
+        let error = process_details(u32::MAX).unwrap_err();
+        assert_eq!(error.kind, ScanErrorKind::ProcessNotFound);
+        assert_eq!(error.pid, Some(u32::MAX));
+    }
+}
@@ -0,0 +1,482 @@
+use super::{DevProject, PortInfo};
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
+
+/// Files that mark a project root, in the order they name the project
+const MARKERS: [&str; 6] = [
+    "package.json",
+    "Cargo.toml",
+    "pyproject.toml",
+    "go.mod",
+    "Gemfile",
+    ".git",
+];
+
+/// Dev servers recognisable from a script or module name in argv, as
+/// (name, label). Names are compared without a .js/.mjs/.cjs/.py extension.
+const ARGV_TOOLS: [(&str, &str); 14] = [
+    ("next", "Next.js"),
+    // Next.js rewrites its process title to "next-server (v14.2.3)"
+    ("next-server", "Next.js"),
+    ("vite", "Vite"),
+    ("webpack-dev-server", "webpack-dev-server"),
+    ("nuxt", "Nuxt"),
+    ("nuxi", "Nuxt"),
+    ("astro", "Astro"),
+    ("react-scripts", "Create React App"),
+    ("ng", "Angular CLI"),
+    ("uvicorn", "uvicorn"),
+    ("gunicorn", "gunicorn"),
+    ("flask", "Flask"),
+    ("rails", "Rails"),
+    ("puma", "Puma"),
+];
+
+/// What the detector needs to know about a process
+#[derive(Debug, Clone, Default)]
+pub struct ProcessContext {
+    pub cwd: Option<PathBuf>,
+    pub argv: Vec<String>,
+}
+
+/// A detection result; the branch is re-read from `git_head` on every scan
+/// since it changes under a running dev server
+#[derive(Debug, Clone)]
+struct Detected {
+    project: DevProject,
+    git_head: Option<PathBuf>,
+}
+
+/// Detections per (pid, start time). A process keeps its cwd and argv, so
+/// the walk up the tree happens once per process, not once per scan.
+type Detections = HashMap<(u32, u64), Option<Detected>>;
+
+static CACHE: Mutex<Option<Detections>> = Mutex::new(None);
+
+/// Set `project` on every entry whose process runs inside a project tree.
+/// `context` is only asked about processes not seen in an earlier scan.
+pub fn attach_projects(ports: &mut [PortInfo], context: impl Fn(u32) -> Option<ProcessContext>) {
+    let Ok(mut guard) = CACHE.lock() else {
+        return;
+    };
+    let cache = guard.get_or_insert_with(HashMap::new);
+    let home = env::var_os("HOME").map(PathBuf::from);
+
+    for info in ports.iter_mut() {
+        let Some(identity) = info.identity else {
+            continue;
+        };
+        let detected = cache
+            .entry((identity.pid, identity.start_time))
+            .or_insert_with(|| {
+                context(identity.pid).and_then(|context| detect(&context, home.as_deref()))
+            });
+        info.project = detected.as_ref().map(|detected| DevProject {
+            git_branch: detected.git_head.as_deref().and_then(read_branch),
+            ..detected.project.clone()
+        });
+    }
+
+    // Forget processes that no longer listen on anything
+    let live: Vec<(u32, u64)> = ports
+        .iter()
+        .filter_map(|info| info.identity.map(|id| (id.pid, id.start_time)))
+        .collect();
+    cache.retain(|key, _| live.contains(key));
+}
+
+/// Find the project a process belongs to: script paths in argv first, since
+/// they point at the code being run, then the working directory
+fn detect(context: &ProcessContext, home: Option<&Path>) -> Option<Detected> {
+    let (root, marker) = start_dirs(context)
+        .iter()
+        .find_map(|dir| find_root(dir, home))?;
+    let git_head = git_head(&root, home);
+
+    Some(Detected {
+        project: DevProject {
+            name: project_name(&root, marker),
+            root: root.to_string_lossy().into_owned(),
+            git_branch: None,
+            framework: framework(&context.argv, &root).map(str::to_string),
+        },
+        git_head,
+    })
+}
+
+/// Directories to start the search from. argv[0] is left out: interpreters
+/// live in toolchain checkouts (~/.pyenv, ~/.nvm) that look like projects.
+/// Anything inside node_modules is cut back to the package that installed it.
+fn start_dirs(context: &ProcessContext) -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    for arg in context.argv.iter().skip(1) {
+        if arg.starts_with('-') || !arg.contains('/') {
+            continue;
+        }
+        let path = Path::new(arg);
+        let path = match &context.cwd {
+            _ if path.is_absolute() => path.to_path_buf(),
+            Some(cwd) => cwd.join(path),
+            None => continue,
+        };
+        let path = outside_node_modules(&path);
+        if path.is_file() {
+            if let Some(parent) = path.parent() {
+                dirs.push(parent.to_path_buf());
+            }
+        } else if path.is_dir() {
+            dirs.push(path);
+        }
+    }
+    dirs.extend(context.cwd.clone());
+    dirs
+}
+
+fn outside_node_modules(path: &Path) -> PathBuf {
+    let mut kept = PathBuf::new();
+    for component in path.components() {
+        if component == Component::Normal("node_modules".as_ref()) {
+            break;
+        }
+        kept.push(component);
+    }
+    kept
+}
+
+/// Nearest directory at or above `start` holding a marker file. The
+/// filesystem root and the home directory (often a dotfiles repo) don't count.
+fn find_root(start: &Path, home: Option<&Path>) -> Option<(PathBuf, &'static str)> {
+    start
+        .ancestors()
+        .take_while(|dir| dir.parent().is_some() && Some(*dir) != home)
+        .find_map(|dir| {
+            MARKERS
+                .iter()
+                .find(|marker| dir.join(marker).exists())
+                .map(|marker| (dir.to_path_buf(), *marker))
+        })
+}
+
+/// Name from the manifest, falling back to the directory name
+fn project_name(root: &Path, marker: &str) -> String {
+    let manifest = fs::read_to_string(root.join(marker)).unwrap_or_default();
+    let declared = match marker {
+        "package.json" => serde_json::from_str::<serde_json::Value>(&manifest)
+            .ok()
+            .and_then(|value| value.get("name")?.as_str().map(str::to_string)),
+        "Cargo.toml" => toml_string(&manifest, "package", "name"),
+        "pyproject.toml" => toml_string(&manifest, "project", "name")
+            .or_else(|| toml_string(&manifest, "tool.poetry", "name")),
+        "go.mod" => go_module_name(&manifest),
+        _ => None,
+    };
+    declared
+        .filter(|name| !name.is_empty())
+        .or_else(|| {
+            root.file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+        })
+        .unwrap_or_else(|| root.to_string_lossy().into_owned())
+}
+
+/// A quoted string `key = "value"` from a `[section]` table. Enough for
+/// manifest names without pulling in a TOML parser.
+fn toml_string(content: &str, section: &str, key: &str) -> Option<String> {
+    let mut current = "";
+    for line in content.lines().map(str::trim) {
+        if let Some(header) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            current = header.trim();
+            continue;
+        }
+        if current != section {
+            continue;
+        }
+        let Some((name, value)) = line.split_once('=') else {
+            continue;
+        };
+        if name.trim() != key {
+            continue;
+        }
+        let value = value.trim();
+        let quote = value.chars().next().filter(|&c| c == '"' || c == '\'')?;
+        return value[1..].split(quote).next().map(str::to_string);
+    }
+    None
+}
+
+/// Last element of the module path, skipping a /vN major version suffix
+fn go_module_name(content: &str) -> Option<String> {
+    let path = content
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("module "))?
+        .trim()
+        .trim_matches('"');
+    path.rsplit('/')
+        .find(|segment| {
+            !(segment.len() > 1
+                && segment.starts_with('v')
+                && segment[1..].chars().all(|c| c.is_ascii_digit()))
+        })
+        .map(str::to_string)
+}
+
+/// The dev server or tool running, from argv; a binary built under the
+/// project's target/ directory is `cargo run`
+fn framework(argv: &[String], root: &Path) -> Option<&'static str> {
+    if let Some(program) = argv.first() {
+        let in_target = ["target/debug", "target/release"]
+            .iter()
+            .any(|dir| Path::new(program).starts_with(root.join(dir)));
+        if in_target && root.join("Cargo.toml").exists() {
+            return Some("cargo run");
+        }
+    }
+
+    let names: Vec<&str> = argv
+        .iter()
+        .filter(|arg| !arg.starts_with('-'))
+        .filter_map(|arg| {
+            let base = arg.rsplit('/').next()?.split_whitespace().next()?;
+            Some(
+                [".js", ".mjs", ".cjs", ".py"]
+                    .iter()
+                    .find_map(|ext| base.strip_suffix(ext))
+                    .unwrap_or(base),
+            )
+        })
+        .collect();
+
+    if names.contains(&"manage") && names.contains(&"runserver") {
+        return Some("Django");
+    }
+    if names.contains(&"webpack") && names.contains(&"serve") {
+        return Some("webpack-dev-server");
+    }
+    names.iter().find_map(|name| {
+        ARGV_TOOLS
+            .iter()
+            .find(|(tool, _)| tool == name)
+            .map(|(_, label)| *label)
+    })
+}
+
+/// HEAD of the repository containing `root`. A `.git` file (worktrees,
+/// submodules) points at the real git directory.
+fn git_head(root: &Path, home: Option<&Path>) -> Option<PathBuf> {
+    root.ancestors()
+        .take_while(|dir| dir.parent().is_some() && Some(*dir) != home)
+        .find_map(|dir| {
+            let dot_git = dir.join(".git");
+            if dot_git.is_dir() {
+                return Some(dot_git.join("HEAD"));
+            }
+            let pointer = fs::read_to_string(&dot_git).ok()?;
+            let git_dir = pointer.trim().strip_prefix("gitdir:")?.trim();
+            Some(dir.join(git_dir).join("HEAD"))
+        })
+}
+
+/// Branch checked out, or the abbreviated commit for a detached HEAD
+fn read_branch(head: &Path) -> Option<String> {
+    parse_head(&fs::read_to_string(head).ok()?)
+}
+
+fn parse_head(content: &str) -> Option<String> {
+    let content = content.trim();
+    if let Some(reference) = content.strip_prefix("ref:") {
+        let reference = reference.trim();
+        return Some(
+            reference
+                .strip_prefix("refs/heads/")
+                .unwrap_or(reference)
+                .to_string(),
+        );
+    }
+    (content.len() >= 7 && content.chars().all(|c| c.is_ascii_hexdigit()))
+        .then(|| content[..7].to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A scratch directory tree, removed when dropped
+    struct Tree(PathBuf);
+
+    impl Tree {
+        fn new(label: &str) -> Self {
+            let dir =
+                env::temp_dir().join(format!("unbind-project-{}-{}", label, std::process::id()));
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(&dir).unwrap();
+            Tree(dir)
+        }
+
+        fn file(&self, path: &str, content: &str) -> PathBuf {
+            let path = self.0.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, content).unwrap();
+            path
+        }
+    }
+
+    impl Drop for Tree {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn argv(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn test_detect_vite_through_node_modules() {
+        let tree = Tree::new("vite");
+        tree.file(
+            "shop/package.json",
+            r#"{"name": "my-shop", "private": true}"#,
+        );
+        tree.file(".git/HEAD", "ref: refs/heads/feature/cart\n");
+        let vite = tree.file("shop/node_modules/vite/bin/vite.js", "");
+        tree.file("shop/node_modules/vite/package.json", r#"{"name": "vite"}"#);
+
+        let context = ProcessContext {
+            cwd: Some(PathBuf::from("/")),
+            argv: argv(&["node", &vite.to_string_lossy(), "--port", "5173"]),
+        };
+        let detected = detect(&context, None).unwrap();
+
+        assert_eq!(detected.project.name, "my-shop");
+        assert_eq!(detected.project.root, tree.0.join("shop").to_string_lossy());
+        assert_eq!(detected.project.framework.as_deref(), Some("Vite"));
+        assert_eq!(
+            detected
+                .git_head
+                .as_deref()
+                .and_then(read_branch)
+                .as_deref(),
+            Some("feature/cart")
+        );
+    }
+
+    #[test]
+    fn test_detect_cargo_run_from_cwd() {
+        let tree = Tree::new("cargo");
+        tree.file(
+            "api/Cargo.toml",
+            "[workspace]\n\n[package]\nname = \"orders-api\"\nversion = \"0.1.0\"\n",
+        );
+        let binary = tree.file("api/target/debug/orders-api", "");
+
+        let context = ProcessContext {
+            cwd: Some(tree.0.join("api")),
+            argv: argv(&[&binary.to_string_lossy()]),
+        };
+        let detected = detect(&context, None).unwrap();
+
+        assert_eq!(detected.project.name, "orders-api");
+        assert_eq!(detected.project.framework.as_deref(), Some("cargo run"));
+    }
+
+    #[test]
+    fn test_detect_ignores_interpreter_path() {
+        let tree = Tree::new("interpreter");
+        tree.file(".pyenv/.git/HEAD", "ref: refs/heads/master\n");
+        let python = tree.file(".pyenv/versions/3.12.4/bin/python3", "");
+        tree.file("site/pyproject.toml", "[project]\nname = \"docs-site\"\n");
+
+        let context = ProcessContext {
+            cwd: Some(tree.0.join("site")),
+            argv: argv(&[&python.to_string_lossy(), "-m", "http.server"]),
+        };
+        let detected = detect(&context, None).unwrap();
+        assert_eq!(detected.project.name, "docs-site");
+        assert_eq!(detected.project.framework, None);
+    }
+
+    #[test]
+    fn test_detect_skips_home() {
+        let tree = Tree::new("home");
+        tree.file(".git/HEAD", "ref: refs/heads/main\n");
+        fs::create_dir_all(tree.0.join("Downloads")).unwrap();
+
+        let context = ProcessContext {
+            cwd: Some(tree.0.join("Downloads")),
+            argv: argv(&["python3", "-m", "http.server"]),
+        };
+        assert!(detect(&context, Some(&tree.0)).is_none());
+        assert!(detect(&context, None).is_some());
+    }
+
+    #[test]
+    fn test_framework_from_argv() {
+        let root = Path::new("/srv/app");
+        let label = |args: &[&str]| framework(&argv(args), root);
+
+        assert_eq!(label(&["next-server (v14.2.3)"]), Some("Next.js"));
+        assert_eq!(
+            label(&["/usr/bin/python3", "-m", "uvicorn", "app:app"]),
+            Some("uvicorn")
+        );
+        assert_eq!(
+            label(&["python", "manage.py", "runserver", "0.0.0.0:8000"]),
+            Some("Django")
+        );
+        assert_eq!(
+            label(&["node", "node_modules/.bin/webpack", "serve"]),
+            Some("webpack-dev-server")
+        );
+        assert_eq!(
+            label(&["ruby", "bin/rails", "server", "-p", "3000"]),
+            Some("Rails")
+        );
+        assert_eq!(label(&["node", "server.js"]), None);
+    }
+
+    #[test]
+    fn test_toml_string() {
+        let pyproject =
+            "[build-system]\nrequires = [\"hatchling\"]\n\n[tool.poetry]\nname = 'billing'\n";
+        assert_eq!(
+            toml_string(pyproject, "tool.poetry", "name").as_deref(),
+            Some("billing")
+        );
+        assert_eq!(toml_string(pyproject, "project", "name"), None);
+        assert_eq!(
+            toml_string(
+                "[package]\nname_suffix = \"x\"\nname = \"y\"",
+                "package",
+                "name"
+            )
+            .as_deref(),
+            Some("y")
+        );
+    }
+
+    #[test]
+    fn test_go_module_name() {
+        assert_eq!(
+            go_module_name("module github.com/acme/gateway/v2\n\ngo 1.22\n").as_deref(),
+            Some("gateway")
+        );
+        assert_eq!(go_module_name("module tools\n").as_deref(), Some("tools"));
+        assert_eq!(go_module_name("go 1.22\n"), None);
+    }
+
+    #[test]
+    fn test_parse_head() {
+        assert_eq!(
+            parse_head("ref: refs/heads/main\n").as_deref(),
+            Some("main")
+        );
+        assert_eq!(
+            parse_head("3f2a9c0d1e2b3a4f5e6d7c8b9a0f1e2d3c4b5a69\n").as_deref(),
+            Some("3f2a9c0")
+        );
+        assert_eq!(parse_head("garbage"), None);
+    }
+}
@@ -0,0 +1,414 @@
+use super::builtin::{self, BUILTIN_NAMES};
+use super::settings::{read_json, write_json};
+use super::{ProtectionPolicy, ProtectionRule, ScanError, ScanErrorKind, ScanResult};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+
+/// What the policy needs to know about a process
+#[derive(Debug, Clone, Default)]
+pub struct ProcessFacts {
+    pub name: String,
+    pub exe: Option<String>,
+    pub uid: Option<u32>,
+    pub kernel_thread: bool,
+}
+
+/// Port forwarders run by container engines. Killing one leaves the engine
+/// believing the port is still published; stopping the container is the fix.
+const CONTAINER_FORWARDERS: &[&str] = &["docker-proxy", "rootlessport", "rootlesskit", "conmon"];
+
+/// User-configured rules and the file they are saved in
+#[derive(Debug, Default)]
+struct UserRules {
+    path: Option<PathBuf>,
+    rules: Vec<ProtectionRule>,
+    /// The file didn't parse, so it holds edits we haven't seen and must not
+    /// be overwritten until it loads again
+    unreadable: bool,
+}
+
+static RULES: Mutex<UserRules> = Mutex::new(UserRules {
+    path: None,
+    rules: Vec::new(),
+    unreadable: false,
+});
+
+fn user_rules() -> Vec<ProtectionRule> {
+    RULES
+        .lock()
+        .map(|guard| guard.rules.clone())
+        .unwrap_or_default()
+}
+
+pub fn policy() -> ProtectionPolicy {
+    ProtectionPolicy {
+        builtin_names: BUILTIN_NAMES
+            .iter()
+            .chain(CONTAINER_FORWARDERS)
+            .map(|name| name.to_string())
+            .collect(),
+        rules: user_rules(),
+    }
+}
+
+/// Read the rules file, a JSON array of `ProtectionRule`, and remember its
+/// path for `set_rules`. A missing file means no rules. On a parse error
+/// the previous rules stay in place and `set_rules` refuses to overwrite the
+/// file until it loads.
+pub fn load_rules(path: &Path) -> ScanResult<usize> {
+    lock_rules()?.load(path)
+}
+
+/// Replace the rules and save them to the file given to `load_rules`
+pub fn set_rules(rules: Vec<ProtectionRule>) -> ScanResult<()> {
+    lock_rules()?.set(rules)
+}
+
+fn lock_rules() -> ScanResult<MutexGuard<'static, UserRules>> {
+    RULES
+        .lock()
+        .map_err(|_| ScanError::new(ScanErrorKind::Other, "Protection rules lock poisoned"))
+}
+
+impl UserRules {
+    fn load(&mut self, path: &Path) -> ScanResult<usize> {
+        let rules = read_json::<Vec<ProtectionRule>>(path, "protection rules");
+        self.path = Some(path.to_path_buf());
+        self.unreadable = rules.is_err();
+        let rules = rules?.unwrap_or_default();
+        let count = rules.len();
+        self.rules = rules;
+        Ok(count)
+    }
+
+    fn set(&mut self, rules: Vec<ProtectionRule>) -> ScanResult<()> {
+        if let (true, Some(path)) = (self.unreadable, &self.path) {
+            return Err(ScanError::new(
+                ScanErrorKind::ParseFailure,
+                format!(
+                    "Not saving protection rules over {}, which could not be read; fix or remove it first",
+                    path.display()
+                ),
+            ));
+        }
+        self.rules = rules;
+        match &self.path {
+            Some(path) => write_json(path, &self.rules),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Refuse to signal a protected process unless `force` is set
+pub fn check(pid: u32, force: bool) -> ScanResult<()> {
+    if force {
+        return Ok(());
+    }
+
+    let facts = process_facts(pid);
+    match protection_reason(pid, facts.as_ref(), &user_rules(), &Context::current()) {
+        None => Ok(()),
+        Some(reason) => {
+            let name = facts.map(|f| f.name).unwrap_or_default();
+            Err(ScanError::new(
+                ScanErrorKind::Protected,
+                format!(
+                    "{} ({}) is protected: {}. Use force to override.",
+                    name, pid, reason
+                ),
+            )
+            .with_pid(pid))
+        }
+    }
+}
+
+/// Our own identity, which is never a valid target
+struct Context {
+    own_pid: u32,
+    /// Leader of the session Unbind runs in (login shell or desktop session)
+    session_leader: Option<u32>,
+}
+
+impl Context {
+    fn current() -> Self {
+        Context {
+            own_pid: std::process::id(),
+            session_leader: builtin::session_leader(),
+        }
+    }
+}
+
+fn process_facts(pid: u32) -> Option<ProcessFacts> {
+    #[cfg(target_os = "linux")]
+    {
+        super::linux::process_facts(pid)
+    }
+    #[cfg(target_os = "macos")]
+    {
+        super::macos::process_facts(pid)
+    }
+    #[cfg(target_os = "windows")]
+    {
+        super::windows::process_facts(pid)
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+    {
+        let _ = pid;
+        None
+    }
+}
+
+fn protection_reason(
+    pid: u32,
+    facts: Option<&ProcessFacts>,
+    rules: &[ProtectionRule],
+    context: &Context,
+) -> Option<String> {
+    if let Some(reason) = builtin::pid_reason(pid, context.own_pid, context.session_leader) {
+        return Some(reason);
+    }
+
+    let facts = facts?;
+    let names = builtin::candidate_names(&facts.name, facts.exe.as_deref());
+    if let Some(reason) = builtin::process_reason(facts.kernel_thread, &names) {
+        return Some(reason);
+    }
+    if let Some(name) = names
+        .iter()
+        .find(|name| CONTAINER_FORWARDERS.contains(&name.as_str()))
+    {
+        return Some(format!(
+            "{} forwards a container's port; stop the container instead",
+            name
+        ));
+    }
+    rules
+        .iter()
+        .find(|rule| rule_matches(rule, facts, &names))
+        .map(|rule| format!("it matches the rule {}", describe_rule(rule)))
+}
+
+/// Every field set on the rule must match; a rule with no fields matches nothing
+fn rule_matches(rule: &ProtectionRule, facts: &ProcessFacts, names: &[String]) -> bool {
+    if rule.name.is_none() && rule.path.is_none() && rule.uid.is_none() {
+        return false;
+    }
+    let name_ok = rule.name.as_deref().map_or(true, |pattern| {
+        names.iter().any(|name| glob_match(pattern, name))
+    });
+    let path_ok = rule.path.as_deref().map_or(true, |pattern| {
+        facts
+            .exe
+            .as_deref()
+            .is_some_and(|exe| glob_match(pattern, exe))
+    });
+    let uid_ok = rule.uid.map_or(true, |uid| facts.uid == Some(uid));
+    name_ok && path_ok && uid_ok
+}
+
+fn describe_rule(rule: &ProtectionRule) -> String {
+    let mut parts = Vec::new();
+    if let Some(name) = &rule.name {
+        parts.push(format!("name={}", name));
+    }
+    if let Some(path) = &rule.path {
+        parts.push(format!("path={}", path));
+    }
+    if let Some(uid) = rule.uid {
+        parts.push(format!("uid={}", uid));
+    }
+    parts.join(" ")
+}
+
+/// Shell-style match supporting `*` (any run) and `?` (any one character)
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // Position of the last `*` and the text index it currently absorbs up to
+    let mut star: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, t));
+            p += 1;
+        } else if let Some((star_p, star_t)) = star {
+            p = star_p + 1;
+            t = star_t + 1;
+            star = Some((star_p, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn facts(name: &str, exe: Option<&str>, uid: Option<u32>) -> ProcessFacts {
+        ProcessFacts {
+            name: name.to_string(),
+            exe: exe.map(str::to_string),
+            uid,
+            kernel_thread: false,
+        }
+    }
+
+    fn context() -> Context {
+        Context {
+            own_pid: 500,
+            session_leader: Some(400),
+        }
+    }
+
+    fn rule(name: Option<&str>, path: Option<&str>, uid: Option<u32>) -> ProtectionRule {
+        ProtectionRule {
+            name: name.map(str::to_string),
+            path: path.map(str::to_string),
+            uid,
+        }
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("postgres", "postgres"));
+        assert!(glob_match("post*", "postgres"));
+        assert!(glob_match("*gres", "postgres"));
+        assert!(glob_match("p*t*s", "postgres"));
+        assert!(glob_match("node?", "node2"));
+        assert!(glob_match("*", ""));
+        assert!(glob_match(
+            "/usr/lib/*/bin/*",
+            "/usr/lib/postgresql/bin/postgres"
+        ));
+        assert!(!glob_match("post", "postgres"));
+        assert!(!glob_match("node?", "node"));
+        assert!(!glob_match("*x", "postgres"));
+    }
+
+    #[test]
+    fn test_builtin_protection() {
+        let ctx = context();
+        let node = facts("node", Some("/usr/bin/node"), Some(1000));
+
+        assert!(protection_reason(500, Some(&node), &[], &ctx).is_some());
+        assert!(protection_reason(400, Some(&node), &[], &ctx).is_some());
+        assert!(protection_reason(3000, Some(&node), &[], &ctx).is_none());
+
+        let sshd = facts("sshd", Some("/usr/sbin/sshd"), Some(0));
+        assert!(protection_reason(900, Some(&sshd), &[], &ctx).is_some());
+
+        let mut kthread = facts("kworker/0:1", None, Some(0));
+        kthread.kernel_thread = true;
+        assert!(protection_reason(12, Some(&kthread), &[], &ctx).is_some());
+
+        let proxy = facts("docker-proxy", Some("/usr/bin/docker-proxy"), Some(0));
+        let reason = protection_reason(1200, Some(&proxy), &[], &ctx).unwrap();
+        assert!(reason.contains("stop the container"));
+
+        // Unknown facts: only the pid-based checks apply
+        assert!(protection_reason(3000, None, &[], &ctx).is_none());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_init_is_protected() {
+        assert!(protection_reason(1, None, &[], &context()).is_some());
+    }
+
+    #[test]
+    fn test_truncated_comm_matches_exe_name() {
+        let session = facts(
+            "gnome-session-b",
+            Some("/usr/libexec/gnome-session-binary"),
+            Some(1000),
+        );
+        let rules = [rule(Some("gnome-session-binary"), None, None)];
+        assert!(protection_reason(800, Some(&session), &rules, &context()).is_some());
+    }
+
+    #[test]
+    fn test_user_rules() {
+        let ctx = context();
+        let postgres = facts(
+            "postgres",
+            Some("/usr/lib/postgresql/16/bin/postgres"),
+            Some(110),
+        );
+        let rules = [rule(Some("postgres"), None, None)];
+        assert!(protection_reason(700, Some(&postgres), &rules, &ctx).is_some());
+
+        let rules = [rule(None, Some("/usr/lib/postgresql/*"), None)];
+        assert!(protection_reason(700, Some(&postgres), &rules, &ctx).is_some());
+
+        let rules = [rule(None, None, Some(110))];
+        assert!(protection_reason(700, Some(&postgres), &rules, &ctx).is_some());
+
+        // All fields of a rule must match
+        let rules = [rule(Some("postgres"), None, Some(0))];
+        assert!(protection_reason(700, Some(&postgres), &rules, &ctx).is_none());
+
+        // An empty rule protects nothing
+        let rules = [rule(None, None, None)];
+        assert!(protection_reason(700, Some(&postgres), &rules, &ctx).is_none());
+    }
+
+    #[test]
+    fn test_rules_are_saved_and_loaded() {
+        let dir = std::env::temp_dir().join(format!("unbind-protect-{}", std::process::id()));
+        let path = dir.join("protection.json");
+        let _ = std::fs::remove_dir_all(&dir);
+
+        // A local store: the global one is shared with every other test
+        let mut store = UserRules::default();
+        assert_eq!(store.load(&path).unwrap(), 0);
+        let rules = vec![
+            rule(Some("postgres"), None, None),
+            rule(None, None, Some(110)),
+        ];
+        store.set(rules.clone()).unwrap();
+        assert_eq!(store.rules, rules);
+
+        let mut reloaded = UserRules::default();
+        assert_eq!(reloaded.load(&path).unwrap(), 2);
+        assert_eq!(reloaded.rules, rules);
+
+        // A broken file keeps the rules already loaded
+        std::fs::write(&path, "[{").unwrap();
+        assert_eq!(
+            reloaded.load(&path).unwrap_err().kind,
+            ScanErrorKind::ParseFailure
+        );
+        assert_eq!(reloaded.rules, rules);
+
+        // ...and isn't overwritten with them
+        assert_eq!(
+            reloaded.set(Vec::new()).unwrap_err().kind,
+            ScanErrorKind::ParseFailure
+        );
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "[{");
+        assert_eq!(reloaded.rules, rules);
+
+        // Once it loads again, saving works
+        std::fs::write(&path, "[]").unwrap();
+        assert_eq!(reloaded.load(&path).unwrap(), 0);
+        reloaded.set(rules.clone()).unwrap();
+        assert_eq!(store.load(&path).unwrap(), 2);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_check_force_overrides() {
+        let own = std::process::id();
+        let error = check(own, false).unwrap_err();
+        assert_eq!(error.kind, ScanErrorKind::Protected);
+        assert_eq!(error.pid, Some(own));
+        assert!(check(own, true).is_ok());
+    }
+}
@@ -0,0 +1,340 @@
+use super::{PortInfo, ScanError, ScanErrorKind, ScanResult, ServiceEntry, ServiceHint};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Which transport a registry entry applies to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Proto {
+    Tcp,
+    Udp,
+    Any,
+}
+
+impl Proto {
+    fn matches(self, protocol: Option<&str>) -> bool {
+        match (self, protocol) {
+            (Proto::Any, _) | (_, None) => true,
+            (Proto::Tcp, Some(protocol)) => protocol == "tcp",
+            (Proto::Udp, Some(protocol)) => protocol == "udp",
+        }
+    }
+}
+
+/// Ports developers run things on, checked before IANA so 3000 reads as a
+/// dev server rather than nothing, as (port, protocol, name, description)
+#[rustfmt::skip]
+const DEV_TOOLS: &[(u16, Proto, &str, &str)] = &[
+    (1025, Proto::Tcp, "Mailpit SMTP", "Local mail catcher (Mailpit, MailHog)"),
+    (1313, Proto::Tcp, "Hugo", "Hugo dev server"),
+    (3000, Proto::Tcp, "Dev server", "Next.js, Rails, Create React App, Grafana"),
+    (4000, Proto::Tcp, "Dev server", "Phoenix, Jekyll, Gatsby"),
+    (4173, Proto::Tcp, "Vite preview", "vite preview"),
+    (4200, Proto::Tcp, "Angular CLI", "ng serve"),
+    (4321, Proto::Tcp, "Astro", "Astro dev server"),
+    (5000, Proto::Tcp, "Flask", "Flask dev server; AirPlay Receiver on macOS"),
+    (5037, Proto::Tcp, "adb server", "Android Debug Bridge server"),
+    (5173, Proto::Tcp, "Vite", "Vite dev server"),
+    (5555, Proto::Tcp, "Prisma Studio", "Prisma Studio; adb over TCP"),
+    (6006, Proto::Tcp, "Storybook", "Storybook dev server"),
+    (7860, Proto::Tcp, "Gradio", "Gradio app"),
+    (8000, Proto::Tcp, "Dev server", "Django, uvicorn, python -m http.server"),
+    (8025, Proto::Tcp, "Mailpit", "Local mail catcher web UI (Mailpit, MailHog)"),
+    (8080, Proto::Tcp, "HTTP alternate", "Common for proxies, Tomcat and dev servers"),
+    (8081, Proto::Tcp, "Metro", "React Native bundler"),
+    (8443, Proto::Tcp, "HTTPS alternate", "Common for proxies and dev servers"),
+    (8501, Proto::Tcp, "Streamlit", "Streamlit app"),
+    (8888, Proto::Tcp, "Jupyter", "Jupyter Notebook / JupyterLab"),
+    (9000, Proto::Tcp, "PHP-FPM", "PHP FastCGI; also MinIO and SonarQube"),
+    (9090, Proto::Tcp, "Prometheus", "Prometheus server"),
+    (9222, Proto::Tcp, "Chrome DevTools", "Chrome remote debugging"),
+    (9229, Proto::Tcp, "Node inspector", "node --inspect debugger"),
+    (11434, Proto::Tcp, "Ollama", "Ollama API"),
+    (15672, Proto::Tcp, "RabbitMQ management", "RabbitMQ management UI"),
+    (19000, Proto::Tcp, "Expo", "Expo dev server"),
+    (24678, Proto::Tcp, "Vite HMR", "Vite hot module reload websocket"),
+    (35729, Proto::Tcp, "LiveReload", "LiveReload server"),
+];
+
+/// IANA-registered services people actually meet, well-known and registered ranges
+#[rustfmt::skip]
+const IANA: &[(u16, Proto, &str, &str)] = &[
+    (20, Proto::Tcp, "FTP data", "File Transfer Protocol data"),
+    (21, Proto::Tcp, "FTP", "File Transfer Protocol control"),
+    (22, Proto::Tcp, "SSH", "Secure Shell"),
+    (23, Proto::Tcp, "Telnet", "Telnet"),
+    (25, Proto::Tcp, "SMTP", "Mail transfer"),
+    (53, Proto::Any, "DNS", "Domain Name System"),
+    (67, Proto::Udp, "DHCP server", "Dynamic Host Configuration Protocol"),
+    (68, Proto::Udp, "DHCP client", "Dynamic Host Configuration Protocol"),
+    (69, Proto::Udp, "TFTP", "Trivial File Transfer Protocol"),
+    (80, Proto::Tcp, "HTTP", "Web server"),
+    (88, Proto::Any, "Kerberos", "Kerberos authentication"),
+    (110, Proto::Tcp, "POP3", "Mail retrieval"),
+    (111, Proto::Any, "rpcbind", "ONC RPC port mapper"),
+    (123, Proto::Udp, "NTP", "Network Time Protocol"),
+    (137, Proto::Udp, "NetBIOS name", "NetBIOS name service"),
+    (138, Proto::Udp, "NetBIOS datagram", "NetBIOS datagram service"),
+    (139, Proto::Tcp, "NetBIOS session", "NetBIOS session service"),
+    (143, Proto::Tcp, "IMAP", "Mail access"),
+    (161, Proto::Udp, "SNMP", "Simple Network Management Protocol"),
+    (162, Proto::Udp, "SNMP trap", "SNMP notifications"),
+    (389, Proto::Tcp, "LDAP", "Directory access"),
+    (443, Proto::Tcp, "HTTPS", "Web server over TLS"),
+    (443, Proto::Udp, "HTTP/3", "HTTP over QUIC"),
+    (445, Proto::Tcp, "SMB", "Windows file sharing"),
+    (465, Proto::Tcp, "SMTPS", "Mail submission over TLS"),
+    (500, Proto::Udp, "IKE", "IPsec key exchange"),
+    (514, Proto::Udp, "syslog", "Remote logging"),
+    (515, Proto::Tcp, "LPD", "Line printer daemon"),
+    (546, Proto::Udp, "DHCPv6 client", "DHCP for IPv6"),
+    (547, Proto::Udp, "DHCPv6 server", "DHCP for IPv6"),
+    (548, Proto::Tcp, "AFP", "Apple Filing Protocol"),
+    (587, Proto::Tcp, "SMTP submission", "Mail submission"),
+    (631, Proto::Any, "IPP", "Internet Printing Protocol (CUPS)"),
+    (636, Proto::Tcp, "LDAPS", "Directory access over TLS"),
+    (853, Proto::Tcp, "DNS over TLS", "Encrypted DNS"),
+    (873, Proto::Tcp, "rsync", "rsync daemon"),
+    (993, Proto::Tcp, "IMAPS", "Mail access over TLS"),
+    (995, Proto::Tcp, "POP3S", "Mail retrieval over TLS"),
+    (1080, Proto::Tcp, "SOCKS", "SOCKS proxy"),
+    (1194, Proto::Udp, "OpenVPN", "OpenVPN"),
+    (1433, Proto::Tcp, "SQL Server", "Microsoft SQL Server"),
+    (1521, Proto::Tcp, "Oracle", "Oracle database listener"),
+    (1723, Proto::Tcp, "PPTP", "Point-to-Point Tunneling Protocol"),
+    (1883, Proto::Tcp, "MQTT", "Message broker"),
+    (1900, Proto::Udp, "SSDP", "UPnP discovery"),
+    (2049, Proto::Any, "NFS", "Network File System"),
+    (2375, Proto::Tcp, "Docker API", "Docker Engine API, unencrypted"),
+    (2376, Proto::Tcp, "Docker API (TLS)", "Docker Engine API over TLS"),
+    (2379, Proto::Tcp, "etcd", "etcd client API"),
+    (2380, Proto::Tcp, "etcd peer", "etcd peer traffic"),
+    (3306, Proto::Tcp, "MySQL", "MySQL / MariaDB"),
+    (3389, Proto::Any, "RDP", "Remote Desktop"),
+    (3478, Proto::Any, "STUN/TURN", "NAT traversal"),
+    (4369, Proto::Tcp, "EPMD", "Erlang port mapper"),
+    (5060, Proto::Any, "SIP", "Session Initiation Protocol"),
+    (5353, Proto::Udp, "mDNS", "Multicast DNS (Bonjour, Avahi)"),
+    (5355, Proto::Udp, "LLMNR", "Link-local name resolution"),
+    (5432, Proto::Tcp, "PostgreSQL", "PostgreSQL"),
+    (5672, Proto::Tcp, "AMQP", "RabbitMQ and other AMQP brokers"),
+    (5900, Proto::Tcp, "VNC", "Remote framebuffer (Screen Sharing)"),
+    (6379, Proto::Tcp, "Redis", "Redis"),
+    (6443, Proto::Tcp, "Kubernetes API", "Kubernetes API server"),
+    (8883, Proto::Tcp, "MQTT over TLS", "Message broker over TLS"),
+    (9042, Proto::Tcp, "Cassandra", "Cassandra CQL"),
+    (9092, Proto::Tcp, "Kafka", "Kafka broker"),
+    (9200, Proto::Tcp, "Elasticsearch", "Elasticsearch / OpenSearch REST API"),
+    (9300, Proto::Tcp, "Elasticsearch transport", "Elasticsearch node traffic"),
+    (11211, Proto::Any, "memcached", "memcached"),
+    (27017, Proto::Tcp, "MongoDB", "MongoDB"),
+    (51820, Proto::Udp, "WireGuard", "WireGuard VPN"),
+];
+
+/// Entries from the user's overrides file and where it was read from
+#[derive(Debug, Default)]
+struct Overrides {
+    path: Option<PathBuf>,
+    entries: Vec<ServiceEntry>,
+}
+
+static OVERRIDES: Mutex<Overrides> = Mutex::new(Overrides {
+    path: None,
+    entries: Vec::new(),
+});
+
+/// Read the overrides file, a JSON array of `ServiceEntry`, and remember its
+/// path for `reload_overrides`. A missing file means no overrides. On a
+/// parse error the previous entries stay in place.
+pub fn load_overrides(path: &Path) -> ScanResult<usize> {
+    let entries = read_overrides(path);
+    let mut guard = OVERRIDES
+        .lock()
+        .map_err(|_| ScanError::new(ScanErrorKind::Other, "Service overrides lock poisoned"))?;
+    guard.path = Some(path.to_path_buf());
+    let entries = entries?;
+    let count = entries.len();
+    guard.entries = entries;
+    Ok(count)
+}
+
+/// Re-read the file given to `load_overrides`, after the user edited it
+pub fn reload_overrides() -> ScanResult<usize> {
+    let path = OVERRIDES
+        .lock()
+        .ok()
+        .and_then(|guard| guard.path.clone())
+        .ok_or_else(|| {
+            ScanError::new(
+                ScanErrorKind::Other,
+                "No service overrides file has been configured",
+            )
+        })?;
+    load_overrides(&path)
+}
+
+fn read_overrides(path: &Path) -> ScanResult<Vec<ServiceEntry>> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    serde_json::from_str(&content).map_err(|e| {
+        ScanError::new(
+            ScanErrorKind::ParseFailure,
+            format!("Invalid service overrides in {}: {}", path.display(), e),
+        )
+    })
+}
+
+/// What usually listens on `port`: the user's overrides first, then the
+/// developer tool list, then IANA. `protocol` None matches any transport.
+pub fn lookup(port: u16, protocol: Option<&str>) -> Option<ServiceHint> {
+    let entries = OVERRIDES
+        .lock()
+        .map(|guard| guard.entries.clone())
+        .unwrap_or_default();
+    lookup_in(&entries, port, protocol)
+}
+
+fn lookup_in(overrides: &[ServiceEntry], port: u16, protocol: Option<&str>) -> Option<ServiceHint> {
+    let user = overrides.iter().find(|entry| {
+        entry.port == port
+            && match (entry.protocol.as_deref(), protocol) {
+                (Some(wanted), Some(protocol)) => wanted == protocol,
+                _ => true,
+            }
+    });
+    if let Some(entry) = user {
+        return Some(ServiceHint {
+            name: entry.name.clone(),
+            description: entry.description.clone(),
+            user_defined: true,
+        });
+    }
+
+    DEV_TOOLS
+        .iter()
+        .chain(IANA)
+        .find(|(p, proto, _, _)| *p == port && proto.matches(protocol))
+        .map(|(_, _, name, description)| ServiceHint {
+            name: name.to_string(),
+            description: Some(description.to_string()),
+            user_defined: false,
+        })
+}
+
+/// Set `service_hint` on every TCP and UDP entry. Unix sockets have no port
+/// and the raw table's "port" is an IP protocol number, so both are skipped.
+pub fn attach_hints(ports: &mut [PortInfo]) {
+    let overrides = OVERRIDES
+        .lock()
+        .map(|guard| guard.entries.clone())
+        .unwrap_or_default();
+    for info in ports.iter_mut() {
+        if matches!(info.protocol.as_str(), "tcp" | "udp") {
+            info.service_hint = lookup_in(&overrides, info.port, Some(&info.protocol));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(port: u16, protocol: Option<&str>, name: &str) -> ServiceEntry {
+        ServiceEntry {
+            port,
+            protocol: protocol.map(str::to_string),
+            name: name.to_string(),
+            description: None,
+        }
+    }
+
+    #[test]
+    fn test_lookup_builtin() {
+        let hint = lookup_in(&[], 5432, Some("tcp")).unwrap();
+        assert_eq!(hint.name, "PostgreSQL");
+        assert!(!hint.user_defined);
+
+        assert_eq!(lookup_in(&[], 5173, None).unwrap().name, "Vite");
+        assert_eq!(
+            lookup_in(&[], 9229, Some("tcp")).unwrap().name,
+            "Node inspector"
+        );
+        assert_eq!(lookup_in(&[], 443, Some("udp")).unwrap().name, "HTTP/3");
+        assert_eq!(lookup_in(&[], 53, Some("udp")).unwrap().name, "DNS");
+        assert!(lookup_in(&[], 6379, Some("udp")).is_none());
+        assert!(lookup_in(&[], 49999, None).is_none());
+    }
+
+    #[test]
+    fn test_lookup_overrides_win() {
+        let overrides = vec![
+            entry(3000, None, "shop frontend"),
+            entry(5432, Some("udp"), "not postgres"),
+        ];
+        let hint = lookup_in(&overrides, 3000, Some("tcp")).unwrap();
+        assert_eq!(hint.name, "shop frontend");
+        assert!(hint.user_defined);
+
+        // A protocol-specific override leaves the other protocol alone
+        assert_eq!(
+            lookup_in(&overrides, 5432, Some("tcp")).unwrap().name,
+            "PostgreSQL"
+        );
+    }
+
+    #[test]
+    fn test_registry_has_no_duplicates() {
+        for table in [DEV_TOOLS, IANA] {
+            for (i, (port, proto, name, _)) in table.iter().enumerate() {
+                assert!(
+                    !table[i + 1..]
+                        .iter()
+                        .any(|(p, other, _, _)| p == port && other == proto),
+                    "{} listed twice",
+                    name
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_attach_hints_skips_unix_and_raw() {
+        let mut ports = vec![
+            PortInfo::new(6379, "tcp", "127.0.0.1".to_string(), vec![]),
+            PortInfo::new(0, "unix", "/run/docker.sock".to_string(), vec![]),
+            PortInfo::new(58, "raw", "[::]".to_string(), vec![]),
+        ];
+        attach_hints(&mut ports);
+        assert_eq!(ports[0].service_hint.as_ref().unwrap().name, "Redis");
+        assert!(ports[1].service_hint.is_none());
+        assert!(ports[2].service_hint.is_none());
+    }
+
+    #[test]
+    fn test_read_overrides() {
+        let dir = std::env::temp_dir().join(format!("unbind-services-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("services.json");
+
+        assert!(read_overrides(&path).unwrap().is_empty());
+
+        fs::write(
+            &path,
+            r#"[{"port": 4000, "protocol": "tcp", "name": "Phoenix", "description": "mix phx.server"},
+                {"port": 7777, "name": "game server"}]"#,
+        )
+        .unwrap();
+        let entries = read_overrides(&path).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[1], entry(7777, None, "game server"));
+
+        fs::write(&path, "{ not json").unwrap();
+        let error = read_overrides(&path).unwrap_err();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(error.kind, ScanErrorKind::ParseFailure);
+    }
+}
@@ -0,0 +1,145 @@
+use super::{ScanError, ScanErrorKind, ScanResult};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+/// Scanner options chosen in settings. The backend keeps them rather than
+/// the webview so they apply from the first scan after startup.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScannerSettings {
+    /// Backend tried first, None for the default order
+    pub scan_backend: Option<String>,
+    /// Also list listeners in other network namespaces (Linux)
+    pub namespace_scanning: bool,
+}
+
+/// The settings file and what it currently holds
+struct Store {
+    path: Option<PathBuf>,
+    settings: ScannerSettings,
+    /// The file didn't parse; it is left alone until it loads again
+    unreadable: bool,
+}
+
+static STORE: Mutex<Store> = Mutex::new(Store {
+    path: None,
+    settings: ScannerSettings {
+        scan_backend: None,
+        namespace_scanning: false,
+    },
+    unreadable: false,
+});
+
+fn store() -> MutexGuard<'static, Store> {
+    STORE.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Read the settings file and remember its path for `update`. A missing
+/// file means the defaults; one that doesn't parse is not overwritten.
+pub fn load(path: &Path) -> ScanResult<ScannerSettings> {
+    let mut store = store();
+    store.path = Some(path.to_path_buf());
+    let settings = read_json::<ScannerSettings>(path, "scanner settings");
+    store.unreadable = settings.is_err();
+    let settings = settings?.unwrap_or_default();
+    store.settings = settings.clone();
+    Ok(settings)
+}
+
+/// The settings in effect (the defaults until `load`)
+pub fn current() -> ScannerSettings {
+    store().settings.clone()
+}
+
+/// Apply `change` and write the result back to the file given to `load`
+pub fn update(change: impl FnOnce(&mut ScannerSettings)) -> ScanResult<()> {
+    let mut store = store();
+    change(&mut store.settings);
+    match &store.path {
+        Some(path) if store.unreadable => Err(ScanError::new(
+            ScanErrorKind::ParseFailure,
+            format!(
+                "Not saving scanner settings over {}, which could not be read; fix or remove it first",
+                path.display()
+            ),
+        )),
+        Some(path) => write_json(path, &store.settings),
+        None => {
+            log::debug!("No scanner settings file configured, not saving");
+            Ok(())
+        }
+    }
+}
+
+/// Parse a JSON config file, None if it doesn't exist
+pub fn read_json<T: DeserializeOwned>(path: &Path, what: &str) -> ScanResult<Option<T>> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    serde_json::from_str(&content).map(Some).map_err(|e| {
+        ScanError::new(
+            ScanErrorKind::ParseFailure,
+            format!("Invalid {} in {}: {}", what, path.display(), e),
+        )
+    })
+}
+
+/// Write a JSON config file through a temporary file, so a crash mid-write
+/// can't leave it truncated
+pub fn write_json<T: Serialize>(path: &Path, value: &T) -> ScanResult<()> {
+    let content = serde_json::to_string_pretty(value)
+        .map_err(|e| ScanError::new(ScanErrorKind::Other, e.to_string()))?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let partial = path.with_extension("json.partial");
+    fs::write(&partial, content)?;
+    fs::rename(&partial, path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("unbind-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let dir = temp_dir("settings-json");
+        let path = dir.join("nested/scanner.json");
+        assert_eq!(
+            read_json::<ScannerSettings>(&path, "settings").unwrap(),
+            None
+        );
+
+        let settings = ScannerSettings {
+            scan_backend: Some("proc".to_string()),
+            namespace_scanning: true,
+        };
+        write_json(&path, &settings).unwrap();
+        assert_eq!(read_json(&path, "settings").unwrap(), Some(settings));
+        assert!(!path.with_extension("json.partial").exists());
+
+        fs::write(&path, "{ not json").unwrap();
+        let error = read_json::<ScannerSettings>(&path, "settings").unwrap_err();
+        assert_eq!(error.kind, ScanErrorKind::ParseFailure);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_missing_fields_take_defaults() {
+        let settings: ScannerSettings = serde_json::from_str("{}").unwrap();
+        assert_eq!(settings, ScannerSettings::default());
+    }
+}
@@ -0,0 +1,88 @@
+use super::{ScanError, ScanErrorKind, ScanResult, Signal};
+use std::io;
+
+/// Send a signal with kill(2) rather than spawning /bin/kill
+pub fn send(pid: u32, signal: libc::c_int) -> ScanResult<()> {
+    let target = target_pid(pid)?;
+    // SAFETY: kill has no memory-safety preconditions; target is a single positive pid
+    if unsafe { libc::kill(target, signal) } == 0 {
+        Ok(())
+    } else {
+        Err(ScanError::from(io::Error::last_os_error()).with_pid(pid))
+    }
+}
+
+/// The platform's number for a signal
+pub fn raw(sig: Signal) -> libc::c_int {
+    match sig {
+        Signal::Hup => libc::SIGHUP,
+        Signal::Int => libc::SIGINT,
+        Signal::Quit => libc::SIGQUIT,
+        Signal::Term => libc::SIGTERM,
+        Signal::Kill => libc::SIGKILL,
+        Signal::Usr1 => libc::SIGUSR1,
+        Signal::Usr2 => libc::SIGUSR2,
+        Signal::Stop => libc::SIGSTOP,
+        Signal::Cont => libc::SIGCONT,
+    }
+}
+
+/// Whether `pid` still exists (zombies included). EPERM means it exists but
+/// belongs to someone else.
+pub fn exists(pid: u32) -> bool {
+    let Ok(target) = target_pid(pid) else {
+        return false;
+    };
+    // SAFETY: signal 0 only checks for existence and permission
+    if unsafe { libc::kill(target, 0) } == 0 {
+        return true;
+    }
+    io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+/// kill(2) treats 0 and negative pids as process groups (-1 is "everything
+/// we may signal"), so refuse anything that isn't a single positive pid
+fn target_pid(pid: u32) -> ScanResult<libc::pid_t> {
+    match libc::pid_t::try_from(pid) {
+        Ok(target) if target > 0 => Ok(target),
+        _ => {
+            Err(ScanError::new(ScanErrorKind::Other, format!("Invalid pid: {}", pid)).with_pid(pid))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rejects_group_pids() {
+        assert_eq!(send(0, 0).unwrap_err().kind, ScanErrorKind::Other);
+        // Would wrap to -1 and signal every process we own
+        assert_eq!(send(u32::MAX, 0).unwrap_err().kind, ScanErrorKind::Other);
+        assert!(!exists(0));
+        assert!(!exists(u32::MAX));
+    }
+
+    #[test]
+    fn test_exists_self() {
+        assert!(exists(std::process::id()));
+        assert!(send(std::process::id(), 0).is_ok());
+    }
+
+    #[test]
+    fn test_terminate_child() {
+        let mut child = std::process::Command::new("sleep")
+            .arg("30")
+            .spawn()
+            .unwrap();
+        let pid = child.id();
+        send(pid, libc::SIGTERM).unwrap();
+        child.wait().unwrap();
+        assert!(!exists(pid));
+
+        let error = send(pid, 0).unwrap_err();
+        assert_eq!(error.kind, ScanErrorKind::ProcessNotFound);
+        assert_eq!(error.pid, Some(pid));
+    }
+}
@@ -0,0 +1,456 @@
+use super::{procfs, PortInfo, ScanError, ScanErrorKind, ScanResult, SystemdUnit};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use zbus::blocking::connection::Builder as ConnectionBuilder;
+use zbus::blocking::proxy::Builder as ProxyBuilder;
+use zbus::blocking::{Connection, Proxy};
+use zbus::proxy::{CacheProperties, MethodFlags};
+use zbus::zvariant::OwnedObjectPath;
+
+const DESTINATION: &str = "org.freedesktop.systemd1";
+const MANAGER_PATH: &str = "/org/freedesktop/systemd1";
+const MANAGER_INTERFACE: &str = "org.freedesktop.systemd1.Manager";
+const UNIT_INTERFACE: &str = "org.freedesktop.systemd1.Unit";
+const SOCKET_INTERFACE: &str = "org.freedesktop.systemd1.Socket";
+
+/// A stuck bus must not stall the scan
+const DBUS_TIMEOUT: Duration = Duration::from_secs(2);
+/// How long socket units' listen addresses are reused across scans
+const SOCKET_CACHE_TTL: Duration = Duration::from_secs(10);
+
+/// Where a process sits in the systemd tree, from its cgroup path
+#[derive(Debug, PartialEq, Eq)]
+enum Placement {
+    /// Runs inside a service
+    Service(SystemdUnit),
+    /// Is a service manager (pid 1, or a user's `systemd --user`) holding
+    /// sockets on behalf of socket-activated units. `user` as in SystemdUnit.
+    Manager { user: Option<u32> },
+}
+
+/// One `.socket` unit and what it listens on
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct SocketUnit {
+    name: String,
+    listen: Vec<Listen>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Listen {
+    Inet { protocol: &'static str, port: u16 },
+    Unix(String),
+}
+
+/// Socket units of the system manager and of our own user manager
+struct SocketCache {
+    system: Option<(Instant, Vec<SocketUnit>)>,
+    user: Option<(Instant, Vec<SocketUnit>)>,
+}
+
+static SOCKET_CACHE: Mutex<SocketCache> = Mutex::new(SocketCache {
+    system: None,
+    user: None,
+});
+
+/// Fill in `systemd_unit` for every listener
+pub fn attach_units(ports: &mut [PortInfo]) {
+    let mut placements: HashMap<u32, Option<Placement>> = HashMap::new();
+    for info in ports.iter_mut() {
+        let placement = placements.entry(info.pid).or_insert_with(|| {
+            procfs::read_cgroup_path(info.pid).and_then(|path| placement_from_path(&path))
+        });
+        info.systemd_unit = match placement {
+            Some(Placement::Service(unit)) => Some(unit.clone()),
+            Some(Placement::Manager { user }) => socket_unit_for(info, *user),
+            None => None,
+        };
+    }
+}
+
+/// Stop a unit. A `.socket` unit's triggered service is stopped too: it
+/// inherited the listening socket, so stopping only the socket frees nothing.
+pub fn stop_unit(unit: &SystemdUnit) -> ScanResult<()> {
+    let connection = connect_for(unit)?;
+    let manager = proxy(&connection, MANAGER_PATH, MANAGER_INTERFACE)?;
+    let mut names = vec![unit.name.clone()];
+    if unit.name.ends_with(".socket") {
+        names.extend(triggered_units(&connection, &manager, &unit.name)?);
+    }
+    for name in &names {
+        queue_job(&manager, "StopUnit", name)?;
+    }
+    invalidate_socket_cache();
+    Ok(())
+}
+
+/// Restart a unit; systemd keeps the listening socket of a socket unit open
+pub fn restart_unit(unit: &SystemdUnit) -> ScanResult<()> {
+    let connection = connect_for(unit)?;
+    let manager = proxy(&connection, MANAGER_PATH, MANAGER_INTERFACE)?;
+    queue_job(&manager, "RestartUnit", &unit.name)?;
+    invalidate_socket_cache();
+    Ok(())
+}
+
+/// The innermost service or scope on the path decides. Scopes other than
+/// a manager's init.scope (login sessions, terminal tabs, containers) are
+/// not units a user would stop or restart to free a port.
+fn placement_from_path(path: &str) -> Option<Placement> {
+    let components: Vec<&str> = path.split('/').filter(|c| !c.is_empty()).collect();
+    let (index, innermost) = components
+        .iter()
+        .enumerate()
+        .rev()
+        .find(|(_, c)| c.ends_with(".service") || c.ends_with(".scope"))?;
+
+    // Units below user@<uid>.service belong to that user's manager
+    let user = components[..index].iter().find_map(|c| {
+        c.strip_prefix("user@")?
+            .strip_suffix(".service")?
+            .parse::<u32>()
+            .ok()
+    });
+
+    if *innermost == "init.scope" {
+        Some(Placement::Manager { user })
+    } else if innermost.ends_with(".service") {
+        Some(Placement::Service(SystemdUnit {
+            name: innermost.to_string(),
+            user,
+        }))
+    } else {
+        None
+    }
+}
+
+/// Parse one entry of a socket unit's Listen property, e.g.
+/// ("Stream", "[::]:631"), ("Datagram", "5353") or ("Stream", "/run/x.sock")
+fn parse_listen(kind: &str, address: &str) -> Option<Listen> {
+    // FIFOs, netlink, message queues... are never in the port list
+    if !matches!(kind, "Stream" | "Datagram" | "SequentialPacket") {
+        return None;
+    }
+    if address.starts_with('/') || address.starts_with('@') {
+        return Some(Listen::Unix(address.to_string()));
+    }
+    let protocol = match kind {
+        "Stream" => "tcp",
+        "Datagram" => "udp",
+        _ => return None,
+    };
+    let port = address.rsplit(':').next()?.parse().ok()?;
+    Some(Listen::Inet { protocol, port })
+}
+
+fn listen_matches(listen: &Listen, info: &PortInfo) -> bool {
+    match listen {
+        Listen::Inet { protocol, port } => info.protocol == *protocol && info.port == *port,
+        Listen::Unix(path) => info.protocol == "unix" && info.local_address == *path,
+    }
+}
+
+/// The socket unit a manager-held listener belongs to. Only our own user
+/// manager is reachable; other users' session buses are not.
+fn socket_unit_for(info: &PortInfo, user: Option<u32>) -> Option<SystemdUnit> {
+    if user.is_some_and(|uid| uid != current_uid()) {
+        return None;
+    }
+    let sockets = cached_socket_units(user.is_some());
+    sockets
+        .iter()
+        .find(|socket| socket.listen.iter().any(|l| listen_matches(l, info)))
+        .map(|socket| SystemdUnit {
+            name: socket.name.clone(),
+            user,
+        })
+}
+
+/// Socket units from the cache, refreshed over D-Bus once it is stale. A
+/// failed refresh is cached as empty so a missing bus isn't retried per scan.
+fn cached_socket_units(user: bool) -> Vec<SocketUnit> {
+    let Ok(mut cache) = SOCKET_CACHE.lock() else {
+        return Vec::new();
+    };
+    let slot = if user {
+        &mut cache.user
+    } else {
+        &mut cache.system
+    };
+    if let Some((fetched, sockets)) = slot {
+        if fetched.elapsed() < SOCKET_CACHE_TTL {
+            return sockets.clone();
+        }
+    }
+    let sockets = fetch_socket_units(user).unwrap_or_else(|e| {
+        log::debug!("Could not list systemd socket units: {}", e.message);
+        Vec::new()
+    });
+    *slot = Some((Instant::now(), sockets.clone()));
+    sockets
+}
+
+fn invalidate_socket_cache() {
+    if let Ok(mut cache) = SOCKET_CACHE.lock() {
+        cache.system = None;
+        cache.user = None;
+    }
+}
+
+/// Fields of a Manager.ListUnits* entry: name, description, load state,
+/// active state, sub state, followed unit, unit path, job id, job type, job path
+type UnitEntry = (
+    String,
+    String,
+    String,
+    String,
+    String,
+    String,
+    OwnedObjectPath,
+    u32,
+    String,
+    OwnedObjectPath,
+);
+
+fn fetch_socket_units(user: bool) -> ScanResult<Vec<SocketUnit>> {
+    let connection = connect(user)?;
+    let manager = proxy(&connection, MANAGER_PATH, MANAGER_INTERFACE)?;
+    let units: Vec<UnitEntry> = manager
+        .call("ListUnitsByPatterns", &(vec!["active"], vec!["*.socket"]))
+        .map_err(dbus_error)?;
+
+    let mut sockets = Vec::new();
+    for (name, _, _, _, _, _, path, ..) in units {
+        let socket = proxy(&connection, path.as_str(), SOCKET_INTERFACE)?;
+        let listen: Vec<(String, String)> = socket.get_property("Listen").map_err(dbus_error)?;
+        sockets.push(SocketUnit {
+            name,
+            listen: listen
+                .iter()
+                .filter_map(|(kind, address)| parse_listen(kind, address))
+                .collect(),
+        });
+    }
+    Ok(sockets)
+}
+
+/// Units a socket unit activates (usually `<name>.service`)
+fn triggered_units(
+    connection: &Connection,
+    manager: &Proxy<'_>,
+    socket: &str,
+) -> ScanResult<Vec<String>> {
+    let path: OwnedObjectPath = manager.call("GetUnit", &(socket,)).map_err(dbus_error)?;
+    let unit = proxy(connection, path.as_str(), UNIT_INTERFACE)?;
+    unit.get_property("Triggers").map_err(dbus_error)
+}
+
+/// Queue a start/stop job. Interactive auth lets polkit prompt for system
+/// units instead of failing outright.
+fn queue_job(manager: &Proxy<'_>, method: &str, name: &str) -> ScanResult<()> {
+    manager
+        .call_with_flags::<_, _, OwnedObjectPath>(
+            method,
+            MethodFlags::AllowInteractiveAuth.into(),
+            &(name, "replace"),
+        )
+        .map(drop)
+        .map_err(|e| {
+            let mut error = dbus_error(e);
+            error.message = format!("Cannot manage {}: {}", name, error.message);
+            error
+        })
+}
+
+/// Only services and sockets: stopping a target or mount from a port list
+/// is never what the user meant
+fn connect_for(unit: &SystemdUnit) -> ScanResult<Connection> {
+    if !(unit.name.ends_with(".service") || unit.name.ends_with(".socket")) {
+        return Err(ScanError::new(
+            ScanErrorKind::Other,
+            format!("{} is not a service or socket unit", unit.name),
+        ));
+    }
+    match unit.user {
+        Some(uid) if uid != current_uid() => Err(ScanError::new(
+            ScanErrorKind::PermissionDenied,
+            format!("{} belongs to another user's session", unit.name),
+        )),
+        user => connect(user.is_some()),
+    }
+}
+
+fn connect(user: bool) -> ScanResult<Connection> {
+    let builder = if user {
+        ConnectionBuilder::session()
+    } else {
+        ConnectionBuilder::system()
+    };
+    builder
+        .map(|builder| builder.method_timeout(DBUS_TIMEOUT))
+        .and_then(|builder| builder.build())
+        .map_err(|e| {
+            ScanError::new(
+                ScanErrorKind::ToolMissing,
+                format!("systemd is not reachable over D-Bus: {}", e),
+            )
+        })
+}
+
+fn proxy<'a>(connection: &Connection, path: &'a str, interface: &'a str) -> ScanResult<Proxy<'a>> {
+    ProxyBuilder::new(connection)
+        .destination(DESTINATION)
+        .and_then(|builder| builder.path(path))
+        .and_then(|builder| builder.interface(interface))
+        .map(|builder| builder.cache_properties(CacheProperties::No))
+        .and_then(|builder| builder.build())
+        .map_err(dbus_error)
+}
+
+fn dbus_error(error: zbus::Error) -> ScanError {
+    let kind = match &error {
+        zbus::Error::MethodError(name, _, _) => match name.as_str() {
+            "org.freedesktop.DBus.Error.AccessDenied"
+            | "org.freedesktop.DBus.Error.InteractiveAuthorizationRequired" => {
+                ScanErrorKind::PermissionDenied
+            }
+            _ => ScanErrorKind::CommandFailed,
+        },
+        zbus::Error::FDO(fdo) => match fdo.as_ref() {
+            zbus::fdo::Error::AccessDenied(_)
+            | zbus::fdo::Error::InteractiveAuthorizationRequired(_) => {
+                ScanErrorKind::PermissionDenied
+            }
+            _ => ScanErrorKind::CommandFailed,
+        },
+        _ => ScanErrorKind::Io,
+    };
+    ScanError::new(kind, error.to_string())
+}
+
+fn current_uid() -> u32 {
+    // SAFETY: getuid has no preconditions and cannot fail
+    unsafe { libc::getuid() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn service(name: &str, user: Option<u32>) -> Option<Placement> {
+        Some(Placement::Service(SystemdUnit {
+            name: name.to_string(),
+            user,
+        }))
+    }
+
+    #[test]
+    fn test_placement_services() {
+        assert_eq!(
+            placement_from_path("/system.slice/nginx.service"),
+            service("nginx.service", None)
+        );
+        assert_eq!(
+            placement_from_path("/init.scope"),
+            Some(Placement::Manager { user: None })
+        );
+        assert_eq!(
+            placement_from_path("/user.slice/user-1000.slice/user@1000.service/init.scope"),
+            Some(Placement::Manager { user: Some(1000) })
+        );
+        assert_eq!(
+            placement_from_path(
+                "/user.slice/user-1000.slice/user@1000.service/app.slice/vite-dev.service"
+            ),
+            service("vite-dev.service", Some(1000))
+        );
+        // Delegated subgroups inside a service still belong to it
+        assert_eq!(
+            placement_from_path("/system.slice/containerd.service/payload"),
+            service("containerd.service", None)
+        );
+    }
+
+    #[test]
+    fn test_placement_scopes() {
+        // Terminal tabs and login sessions are scopes, not restartable units
+        assert_eq!(
+            placement_from_path(
+                "/user.slice/user-1000.slice/user@1000.service/app.slice/vte-spawn-1.scope"
+            ),
+            None
+        );
+        assert_eq!(
+            placement_from_path("/user.slice/user-1000.slice/session-2.scope"),
+            None
+        );
+        assert_eq!(placement_from_path("/"), None);
+    }
+
+    #[test]
+    fn test_parse_listen() {
+        assert_eq!(
+            parse_listen("Stream", "[::]:631"),
+            Some(Listen::Inet {
+                protocol: "tcp",
+                port: 631
+            })
+        );
+        assert_eq!(
+            parse_listen("Stream", "0.0.0.0:22"),
+            Some(Listen::Inet {
+                protocol: "tcp",
+                port: 22
+            })
+        );
+        assert_eq!(
+            parse_listen("Datagram", "5353"),
+            Some(Listen::Inet {
+                protocol: "udp",
+                port: 5353
+            })
+        );
+        assert_eq!(
+            parse_listen("Stream", "/run/cups/cups.sock"),
+            Some(Listen::Unix("/run/cups/cups.sock".to_string()))
+        );
+        assert_eq!(parse_listen("FIFO", "/run/initctl"), None);
+        assert_eq!(parse_listen("Netlink", "kobject-uevent 1"), None);
+    }
+
+    #[test]
+    fn test_listen_matches() {
+        let tcp = PortInfo::new(631, "tcp", "[::]:631".to_string(), Vec::new());
+        let udp = PortInfo::new(631, "udp", "0.0.0.0:631".to_string(), Vec::new());
+        let listen = Listen::Inet {
+            protocol: "tcp",
+            port: 631,
+        };
+        assert!(listen_matches(&listen, &tcp));
+        assert!(!listen_matches(&listen, &udp));
+
+        let unix = PortInfo::new(0, "unix", "/run/cups/cups.sock".to_string(), Vec::new());
+        assert!(listen_matches(
+            &Listen::Unix("/run/cups/cups.sock".to_string()),
+            &unix
+        ));
+    }
+
+    #[test]
+    fn test_only_services_and_sockets() {
+        let target = SystemdUnit {
+            name: "multi-user.target".to_string(),
+            user: None,
+        };
+        assert_eq!(stop_unit(&target).unwrap_err().kind, ScanErrorKind::Other);
+
+        let foreign = SystemdUnit {
+            name: "app.service".to_string(),
+            user: Some(current_uid().wrapping_add(1)),
+        };
+        assert_eq!(
+            restart_unit(&foreign).unwrap_err().kind,
+            ScanErrorKind::PermissionDenied
+        );
+    }
+}
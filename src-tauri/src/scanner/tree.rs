@@ -0,0 +1,232 @@
+use super::procfs::{self, ProcStat};
+use super::protect;
+use super::{
+    KillOutcome, ProcessNode, ProcessTree, ScanError, ScanErrorKind, ScanResult, TreeKillMode,
+};
+use std::collections::{HashMap, HashSet};
+
+/// Ancestry and descendants of `pid`, from the PPid of every /proc/*/stat
+pub fn process_tree(pid: u32) -> ScanResult<ProcessTree> {
+    build_tree(&procfs::all_stats(), pid).ok_or_else(|| not_found(pid))
+}
+
+/// Kill `pid` with its subtree or process group, children before parents so
+/// nothing gets reparented to init and lingers. Our own process and its
+/// ancestors are never targeted; other protected processes are reported
+/// as failures unless `force` is set.
+pub fn kill_tree(pid: u32, mode: TreeKillMode, force: bool) -> ScanResult<Vec<KillOutcome>> {
+    let stats = procfs::all_stats();
+    let order = kill_order(&stats, pid, mode).ok_or_else(|| not_found(pid))?;
+
+    let own_pid = std::process::id();
+    let by_pid = index(&stats);
+    let mut protected: HashSet<u32> = ancestors(&by_pid, own_pid)
+        .iter()
+        .map(|node| node.pid)
+        .collect();
+    protected.insert(own_pid);
+
+    let outcomes = order
+        .into_iter()
+        .filter(|target| !protected.contains(target))
+        .map(|target| {
+            let name = by_pid
+                .get(&target)
+                .map(|stat| stat.comm.clone())
+                .unwrap_or_default();
+            match protect::check(target, force).and_then(|()| super::kill_process(target)) {
+                Ok(()) => KillOutcome {
+                    pid: target,
+                    name,
+                    killed: true,
+                    error: None,
+                },
+                Err(e) => KillOutcome {
+                    pid: target,
+                    name,
+                    killed: false,
+                    error: Some(e),
+                },
+            }
+        })
+        .collect();
+    Ok(outcomes)
+}
+
+fn not_found(pid: u32) -> ScanError {
+    ScanError::new(
+        ScanErrorKind::ProcessNotFound,
+        format!("Process {} not found", pid),
+    )
+    .with_pid(pid)
+}
+
+fn index(stats: &[ProcStat]) -> HashMap<u32, &ProcStat> {
+    stats.iter().map(|stat| (stat.pid, stat)).collect()
+}
+
+fn children_of(stats: &[ProcStat]) -> HashMap<u32, Vec<&ProcStat>> {
+    let mut children: HashMap<u32, Vec<&ProcStat>> = HashMap::new();
+    for stat in stats {
+        children.entry(stat.ppid).or_default().push(stat);
+    }
+    for list in children.values_mut() {
+        list.sort_by_key(|stat| stat.pid);
+    }
+    children
+}
+
+fn build_tree(stats: &[ProcStat], pid: u32) -> Option<ProcessTree> {
+    let by_pid = index(stats);
+    let root = by_pid.get(&pid)?;
+    Some(ProcessTree {
+        ancestors: ancestors(&by_pid, pid),
+        root: subtree(root, &children_of(stats)),
+    })
+}
+
+/// Parent chain of `pid`, nearest first, up to init
+fn ancestors(by_pid: &HashMap<u32, &ProcStat>, pid: u32) -> Vec<ProcessNode> {
+    let mut chain = Vec::new();
+    let mut seen = HashSet::from([pid]);
+    let mut current = by_pid.get(&pid).map(|stat| stat.ppid);
+
+    while let Some(parent) = current {
+        // The ppid chain is re-read live, so guard against loops
+        if !seen.insert(parent) {
+            break;
+        }
+        let Some(stat) = by_pid.get(&parent) else {
+            break;
+        };
+        chain.push(node(stat, Vec::new()));
+        current = Some(stat.ppid);
+    }
+    chain
+}
+
+fn subtree(stat: &ProcStat, children: &HashMap<u32, Vec<&ProcStat>>) -> ProcessNode {
+    let kids = children
+        .get(&stat.pid)
+        .map(|list| list.iter().map(|child| subtree(child, children)).collect())
+        .unwrap_or_default();
+    node(stat, kids)
+}
+
+fn node(stat: &ProcStat, children: Vec<ProcessNode>) -> ProcessNode {
+    ProcessNode {
+        pid: stat.pid,
+        ppid: stat.ppid,
+        pgrp: stat.pgrp,
+        name: stat.comm.clone(),
+        children,
+    }
+}
+
+/// Pids to signal, leaves first
+fn kill_order(stats: &[ProcStat], pid: u32, mode: TreeKillMode) -> Option<Vec<u32>> {
+    let tree = build_tree(stats, pid)?;
+    match mode {
+        TreeKillMode::Subtree => {
+            let mut order = Vec::new();
+            post_order(&tree.root, &mut order);
+            Some(order)
+        }
+        TreeKillMode::ProcessGroup => {
+            let by_pid = index(stats);
+            let pgrp = by_pid.get(&pid)?.pgrp;
+            let mut members: Vec<(usize, u32)> = stats
+                .iter()
+                .filter(|stat| stat.pgrp == pgrp)
+                .map(|stat| (ancestors(&by_pid, stat.pid).len(), stat.pid))
+                .collect();
+            // Deepest first; the group leader usually comes last
+            members.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+            Some(members.into_iter().map(|(_, pid)| pid).collect())
+        }
+    }
+}
+
+fn post_order(node: &ProcessNode, order: &mut Vec<u32>) {
+    for child in &node.children {
+        post_order(child, order);
+    }
+    order.push(node.pid);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stat(pid: u32, ppid: u32, pgrp: u32, comm: &str) -> ProcStat {
+        ProcStat {
+            pid,
+            comm: comm.to_string(),
+            state: 'S',
+            ppid,
+            pgrp,
+            session: 0,
+            flags: 0,
+            utime: 0,
+            stime: 0,
+            num_threads: 1,
+            start_time: 0,
+            rss_pages: 0,
+        }
+    }
+
+    /// init → bash → npm → sh → node → {esbuild, worker}, plus an unrelated sibling
+    fn npm_dev() -> Vec<ProcStat> {
+        vec![
+            stat(1, 0, 1, "systemd"),
+            stat(100, 1, 100, "bash"),
+            stat(200, 100, 200, "npm"),
+            stat(210, 200, 200, "sh"),
+            stat(220, 210, 200, "node"),
+            stat(230, 220, 200, "esbuild"),
+            stat(231, 220, 200, "node"),
+            stat(300, 100, 300, "vim"),
+        ]
+    }
+
+    #[test]
+    fn test_build_tree() {
+        let tree = build_tree(&npm_dev(), 210).unwrap();
+
+        let ancestors: Vec<u32> = tree.ancestors.iter().map(|n| n.pid).collect();
+        assert_eq!(ancestors, vec![200, 100, 1]);
+
+        assert_eq!(tree.root.pid, 210);
+        assert_eq!(tree.root.children.len(), 1);
+        let node = &tree.root.children[0];
+        assert_eq!(node.name, "node");
+        let leaves: Vec<u32> = node.children.iter().map(|n| n.pid).collect();
+        assert_eq!(leaves, vec![230, 231]);
+    }
+
+    #[test]
+    fn test_build_tree_missing_pid() {
+        assert!(build_tree(&npm_dev(), 999).is_none());
+    }
+
+    #[test]
+    fn test_ancestors_with_loop() {
+        let stats = vec![stat(10, 11, 10, "a"), stat(11, 10, 10, "b")];
+        let by_pid = index(&stats);
+        let chain: Vec<u32> = ancestors(&by_pid, 10).iter().map(|n| n.pid).collect();
+        assert_eq!(chain, vec![11]);
+    }
+
+    #[test]
+    fn test_kill_order_subtree_leaf_first() {
+        let order = kill_order(&npm_dev(), 200, TreeKillMode::Subtree).unwrap();
+        assert_eq!(order, vec![230, 231, 220, 210, 200]);
+    }
+
+    #[test]
+    fn test_kill_order_process_group() {
+        // Started from the node pid, still takes down the whole npm group
+        let order = kill_order(&npm_dev(), 220, TreeKillMode::ProcessGroup).unwrap();
+        assert_eq!(order, vec![230, 231, 220, 210, 200]);
+    }
+}
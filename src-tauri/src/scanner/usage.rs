@@ -0,0 +1,231 @@
+use super::procfs::{self, ProcStat};
+use super::{ProcessState, ProcessUsage};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Shortest span CPU% is measured over. Scans come from the watcher, the
+/// panel and port lookups at uneven times, and diffing two that land close
+/// together reports noise, so a pid's baseline only moves on once this much
+/// time has passed.
+const MIN_CPU_WINDOW: Duration = Duration::from_secs(1);
+
+/// Forget pids no scan has asked about for this long
+const FORGET_AFTER: Duration = Duration::from_secs(60);
+
+/// CPU time of a process at one scan, to diff against the next
+#[derive(Debug, Clone, Copy)]
+struct CpuSample {
+    /// Tells a restarted process apart from a reused pid
+    start_time: u64,
+    cpu_ticks: u64,
+    taken_at: Instant,
+}
+
+/// Where a pid's CPU% is measured from, and the figure over its last full window
+#[derive(Debug, Clone, Copy)]
+struct CpuBaseline {
+    sample: CpuSample,
+    cpu_percent: Option<f64>,
+    seen_at: Instant,
+}
+
+/// What one scan learns about a process from /proc
+#[derive(Debug, Clone)]
+pub struct ProcessSample {
+    pub usage: ProcessUsage,
+    pub state: ProcessState,
+    /// Clock ticks after boot, for the identity token
+    pub start_time: u64,
+}
+
+/// CPU baseline per pid, kept across scans until the pid stops showing up
+static BASELINES: Mutex<Option<HashMap<u32, CpuBaseline>>> = Mutex::new(None);
+
+/// Sample state, CPU, memory, threads and descriptors for each pid. CPU% is
+/// measured over at least `MIN_CPU_WINDOW` per pid, whoever scans in
+/// between, and is None until a pid has been watched that long.
+pub fn sample(pids: &[u32]) -> HashMap<u32, ProcessSample> {
+    let now = Instant::now();
+    let ticks_per_second = procfs::clock_ticks_per_second();
+    let mut guard = match BASELINES.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    let baselines = guard.get_or_insert_with(HashMap::new);
+    baselines.retain(|_, baseline| now.duration_since(baseline.seen_at) < FORGET_AFTER);
+
+    let mut usage = HashMap::new();
+    for &pid in pids {
+        if pid == 0 || usage.contains_key(&pid) {
+            continue;
+        }
+        // Exited between the scan and now
+        let Ok(stat) = procfs::read_stat(pid) else {
+            continue;
+        };
+
+        let current = CpuSample {
+            start_time: stat.start_time,
+            cpu_ticks: stat.utime + stat.stime,
+            taken_at: now,
+        };
+        let baseline = advance(baselines.get(&pid), current, ticks_per_second);
+        baselines.insert(pid, baseline);
+        usage.insert(
+            pid,
+            ProcessSample {
+                usage: usage_from(pid, &stat, baseline.cpu_percent),
+                state: ProcessState::from_code(stat.state),
+                start_time: stat.start_time,
+            },
+        );
+    }
+
+    usage
+}
+
+/// Move a pid's baseline to `current` once the window is long enough,
+/// recomputing CPU% over it; until then keep the previous figure. A new or
+/// restarted process starts a fresh baseline.
+fn advance(prev: Option<&CpuBaseline>, current: CpuSample, ticks_per_second: u64) -> CpuBaseline {
+    match prev {
+        Some(prev) if prev.sample.start_time == current.start_time => {
+            if current.taken_at.duration_since(prev.sample.taken_at) >= MIN_CPU_WINDOW {
+                CpuBaseline {
+                    sample: current,
+                    cpu_percent: cpu_percent(&prev.sample, &current, ticks_per_second),
+                    seen_at: current.taken_at,
+                }
+            } else {
+                CpuBaseline {
+                    seen_at: current.taken_at,
+                    ..*prev
+                }
+            }
+        }
+        _ => CpuBaseline {
+            sample: current,
+            cpu_percent: None,
+            seen_at: current.taken_at,
+        },
+    }
+}
+
+fn usage_from(pid: u32, stat: &ProcStat, cpu_percent: Option<f64>) -> ProcessUsage {
+    ProcessUsage {
+        cpu_percent,
+        rss_bytes: procfs::read_rss_bytes(pid).unwrap_or(stat.rss_pages * procfs::page_size()),
+        threads: stat.num_threads,
+        open_fds: procfs::count_fds(pid),
+    }
+}
+
+/// Share of one CPU used between two samples of the same process, in percent
+fn cpu_percent(prev: &CpuSample, current: &CpuSample, ticks_per_second: u64) -> Option<f64> {
+    if prev.start_time != current.start_time || ticks_per_second == 0 {
+        return None;
+    }
+    let elapsed = current.taken_at.duration_since(prev.taken_at).as_secs_f64();
+    if elapsed <= 0.0 {
+        return None;
+    }
+    let ticks = current.cpu_ticks.saturating_sub(prev.cpu_ticks);
+    Some(ticks as f64 / ticks_per_second as f64 / elapsed * 100.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn sample_at(start_time: u64, cpu_ticks: u64, taken_at: Instant) -> CpuSample {
+        CpuSample {
+            start_time,
+            cpu_ticks,
+            taken_at,
+        }
+    }
+
+    #[test]
+    fn test_cpu_percent() {
+        let t0 = Instant::now();
+        let t1 = t0 + Duration::from_secs(2);
+        let prev = sample_at(500, 1000, t0);
+
+        // 100 ticks at 100 Hz over 2s: half a core
+        let current = sample_at(500, 1100, t1);
+        let percent = cpu_percent(&prev, &current, 100).unwrap();
+        assert!((percent - 50.0).abs() < 1e-9);
+
+        // Multi-threaded processes can exceed 100%
+        let current = sample_at(500, 1600, t1);
+        let percent = cpu_percent(&prev, &current, 100).unwrap();
+        assert!((percent - 300.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_cpu_percent_reused_pid() {
+        let t0 = Instant::now();
+        let prev = sample_at(500, 1000, t0);
+        let current = sample_at(900, 10, t0 + Duration::from_secs(1));
+        assert_eq!(cpu_percent(&prev, &current, 100), None);
+    }
+
+    #[test]
+    fn test_cpu_percent_no_elapsed_time() {
+        let t0 = Instant::now();
+        let prev = sample_at(500, 1000, t0);
+        assert_eq!(cpu_percent(&prev, &prev, 100), None);
+    }
+
+    #[test]
+    fn test_advance_waits_for_min_window() {
+        let t0 = Instant::now();
+        let first = advance(None, sample_at(500, 1000, t0), 100);
+        assert_eq!(first.cpu_percent, None);
+
+        // A quick re-scan keeps the baseline and reports nothing new
+        let quick = advance(
+            Some(&first),
+            sample_at(500, 1001, t0 + Duration::from_millis(100)),
+            100,
+        );
+        assert_eq!(quick.cpu_percent, None);
+        assert_eq!(quick.sample.cpu_ticks, 1000);
+
+        // Measured from the original baseline: 50 ticks over 1s at 100 Hz
+        let t1 = t0 + MIN_CPU_WINDOW;
+        let full = advance(Some(&quick), sample_at(500, 1050, t1), 100);
+        assert!((full.cpu_percent.unwrap() - 50.0).abs() < 1e-9);
+        assert_eq!(full.sample.cpu_ticks, 1050);
+
+        // Between windows the last figure is repeated
+        let between = advance(
+            Some(&full),
+            sample_at(500, 1090, t1 + Duration::from_millis(100)),
+            100,
+        );
+        assert_eq!(between.cpu_percent, full.cpu_percent);
+
+        // Same pid, new process: start over
+        let restarted = advance(Some(&full), sample_at(900, 5, t1 + MIN_CPU_WINDOW), 100);
+        assert_eq!(restarted.cpu_percent, None);
+    }
+
+    #[test]
+    fn test_sample_self() {
+        let pid = std::process::id();
+        let usage = sample(&[pid, pid, 0]);
+        assert_eq!(usage.len(), 1);
+        let own = &usage[&pid];
+        // The main thread may be sleeping while the test harness runs us
+        assert!(matches!(
+            own.state,
+            ProcessState::Running | ProcessState::Sleeping
+        ));
+        assert!(own.usage.rss_bytes > 0);
+        assert!(own.usage.threads >= 1);
+        assert!(own.usage.open_fds.is_some());
+    }
+}
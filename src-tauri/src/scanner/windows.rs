@@ -1,4 +1,6 @@
-use super::{PortInfo, ScanError, ScanResult};
+use super::backend::{Capabilities, ScanBackend};
+use super::protect::ProcessFacts;
+use super::{exec, PortInfo, ProcessOwner, ScanError, ScanResult};
 use std::collections::HashMap;
 use std::os::windows::process::CommandExt;
 use std::process::Command;
@@ -6,6 +8,36 @@ use std::process::Command;
 // Windows flag to hide console window
 const CREATE_NO_WINDOW: u32 = 0x08000000;
 
+/// Windows has a single scan backend: netstat + tasklist
+pub fn backends() -> Vec<Box<dyn ScanBackend>> {
+    vec![Box::new(NetstatBackend)]
+}
+
+struct NetstatBackend;
+
+impl ScanBackend for NetstatBackend {
+    fn name(&self) -> &'static str {
+        "netstat"
+    }
+
+    fn check_available(&self) -> Result<(), String> {
+        // netstat and tasklist ship with every Windows install
+        Ok(())
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            pids: true,
+            addresses: true,
+            udp: true,
+        }
+    }
+
+    fn scan(&self) -> ScanResult<Vec<PortInfo>> {
+        scan_ports()
+    }
+}
+
 /// Scan listening ports on Windows using netstat and tasklist
 pub fn scan_ports() -> ScanResult<Vec<PortInfo>> {
     // Build PID to process name map first
@@ -16,10 +48,12 @@ pub fn scan_ports() -> ScanResult<Vec<PortInfo>> {
     // -n = numeric addresses
     // -o = show PID
     // -p TCP = TCP only (we'll run again for UDP)
-    let tcp_output = Command::new("netstat")
-        .args(["-ano", "-p", "TCP"])
-        .creation_flags(CREATE_NO_WINDOW)
-        .output()?;
+    let tcp_output = exec::output(
+        Command::new("netstat")
+            .args(["-ano", "-p", "TCP"])
+            .creation_flags(CREATE_NO_WINDOW),
+        "netstat",
+    )?;
 
     let mut ports = Vec::new();
 
@@ -33,10 +67,12 @@ pub fn scan_ports() -> ScanResult<Vec<PortInfo>> {
     }
 
     // Get UDP listeners
-    let udp_output = Command::new("netstat")
-        .args(["-ano", "-p", "UDP"])
-        .creation_flags(CREATE_NO_WINDOW)
-        .output()?;
+    let udp_output = exec::output(
+        Command::new("netstat")
+            .args(["-ano", "-p", "UDP"])
+            .creation_flags(CREATE_NO_WINDOW),
+        "netstat",
+    )?;
 
     if udp_output.status.success() {
         let stdout = String::from_utf8_lossy(&udp_output.stdout);
@@ -99,14 +135,17 @@ fn parse_netstat_line(line: &str, protocol: &str, pid_map: &HashMap<u32, String>
         .cloned()
         .unwrap_or_else(|| "unknown".to_string());
 
-    Some(PortInfo {
-        port,
+    // netstat only reports the owning PID, never the descriptor
+    let owner = ProcessOwner {
         pid,
-        process_name,
-        protocol: protocol.to_string(),
-        local_address: address,
-        state,
-    })
+        name: process_name,
+        fd: None,
+        usage: None,
+    };
+
+    let mut info = PortInfo::new(port, protocol, address, vec![owner]);
+    info.state = state;
+    Some(info)
 }
 
 /// Parse address:port format for Windows
@@ -132,10 +171,12 @@ fn parse_address_port(addr_port: &str) -> Option<(String, u16)> {
 
 /// Build a map of PID to process name using tasklist
 fn build_pid_name_map() -> ScanResult<HashMap<u32, String>> {
-    let output = Command::new("tasklist")
-        .args(["/FO", "CSV", "/NH"]) // CSV format, no header
-        .creation_flags(CREATE_NO_WINDOW)
-        .output()?;
+    let output = exec::output(
+        Command::new("tasklist")
+            .args(["/FO", "CSV", "/NH"]) // CSV format, no header
+            .creation_flags(CREATE_NO_WINDOW),
+        "tasklist",
+    )?;
 
     let mut map = HashMap::new();
 
@@ -158,18 +199,66 @@ fn build_pid_name_map() -> ScanResult<HashMap<u32, String>> {
 
 /// Kill a process by PID on Windows
 pub fn kill_process(pid: u32) -> ScanResult<()> {
-    let output = Command::new("taskkill")
-        .args(["/F", "/PID", &pid.to_string()])
-        .creation_flags(CREATE_NO_WINDOW)
-        .output()?;
+    taskkill(pid, true)
+}
+
+/// Ask a process to close. Without /F, taskkill posts WM_CLOSE, which only
+/// windowed apps act on; console servers are left to the forced stage.
+pub fn terminate_process(pid: u32) -> ScanResult<()> {
+    taskkill(pid, false)
+}
+
+fn taskkill(pid: u32, force: bool) -> ScanResult<()> {
+    let pid_arg = pid.to_string();
+    let mut args = vec!["/PID", pid_arg.as_str()];
+    if force {
+        args.insert(0, "/F");
+    }
+
+    let output = exec::output(
+        Command::new("taskkill")
+            .args(&args)
+            .creation_flags(CREATE_NO_WINDOW),
+        "taskkill",
+    )?;
 
     if output.status.success() {
         Ok(())
     } else {
         let stderr = String::from_utf8_lossy(&output.stderr);
-        Err(ScanError::from(format!(
-            "Failed to kill process: {}",
-            stderr
-        )))
+        Err(ScanError::kill_failed(pid, output.status.code(), &stderr))
     }
 }
+
+/// Whether a process with this PID is still listed by tasklist
+pub fn process_running(pid: u32) -> bool {
+    tasklist_name(pid).is_some()
+}
+
+/// What the protection policy checks; tasklist only gives the image name
+pub fn process_facts(pid: u32) -> Option<ProcessFacts> {
+    Some(ProcessFacts {
+        name: tasklist_name(pid)?,
+        ..ProcessFacts::default()
+    })
+}
+
+/// Image name of one process, None if tasklist doesn't list it
+fn tasklist_name(pid: u32) -> Option<String> {
+    let filter = format!("PID eq {}", pid);
+    let output = exec::output(
+        Command::new("tasklist")
+            .args(["/FI", &filter, "/FO", "CSV", "/NH"])
+            .creation_flags(CREATE_NO_WINDOW),
+        "tasklist",
+    )
+    .ok()?;
+
+    // Format: "process.exe","1234",... With no match tasklist prints an
+    // "INFO:" line instead of a row.
+    String::from_utf8_lossy(&output.stdout).lines().find_map(|line| {
+        let parts: Vec<&str> = line.split(',').collect();
+        let row_pid = parts.get(1)?.trim_matches('"').parse::<u32>().ok()?;
+        (row_pid == pid).then(|| parts[0].trim_matches('"').to_string())
+    })
+}
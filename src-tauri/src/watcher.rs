@@ -0,0 +1,404 @@
+use crate::scanner::{self, PortInfo};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Condvar, Mutex};
+use std::thread;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+/// Listeners that appeared since the previous scan (payload: `Vec<PortInfo>`)
+pub const PORT_OPENED: &str = "port-opened";
+/// Listeners that went away (payload: `Vec<PortInfo>`, as last seen)
+pub const PORT_CLOSED: &str = "port-closed";
+/// Listeners whose owner or metadata changed (payload: `Vec<PortChange>`)
+pub const PORT_CHANGED: &str = "port-changed";
+
+const DEFAULT_INTERVAL_MS: u64 = 2000;
+const DEFAULT_HIDDEN_INTERVAL_MS: u64 = 10_000;
+const MIN_INTERVAL_MS: u64 = 500;
+const MAX_INTERVAL_MS: u64 = 60_000;
+/// Unchanged scans in a row, while hidden, before the interval doubles
+const QUIET_SCANS_PER_BACKOFF: u32 = 5;
+/// Backoff stops here so favorites notifications are at most this late
+const MAX_BACKOFF_MS: u64 = 60_000;
+
+/// Scan interval while the window is shown
+static INTERVAL_MS: AtomicU64 = AtomicU64::new(DEFAULT_INTERVAL_MS);
+/// Scan interval while it is hidden, before any backoff
+static HIDDEN_INTERVAL_MS: AtomicU64 = AtomicU64::new(DEFAULT_HIDDEN_INTERVAL_MS);
+/// Mirrors show_window/hide_window; the window starts hidden
+static WINDOW_VISIBLE: AtomicBool = AtomicBool::new(false);
+/// Set to cut the current wait short and scan right away
+static WAKE: Mutex<bool> = Mutex::new(false);
+static WAKE_SIGNAL: Condvar = Condvar::new();
+/// Result of the watcher's last successful scan
+static SNAPSHOT: Mutex<Option<Vec<PortInfo>>> = Mutex::new(None);
+
+/// One listener before and after a change
+#[derive(Debug, Clone, Serialize)]
+pub struct PortChange {
+    pub previous: PortInfo,
+    pub current: PortInfo,
+}
+
+/// What changed between two scans
+#[derive(Debug, Default)]
+struct PortDelta {
+    opened: Vec<PortInfo>,
+    closed: Vec<PortInfo>,
+    changed: Vec<PortChange>,
+}
+
+impl PortDelta {
+    fn is_empty(&self) -> bool {
+        self.opened.is_empty() && self.closed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Start the background watcher. The first scan reports every listener as
+/// opened; after that only differences are emitted.
+pub fn start(app: AppHandle) {
+    let spawned = thread::Builder::new()
+        .name("port-watcher".to_string())
+        .spawn(move || run(&app));
+    if let Err(e) = spawned {
+        log::error!("Failed to start the port watcher: {}", e);
+    }
+}
+
+/// Change how often the watcher scans while the window is shown and, if
+/// given, while it is hidden. Returns the shown interval actually used.
+pub fn set_interval(interval_ms: u64, hidden_interval_ms: Option<u64>) -> u64 {
+    let interval_ms = interval_ms.clamp(MIN_INTERVAL_MS, MAX_INTERVAL_MS);
+    INTERVAL_MS.store(interval_ms, Ordering::Relaxed);
+    if let Some(hidden) = hidden_interval_ms {
+        HIDDEN_INTERVAL_MS.store(
+            hidden.clamp(MIN_INTERVAL_MS, MAX_INTERVAL_MS),
+            Ordering::Relaxed,
+        );
+    }
+    wake();
+    interval_ms
+}
+
+/// Called as the window is shown or hidden. Showing scans immediately so
+/// the panel never opens on data from a slow hidden-window cycle.
+pub fn set_window_visible(visible: bool) {
+    let was_visible = WINDOW_VISIBLE.swap(visible, Ordering::Relaxed);
+    if visible && !was_visible {
+        wake();
+    }
+}
+
+fn wake() {
+    if let Ok(mut woken) = WAKE.lock() {
+        *woken = true;
+        WAKE_SIGNAL.notify_all();
+    }
+}
+
+/// Sleep for `delay` unless woken first
+fn wait(delay: Duration) {
+    let Ok(guard) = WAKE.lock() else {
+        thread::sleep(delay);
+        return;
+    };
+    if let Ok((mut woken, _)) = WAKE_SIGNAL.wait_timeout_while(guard, delay, |woken| !*woken) {
+        *woken = false;
+    }
+}
+
+/// Time until the next scan: the shown interval while visible; while
+/// hidden the slower interval, doubled after every few scans that found
+/// nothing new
+fn next_delay(
+    visible: bool,
+    interval_ms: u64,
+    hidden_interval_ms: u64,
+    quiet_scans: u32,
+) -> Duration {
+    if visible {
+        return Duration::from_millis(interval_ms);
+    }
+    let base = hidden_interval_ms.max(interval_ms);
+    let doublings = (quiet_scans / QUIET_SCANS_PER_BACKOFF).min(16);
+    let backed_off = base.saturating_mul(1 << doublings);
+    Duration::from_millis(backed_off.min(MAX_BACKOFF_MS.max(base)))
+}
+
+/// The listeners as of the watcher's last scan, for subscribers that start
+/// after the first events went out. None until that scan finishes.
+pub fn snapshot() -> Option<Vec<PortInfo>> {
+    SNAPSHOT.lock().ok().and_then(|guard| guard.clone())
+}
+
+fn run(app: &AppHandle) {
+    let mut quiet_scans: u32 = 0;
+    loop {
+        match scanner::scan_ports() {
+            Ok(ports) => {
+                let delta = match SNAPSHOT.lock() {
+                    Ok(mut snapshot) => {
+                        let delta = diff(snapshot.as_deref().unwrap_or_default(), &ports);
+                        *snapshot = Some(ports);
+                        delta
+                    }
+                    Err(_) => PortDelta::default(),
+                };
+                quiet_scans = if delta.is_empty() {
+                    quiet_scans.saturating_add(1)
+                } else {
+                    0
+                };
+                emit(app, &delta);
+            }
+            // Keep the old snapshot: a failed scan must not look like every
+            // port closing
+            Err(e) => log::warn!("Port watcher scan failed: {}", e.message),
+        }
+
+        let visible = WINDOW_VISIBLE.load(Ordering::Relaxed);
+        if visible {
+            quiet_scans = 0;
+        }
+        wait(next_delay(
+            visible,
+            INTERVAL_MS.load(Ordering::Relaxed),
+            HIDDEN_INTERVAL_MS.load(Ordering::Relaxed),
+            quiet_scans,
+        ));
+    }
+}
+
+fn emit(app: &AppHandle, delta: &PortDelta) {
+    let results = [
+        (!delta.opened.is_empty()).then(|| app.emit(PORT_OPENED, &delta.opened)),
+        (!delta.closed.is_empty()).then(|| app.emit(PORT_CLOSED, &delta.closed)),
+        (!delta.changed.is_empty()).then(|| app.emit(PORT_CHANGED, &delta.changed)),
+    ];
+    for result in results.into_iter().flatten() {
+        if let Err(e) = result {
+            log::warn!("Failed to emit port event: {}", e);
+        }
+    }
+}
+
+/// A listener is the same across scans while its socket address is: the
+/// protocol, local address, port and network namespace
+type ListenerKey<'a> = (&'a str, &'a str, u16, Option<u64>);
+
+fn key(info: &PortInfo) -> ListenerKey<'_> {
+    (
+        &info.protocol,
+        &info.local_address,
+        info.port,
+        info.net_namespace.as_ref().map(|namespace| namespace.inode),
+    )
+}
+
+/// Index a scan by listener. SO_REUSEPORT lets several sockets share an
+/// address; the lowest pid stands for all of them so the choice is stable.
+fn index(ports: &[PortInfo]) -> HashMap<ListenerKey<'_>, &PortInfo> {
+    let mut index: HashMap<ListenerKey<'_>, &PortInfo> = HashMap::new();
+    for info in ports {
+        index
+            .entry(key(info))
+            .and_modify(|kept| {
+                if info.pid < kept.pid {
+                    *kept = info;
+                }
+            })
+            .or_insert(info);
+    }
+    index
+}
+
+/// Whether anything a subscriber shows is different. Usage moves on every
+/// scan and is deliberately left out.
+fn same_listener(a: &PortInfo, b: &PortInfo) -> bool {
+    let pids = |info: &PortInfo| info.owners.iter().map(|o| o.pid).collect::<Vec<_>>();
+    a.pid == b.pid
+        && a.process_name == b.process_name
+        && a.state == b.state
+        && pids(a) == pids(b)
+        && a.identity == b.identity
+        && a.process_state == b.process_state
+        && a.systemd_unit == b.systemd_unit
+        && a.container == b.container
+        && a.project == b.project
+        && a.service_hint == b.service_hint
+}
+
+fn diff(previous: &[PortInfo], current: &[PortInfo]) -> PortDelta {
+    let before = index(previous);
+    let after = index(current);
+    let mut delta = PortDelta::default();
+
+    for info in current {
+        let key = key(info);
+        // Only the entry that stands for its key is reported
+        if !after
+            .get(&key)
+            .is_some_and(|kept| std::ptr::eq(*kept, info))
+        {
+            continue;
+        }
+        match before.get(&key) {
+            None => delta.opened.push(info.clone()),
+            Some(old) if !same_listener(old, info) => delta.changed.push(PortChange {
+                previous: (*old).clone(),
+                current: info.clone(),
+            }),
+            Some(_) => {}
+        }
+    }
+    for info in previous {
+        let key = key(info);
+        if before
+            .get(&key)
+            .is_some_and(|kept| std::ptr::eq(*kept, info))
+            && !after.contains_key(&key)
+        {
+            delta.closed.push(info.clone());
+        }
+    }
+    delta
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::{ProcessOwner, ProcessUsage};
+
+    fn listener(port: u16, address: &str, pid: u32, name: &str) -> PortInfo {
+        PortInfo::new(
+            port,
+            "tcp",
+            address.to_string(),
+            vec![ProcessOwner {
+                pid,
+                name: name.to_string(),
+                fd: Some(5),
+                usage: None,
+            }],
+        )
+    }
+
+    fn ports(delta: &[PortInfo]) -> Vec<u16> {
+        delta.iter().map(|info| info.port).collect()
+    }
+
+    #[test]
+    fn test_diff_first_scan_opens_everything() {
+        let current = vec![
+            listener(3000, "0.0.0.0", 10, "node"),
+            listener(5432, "127.0.0.1", 20, "postgres"),
+        ];
+        let delta = diff(&[], &current);
+        assert_eq!(ports(&delta.opened), vec![3000, 5432]);
+        assert!(delta.closed.is_empty());
+        assert!(delta.changed.is_empty());
+    }
+
+    #[test]
+    fn test_diff_opened_closed_changed() {
+        let previous = vec![
+            listener(3000, "0.0.0.0", 10, "node"),
+            listener(5432, "127.0.0.1", 20, "postgres"),
+            listener(8080, "0.0.0.0", 30, "java"),
+        ];
+        let current = vec![
+            listener(3000, "0.0.0.0", 10, "node"),
+            // Restarted under a new pid
+            listener(8080, "0.0.0.0", 31, "java"),
+            listener(5173, "[::1]", 40, "node"),
+        ];
+        let delta = diff(&previous, &current);
+
+        assert_eq!(ports(&delta.opened), vec![5173]);
+        assert_eq!(ports(&delta.closed), vec![5432]);
+        assert_eq!(delta.changed.len(), 1);
+        assert_eq!(delta.changed[0].previous.pid, 30);
+        assert_eq!(delta.changed[0].current.pid, 31);
+    }
+
+    #[test]
+    fn test_diff_ignores_usage_and_families() {
+        let previous = vec![listener(3000, "0.0.0.0", 10, "node")];
+        let mut busy = listener(3000, "0.0.0.0", 10, "node");
+        busy.usage = Some(ProcessUsage {
+            cpu_percent: Some(85.0),
+            rss_bytes: 1 << 30,
+            threads: 12,
+            open_fds: Some(40),
+        });
+        // The IPv6 socket is a different listener on the same port
+        let current = vec![busy, listener(3000, "[::]", 10, "node")];
+
+        let delta = diff(&previous, &current);
+        assert!(delta.changed.is_empty());
+        assert_eq!(delta.opened.len(), 1);
+        assert_eq!(delta.opened[0].local_address, "[::]");
+    }
+
+    #[test]
+    fn test_diff_reuseport_is_stable() {
+        let previous = vec![
+            listener(80, "0.0.0.0", 101, "nginx"),
+            listener(80, "0.0.0.0", 100, "nginx"),
+        ];
+        let current = vec![
+            listener(80, "0.0.0.0", 100, "nginx"),
+            listener(80, "0.0.0.0", 101, "nginx"),
+        ];
+        let delta = diff(&previous, &current);
+        assert!(delta.opened.is_empty());
+        assert!(delta.closed.is_empty());
+        assert!(delta.changed.is_empty());
+    }
+
+    #[test]
+    fn test_set_interval_clamps() {
+        assert_eq!(set_interval(10, None), MIN_INTERVAL_MS);
+        assert_eq!(set_interval(u64::MAX, None), MAX_INTERVAL_MS);
+        assert_eq!(
+            set_interval(DEFAULT_INTERVAL_MS, Some(DEFAULT_HIDDEN_INTERVAL_MS)),
+            DEFAULT_INTERVAL_MS
+        );
+    }
+
+    #[test]
+    fn test_next_delay() {
+        let delay = |visible, quiet| next_delay(visible, 2000, 10_000, quiet).as_millis();
+
+        assert_eq!(delay(true, 0), 2000);
+        // Visible never backs off
+        assert_eq!(delay(true, 100), 2000);
+
+        assert_eq!(delay(false, 0), 10_000);
+        assert_eq!(delay(false, QUIET_SCANS_PER_BACKOFF - 1), 10_000);
+        assert_eq!(delay(false, QUIET_SCANS_PER_BACKOFF), 20_000);
+        assert_eq!(delay(false, 2 * QUIET_SCANS_PER_BACKOFF), 40_000);
+        assert_eq!(delay(false, 3 * QUIET_SCANS_PER_BACKOFF), 60_000);
+        assert_eq!(delay(false, u32::MAX), 60_000);
+
+        // Hidden is never faster than shown
+        assert_eq!(next_delay(false, 30_000, 10_000, 0).as_millis(), 30_000);
+    }
+
+    #[test]
+    fn test_wake_cuts_wait_short() {
+        let waiter = thread::spawn(|| {
+            let started = std::time::Instant::now();
+            wait(Duration::from_secs(30));
+            started.elapsed()
+        });
+        // Keep waking until the waiter has started waiting and returned
+        while !waiter.is_finished() {
+            wake();
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert!(waiter.join().unwrap() < Duration::from_secs(5));
+    }
+}
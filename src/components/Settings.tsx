@@ -2,12 +2,26 @@ import { useState, useEffect } from 'react'
 import { useSettingsStore } from '../stores/settingsStore'
 import { useAutoStart } from '../hooks/useAutoStart'
 import { useUpdater } from '../hooks/useUpdater'
+import {
+  useScannerSettings,
+  type ProtectionRule,
+} from '../hooks/useScannerSettings'
 
 interface SettingsPanelProps {
   isOpen: boolean
   onClose: () => void
 }
 
+function describeRule(rule: ProtectionRule): string {
+  return [
+    rule.name && `name ${rule.name}`,
+    rule.path && `path ${rule.path}`,
+    rule.uid !== null && `uid ${rule.uid}`,
+  ]
+    .filter(Boolean)
+    .join(', ')
+}
+
 function Toggle({
   enabled,
   onChange,
@@ -42,7 +56,20 @@ export function SettingsPanel({ isOpen, onClose }: SettingsPanelProps) {
   const { autoStart, toggleAutoStart } = useAutoStart()
   const { checking, updating, updateAvailable, checkForUpdates, installUpdate } =
     useUpdater()
+  const {
+    backends,
+    preferredBackend,
+    selectBackend,
+    namespaceScanning,
+    toggleNamespaceScanning,
+    protectionRules,
+    addProtectionRule,
+    removeProtectionRule,
+    error: scannerError,
+  } = useScannerSettings()
   const [isToggling, setIsToggling] = useState(false)
+  const [rulePattern, setRulePattern] = useState('')
+  const [ruleUid, setRuleUid] = useState('')
   const [appVersion, setAppVersion] = useState('0.1.0')
 
   // Get app version on mount
@@ -65,6 +92,24 @@ export function SettingsPanel({ isOpen, onClose }: SettingsPanelProps) {
     setIsToggling(false)
   }
 
+  // Patterns with a path separator match the executable path, others the name
+  const handleAddRule = async () => {
+    const pattern = rulePattern.trim()
+    const uid = ruleUid.trim() ? parseInt(ruleUid, 10) : null
+    if (!pattern && uid === null) return
+
+    const isPath = pattern.includes('/') || pattern.includes('\\')
+    const added = await addProtectionRule({
+      name: pattern && !isPath ? pattern : null,
+      path: pattern && isPath ? pattern : null,
+      uid: uid !== null && !isNaN(uid) ? uid : null,
+    })
+    if (added) {
+      setRulePattern('')
+      setRuleUid('')
+    }
+  }
+
   if (!isOpen) return null
 
   return (
@@ -272,6 +317,166 @@ export function SettingsPanel({ isOpen, onClose }: SettingsPanelProps) {
             </div>
           </section>
 
+          {/* Scanning Section */}
+          <section>
+            <h4
+              className="text-xs font-semibold uppercase tracking-wide mb-3"
+              style={{ color: 'var(--text-tertiary)' }}
+            >
+              Scanning
+            </h4>
+
+            <div className="py-2">
+              <label
+                className="text-xs font-medium mb-1.5 block"
+                style={{ color: 'var(--text-secondary)' }}
+              >
+                Scan Backend
+              </label>
+              <select
+                value={preferredBackend ?? ''}
+                onChange={(e) => selectBackend(e.target.value || null)}
+                className="w-full px-2.5 py-1.5 rounded-lg text-sm outline-none"
+                style={{
+                  background: 'var(--bg-secondary)',
+                  border: '1px solid var(--border-color)',
+                  color: 'var(--text-primary)',
+                }}
+              >
+                <option value="">Automatic</option>
+                {backends.map((backend) => (
+                  <option
+                    key={backend.name}
+                    value={backend.name}
+                    disabled={!backend.available}
+                    title={backend.unavailable_reason ?? undefined}
+                  >
+                    {backend.available
+                      ? backend.name
+                      : `${backend.name} (unavailable)`}
+                  </option>
+                ))}
+              </select>
+              <p
+                className="text-xs mt-1.5"
+                style={{ color: 'var(--text-tertiary)' }}
+              >
+                Tried first; the others remain fallbacks
+              </p>
+            </div>
+
+            <div
+              className="flex items-center justify-between py-2"
+              style={{ borderTop: '1px solid var(--border-light)' }}
+            >
+              <div>
+                <p
+                  className="text-sm font-medium"
+                  style={{ color: 'var(--text-primary)' }}
+                >
+                  Other Network Namespaces
+                </p>
+                <p
+                  className="text-xs"
+                  style={{ color: 'var(--text-tertiary)' }}
+                >
+                  Include container and netns listeners (Linux)
+                </p>
+              </div>
+              <Toggle
+                enabled={namespaceScanning}
+                onChange={toggleNamespaceScanning}
+              />
+            </div>
+          </section>
+
+          {/* Protected Processes Section */}
+          <section>
+            <h4
+              className="text-xs font-semibold uppercase tracking-wide mb-1"
+              style={{ color: 'var(--text-tertiary)' }}
+            >
+              Protected Processes
+            </h4>
+            <p
+              className="text-xs mb-3"
+              style={{ color: 'var(--text-tertiary)' }}
+            >
+              Kills of matching processes are refused unless forced. System
+              processes are always protected.
+            </p>
+
+            {protectionRules.map((rule, index) => (
+              <div
+                key={index}
+                className="flex items-center justify-between py-1.5"
+                style={{ borderBottom: '1px solid var(--border-light)' }}
+              >
+                <span
+                  className="text-sm font-mono truncate"
+                  style={{ color: 'var(--text-primary)' }}
+                >
+                  {describeRule(rule)}
+                </span>
+                <button
+                  onClick={() => removeProtectionRule(index)}
+                  className="text-xs px-2 py-0.5 rounded"
+                  style={{
+                    color: 'var(--danger)',
+                    background: 'rgba(255, 59, 48, 0.1)',
+                  }}
+                >
+                  Remove
+                </button>
+              </div>
+            ))}
+
+            <div className="flex items-center gap-2 mt-2">
+              <input
+                type="text"
+                placeholder="Name or path, e.g. postgres*"
+                value={rulePattern}
+                onChange={(e) => setRulePattern(e.target.value)}
+                onKeyDown={(e) => e.key === 'Enter' && handleAddRule()}
+                className="flex-1 min-w-0 px-2.5 py-1.5 rounded-lg text-sm outline-none"
+                style={{
+                  background: 'var(--bg-secondary)',
+                  border: '1px solid var(--border-color)',
+                  color: 'var(--text-primary)',
+                }}
+              />
+              <input
+                type="number"
+                placeholder="uid"
+                value={ruleUid}
+                onChange={(e) => setRuleUid(e.target.value)}
+                min={0}
+                className="w-16 px-2.5 py-1.5 rounded-lg text-sm outline-none"
+                style={{
+                  background: 'var(--bg-secondary)',
+                  border: '1px solid var(--border-color)',
+                  color: 'var(--text-primary)',
+                }}
+              />
+              <button
+                onClick={handleAddRule}
+                className="px-3 py-1.5 rounded-lg text-xs font-medium"
+                style={{
+                  background: 'var(--bg-tertiary)',
+                  color: 'var(--text-secondary)',
+                }}
+              >
+                Add
+              </button>
+            </div>
+
+            {scannerError && (
+              <p className="text-xs mt-2" style={{ color: 'var(--danger)' }}>
+                {scannerError}
+              </p>
+            )}
+          </section>
+
           {/* Startup Section */}
           <section>
             <h4
@@ -1,141 +1,119 @@
-import { useEffect, useRef, useCallback } from 'react'
-import { usePortStore } from '../stores/portStore'
+import { useEffect, useRef } from 'react'
+import { usePortStore, type Favorite, type PortInfo } from '../stores/portStore'
+import { mapBackendPort, type BackendPortInfo, type CommandResponse } from './usePortScanner'
 
 function isTauri(): boolean {
   return typeof window !== 'undefined' && '__TAURI_INTERNALS__' in window
 }
 
-export function useNotifications() {
-  const { ports, favorites } = usePortStore()
-  const previousPortsRef = useRef<Set<number>>(new Set())
-  const notificationSentRef = useRef<Set<number>>(new Set())
-
-  const sendNotification = useCallback(async (title: string, body: string) => {
-    if (!isTauri()) return
+async function sendNotification(title: string, body: string) {
+  if (!isTauri()) return
 
-    try {
-      const {
-        isPermissionGranted,
-        requestPermission,
-        sendNotification: tauriSendNotification,
-      } = await import('@tauri-apps/plugin-notification')
+  try {
+    const {
+      isPermissionGranted,
+      requestPermission,
+      sendNotification: tauriSendNotification,
+    } = await import('@tauri-apps/plugin-notification')
 
-      let permissionGranted = await isPermissionGranted()
+    let permissionGranted = await isPermissionGranted()
 
-      if (!permissionGranted) {
-        const permission = await requestPermission()
-        permissionGranted = permission === 'granted'
-      }
+    if (!permissionGranted) {
+      const permission = await requestPermission()
+      permissionGranted = permission === 'granted'
+    }
 
-      if (permissionGranted) {
-        await tauriSendNotification({ title, body })
-      }
-    } catch (error) {
-      console.error('Failed to send notification:', error)
+    if (permissionGranted) {
+      await tauriSendNotification({ title, body })
     }
-  }, [])
+  } catch (error) {
+    console.error('Failed to send notification:', error)
+  }
+}
+
+// Calls `onOpened` when a favorite port comes into use, from the backend
+// watcher's port-opened events, once until the port is closed again. With
+// `skipInUse`, ports already in use when the hook mounts don't count.
+function useFavoritePortOpened(
+  onOpened: (port: PortInfo, favorite: Favorite) => void,
+  skipInUse: boolean
+) {
+  const onOpenedRef = useRef(onOpened)
+
+  useEffect(() => {
+    onOpenedRef.current = onOpened
+  }, [onOpened])
 
-  // Check for favorite ports that become occupied
   useEffect(() => {
     if (!isTauri()) return
 
-    const currentPorts = new Set(ports.map((p) => p.port))
-    const previousPorts = previousPortsRef.current
-
-    // Find newly occupied ports
-    const newPorts = ports.filter((p) => !previousPorts.has(p.port))
-
-    // Check if any new port is a favorite
-    for (const port of newPorts) {
-      const favorite = favorites.find((f) => f.port === port.port)
-      if (favorite && !notificationSentRef.current.has(port.port)) {
-        sendNotification(
-          `Port ${port.port} is now in use`,
-          `${favorite.label} - Process: ${port.processName} (PID ${port.pid})`
-        )
-        notificationSentRef.current.add(port.port)
+    let cancelled = false
+    const unlisteners: Array<() => void> = []
+    // Ports known to be in use, so a second listener on one (IPv4 and
+    // IPv6, say) doesn't notify twice
+    const inUse = new Set<number>()
+
+    const subscribe = async () => {
+      try {
+        if (skipInUse) {
+          const { invoke } = await import('@tauri-apps/api/core')
+          const response = await invoke<CommandResponse<BackendPortInfo[]>>('get_port_snapshot')
+          response.data?.forEach((p) => inUse.add(p.port))
+        }
+
+        const { listen } = await import('@tauri-apps/api/event')
+        const subscriptions = await Promise.all([
+          listen<BackendPortInfo[]>('port-opened', (event) => {
+            const { favorites } = usePortStore.getState()
+            for (const port of event.payload.map(mapBackendPort)) {
+              const favorite = favorites.find((f) => f.port === port.port)
+              if (favorite && !inUse.has(port.port)) {
+                onOpenedRef.current(port, favorite)
+              }
+              inUse.add(port.port)
+            }
+          }),
+          listen<BackendPortInfo[]>('port-closed', (event) => {
+            event.payload.forEach((p) => inUse.delete(p.port))
+          }),
+        ])
+        if (cancelled) {
+          subscriptions.forEach((unlisten) => unlisten())
+          return
+        }
+        unlisteners.push(...subscriptions)
+      } catch (err) {
+        console.error('Failed to subscribe to port events:', err)
       }
     }
+    subscribe()
 
-    // Clear notification sent flag for ports that are no longer in use
-    for (const portNum of notificationSentRef.current) {
-      if (!currentPorts.has(portNum)) {
-        notificationSentRef.current.delete(portNum)
-      }
+    return () => {
+      cancelled = true
+      unlisteners.forEach((unlisten) => unlisten())
     }
+  }, [skipInUse])
+}
 
-    // Update previous ports reference
-    previousPortsRef.current = currentPorts
-  }, [ports, favorites, sendNotification])
+// Notify whenever a favorite port becomes occupied
+export function useNotifications() {
+  useFavoritePortOpened((port, favorite) => {
+    sendNotification(
+      `Port ${port.port} is now in use`,
+      `${favorite.label} - Process: ${port.processName} (PID ${port.pid})`
+    )
+  }, false)
 
   return { sendNotification }
 }
 
 // Hook to initialize notifications on app start
 export function useFavoritePortNotifications() {
-  const { ports, favorites } = usePortStore()
-  const initializedRef = useRef(false)
-  const notifiedPortsRef = useRef<Set<number>>(new Set())
-
-  const sendNotification = useCallback(async (title: string, body: string) => {
-    if (!isTauri()) return
-
-    try {
-      const {
-        isPermissionGranted,
-        requestPermission,
-        sendNotification: tauriSendNotification,
-      } = await import('@tauri-apps/plugin-notification')
-
-      let permissionGranted = await isPermissionGranted()
-
-      if (!permissionGranted) {
-        const permission = await requestPermission()
-        permissionGranted = permission === 'granted'
-      }
-
-      if (permissionGranted) {
-        await tauriSendNotification({ title, body })
-      }
-    } catch (error) {
-      console.error('Failed to send notification:', error)
-    }
-  }, [])
-
-  // Skip initial scan, only notify on changes
-  useEffect(() => {
-    if (!isTauri()) return
-
-    // After first render with ports, mark as initialized
-    if (!initializedRef.current && ports.length > 0) {
-      // Record all current ports as "already notified" so we don't spam on startup
-      ports.forEach((p) => notifiedPortsRef.current.add(p.port))
-      initializedRef.current = true
-      return
-    }
-
-    if (!initializedRef.current) return
-
-    // Find favorite ports that are newly in use
-    const currentPortSet = new Set(ports.map((p) => p.port))
-
-    // Check for new favorite ports
-    for (const port of ports) {
-      const favorite = favorites.find((f) => f.port === port.port)
-      if (favorite && !notifiedPortsRef.current.has(port.port)) {
-        sendNotification(
-          `Favorite port :${port.port} is now in use`,
-          `${favorite.label}\nProcess: ${port.processName} (PID ${port.pid})`
-        )
-        notifiedPortsRef.current.add(port.port)
-      }
-    }
-
-    // Clean up ports that are no longer in use
-    for (const portNum of notifiedPortsRef.current) {
-      if (!currentPortSet.has(portNum)) {
-        notifiedPortsRef.current.delete(portNum)
-      }
-    }
-  }, [ports, favorites, sendNotification])
+  // Ports in use at startup were not just opened, so don't spam about them
+  useFavoritePortOpened((port, favorite) => {
+    sendNotification(
+      `Favorite port :${port.port} is now in use`,
+      `${favorite.label}\nProcess: ${port.processName} (PID ${port.pid})`
+    )
+  }, true)
 }
@@ -5,11 +5,22 @@ import { usePortStore } from '../stores/portStore'
 // Mock invoke at module level
 const mockInvoke = vi.fn()
 
-// Mock the Tauri API module
+// Event handlers registered through listen, by event name
+const listeners = new Map<string, (event: { payload: unknown }) => void>()
+const mockUnlisten = vi.fn()
+
+// Mock the Tauri API modules
 vi.mock('@tauri-apps/api/core', () => ({
   invoke: (...args: unknown[]) => mockInvoke(...args),
 }))
 
+vi.mock('@tauri-apps/api/event', () => ({
+  listen: (event: string, handler: (event: { payload: unknown }) => void) => {
+    listeners.set(event, handler)
+    return Promise.resolve(mockUnlisten)
+  },
+}))
+
 // Mock the database
 vi.mock('../lib/database', () => ({
   addKillHistory: vi.fn().mockResolvedValue(undefined),
@@ -18,10 +29,43 @@ vi.mock('../lib/database', () => ({
 // Import hook after mocks
 import { usePortScanner } from './usePortScanner'
 
+const nodePort = {
+  port: 3000,
+  pid: 1234,
+  process_name: 'node',
+  protocol: 'tcp',
+  local_address: '0.0.0.0',
+  state: 'LISTEN',
+}
+
+const javaPort = {
+  port: 8080,
+  pid: 5678,
+  process_name: 'java',
+  protocol: 'tcp',
+  local_address: '127.0.0.1',
+  state: 'LISTEN',
+}
+
+// Answer each command from `responses`; set_watch_interval always succeeds
+function respondWith(responses: Record<string, unknown>) {
+  mockInvoke.mockImplementation((command: string) => {
+    if (command in responses) {
+      const response = responses[command]
+      return response instanceof Error ? Promise.reject(response) : Promise.resolve(response)
+    }
+    if (command === 'set_watch_interval') {
+      return Promise.resolve({ success: true, data: 2000, error: null })
+    }
+    return Promise.reject(new Error(`unexpected command ${command}`))
+  })
+}
+
 describe('usePortScanner', () => {
   beforeEach(() => {
     vi.clearAllMocks()
     vi.useFakeTimers({ shouldAdvanceTime: true })
+    listeners.clear()
 
     // Reset store state
     usePortStore.setState({
@@ -36,39 +80,21 @@ describe('usePortScanner', () => {
     vi.useRealTimers()
   })
 
-  describe('scanPorts', () => {
-    it('should scan ports and update the store on success', async () => {
-      const mockPorts = [
-        {
-          port: 3000,
-          pid: 1234,
-          process_name: 'node',
-          protocol: 'tcp',
-          local_address: '0.0.0.0',
-          state: 'LISTEN',
-        },
-        {
-          port: 8080,
-          pid: 5678,
-          process_name: 'java',
-          protocol: 'tcp',
-          local_address: '127.0.0.1',
-          state: 'LISTEN',
-        },
-      ]
+  async function renderScanner() {
+    const rendered = renderHook(() => usePortScanner())
+    await act(async () => {
+      await vi.advanceTimersByTimeAsync(100)
+    })
+    return rendered
+  }
 
-      mockInvoke.mockResolvedValue({
-        success: true,
-        data: mockPorts,
-        error: null,
+  describe('snapshot', () => {
+    it('should load the watcher snapshot into the store', async () => {
+      respondWith({
+        get_port_snapshot: { success: true, data: [nodePort, javaPort], error: null },
       })
 
-      const { result } = renderHook(() => usePortScanner())
-
-      // Advance timers to allow useEffect to run
-      await act(async () => {
-        await vi.advanceTimersByTimeAsync(100)
-      })
+      const { result } = await renderScanner()
 
       await waitFor(
         () => {
@@ -82,6 +108,8 @@ describe('usePortScanner', () => {
         pid: 1234,
         processName: 'node',
         protocol: 'tcp',
+        localAddress: '0.0.0.0',
+        netNamespace: null,
       })
 
       expect(result.current.ports[1]).toEqual({
@@ -89,23 +117,20 @@ describe('usePortScanner', () => {
         pid: 5678,
         processName: 'java',
         protocol: 'tcp',
+        localAddress: '127.0.0.1',
+        netNamespace: null,
       })
 
-      expect(mockInvoke).toHaveBeenCalledWith('scan_ports')
+      expect(mockInvoke).toHaveBeenCalledWith('get_port_snapshot')
+      expect(mockInvoke).not.toHaveBeenCalledWith('scan_ports')
     })
 
-    it('should set error on scan failure', async () => {
-      mockInvoke.mockResolvedValue({
-        success: false,
-        data: null,
-        error: 'Permission denied',
+    it('should set error on failure', async () => {
+      respondWith({
+        get_port_snapshot: { success: false, data: null, error: 'Permission denied' },
       })
 
-      const { result } = renderHook(() => usePortScanner())
-
-      await act(async () => {
-        await vi.advanceTimersByTimeAsync(100)
-      })
+      const { result } = await renderScanner()
 
       await waitFor(
         () => {
@@ -116,13 +141,9 @@ describe('usePortScanner', () => {
     })
 
     it('should handle exceptions gracefully', async () => {
-      mockInvoke.mockRejectedValue(new Error('Network error'))
+      respondWith({ get_port_snapshot: new Error('Network error') })
 
-      const { result } = renderHook(() => usePortScanner())
-
-      await act(async () => {
-        await vi.advanceTimersByTimeAsync(100)
-      })
+      const { result } = await renderScanner()
 
       await waitFor(
         () => {
@@ -133,30 +154,14 @@ describe('usePortScanner', () => {
     })
   })
 
-  describe('killProcess', () => {
-    it('should kill a process and rescan ports on success', async () => {
-      const mockPorts = [
-        {
-          port: 3000,
-          pid: 1234,
-          process_name: 'node',
-          protocol: 'tcp',
-          local_address: '0.0.0.0',
-          state: 'LISTEN',
-        },
-      ]
-
-      mockInvoke
-        .mockResolvedValueOnce({ success: true, data: mockPorts, error: null }) // Initial scan
-        .mockResolvedValueOnce({ success: true, data: null, error: null }) // Kill process
-        .mockResolvedValueOnce({ success: true, data: [], error: null }) // Rescan after kill
-
-      const { result } = renderHook(() => usePortScanner())
-
-      await act(async () => {
-        await vi.advanceTimersByTimeAsync(100)
+  describe('port events', () => {
+    it('should apply opened, closed and changed ports', async () => {
+      respondWith({
+        get_port_snapshot: { success: true, data: [nodePort], error: null },
       })
 
+      const { result } = await renderScanner()
+
       await waitFor(
         () => {
           expect(result.current.ports.length).toBe(1)
@@ -164,51 +169,54 @@ describe('usePortScanner', () => {
         { timeout: 1000 }
       )
 
-      let killResult: boolean = false
-      await act(async () => {
-        killResult = await result.current.killProcess(1234, {
-          port: 3000,
-          processName: 'node',
+      act(() => {
+        listeners.get('port-opened')!({ payload: [javaPort] })
+      })
+      expect(result.current.ports.map((p) => p.port)).toEqual([3000, 8080])
+
+      act(() => {
+        listeners.get('port-changed')!({
+          payload: [{ previous: nodePort, current: { ...nodePort, pid: 4321 } }],
         })
       })
+      expect(result.current.ports[0].pid).toBe(4321)
+      expect(result.current.ports.length).toBe(2)
 
-      expect(killResult).toBe(true)
-      expect(mockInvoke).toHaveBeenCalledWith('kill_process', { pid: 1234 })
+      act(() => {
+        listeners.get('port-closed')!({ payload: [javaPort] })
+      })
+      expect(result.current.ports.map((p) => p.port)).toEqual([3000])
+    })
+
+    it('should keep one row per shared listener so a close removes it', async () => {
+      const worker = { ...nodePort, pid: 1240 }
+      respondWith({
+        get_port_snapshot: { success: true, data: [worker, nodePort], error: null },
+      })
+
+      const { result } = await renderScanner()
 
       await waitFor(
         () => {
-          expect(result.current.ports.length).toBe(0)
+          expect(result.current.ports.length).toBe(1)
         },
         { timeout: 1000 }
       )
-    })
-
-    it('should return false and set error on kill failure', async () => {
-      const mockPorts = [
-        {
-          port: 3000,
-          pid: 1234,
-          process_name: 'node',
-          protocol: 'tcp',
-          local_address: '0.0.0.0',
-          state: 'LISTEN',
-        },
-      ]
-
-      mockInvoke
-        .mockResolvedValueOnce({ success: true, data: mockPorts, error: null })
-        .mockResolvedValueOnce({
-          success: false,
-          data: null,
-          error: 'Access denied',
-        })
+      expect(result.current.ports[0].pid).toBe(1234)
 
-      const { result } = renderHook(() => usePortScanner())
+      act(() => {
+        listeners.get('port-closed')!({ payload: [nodePort] })
+      })
+      expect(result.current.ports.length).toBe(0)
+    })
 
-      await act(async () => {
-        await vi.advanceTimersByTimeAsync(100)
+    it('should not duplicate a listener reported as opened again', async () => {
+      respondWith({
+        get_port_snapshot: { success: true, data: [nodePort], error: null },
       })
 
+      const { result } = await renderScanner()
+
       await waitFor(
         () => {
           expect(result.current.ports.length).toBe(1)
@@ -216,116 +224,134 @@ describe('usePortScanner', () => {
         { timeout: 1000 }
       )
 
-      let killResult: boolean = true
-      await act(async () => {
-        killResult = await result.current.killProcess(1234)
+      act(() => {
+        listeners.get('port-opened')!({ payload: [nodePort] })
       })
-
-      expect(killResult).toBe(false)
-      expect(result.current.error).toBe('Access denied')
+      expect(result.current.ports.length).toBe(1)
     })
-  })
 
-  describe('polling', () => {
-    it('should poll at the configured interval', async () => {
-      const mockPorts = [
-        {
-          port: 3000,
-          pid: 1234,
-          process_name: 'node',
-          protocol: 'tcp',
-          local_address: '0.0.0.0',
-          state: 'LISTEN',
-        },
-      ]
-
-      mockInvoke.mockResolvedValue({
-        success: true,
-        data: mockPorts,
-        error: null,
+    it('should stop listening on unmount', async () => {
+      respondWith({
+        get_port_snapshot: { success: true, data: [], error: null },
       })
 
-      renderHook(() => usePortScanner())
-
-      // Initial scan
-      await act(async () => {
-        await vi.advanceTimersByTimeAsync(100)
-      })
+      const { unmount } = await renderScanner()
 
       await waitFor(
         () => {
-          expect(mockInvoke).toHaveBeenCalledTimes(1)
+          expect(listeners.size).toBe(3)
         },
         { timeout: 1000 }
       )
 
-      // Advance timers by refresh interval (2000ms)
-      await act(async () => {
-        await vi.advanceTimersByTimeAsync(2000)
+      unmount()
+      expect(mockUnlisten).toHaveBeenCalledTimes(3)
+    })
+  })
+
+  describe('watch interval', () => {
+    it('should pass the refresh interval to the watcher', async () => {
+      respondWith({
+        get_port_snapshot: { success: true, data: [], error: null },
       })
 
+      await renderScanner()
+
       await waitFor(
         () => {
-          expect(mockInvoke).toHaveBeenCalledTimes(2)
+          expect(mockInvoke).toHaveBeenCalledWith('set_watch_interval', { intervalMs: 2000 })
         },
         { timeout: 1000 }
       )
 
-      // Advance again
-      await act(async () => {
-        await vi.advanceTimersByTimeAsync(2000)
+      act(() => {
+        usePortStore.getState().setRefreshInterval(5000)
       })
 
       await waitFor(
         () => {
-          expect(mockInvoke).toHaveBeenCalledTimes(3)
+          expect(mockInvoke).toHaveBeenCalledWith('set_watch_interval', { intervalMs: 5000 })
         },
         { timeout: 1000 }
       )
     })
 
-    it('should stop polling when stopPolling is called', async () => {
-      const mockPorts = [
-        {
-          port: 3000,
-          pid: 1234,
-          process_name: 'node',
-          protocol: 'tcp',
-          local_address: '0.0.0.0',
-          state: 'LISTEN',
-        },
-      ]
+    it('should not poll for ports', async () => {
+      respondWith({
+        get_port_snapshot: { success: true, data: [nodePort], error: null },
+      })
 
-      mockInvoke.mockResolvedValue({
-        success: true,
-        data: mockPorts,
-        error: null,
+      await renderScanner()
+
+      await act(async () => {
+        await vi.advanceTimersByTimeAsync(10000)
       })
 
-      const { result } = renderHook(() => usePortScanner())
+      const scans = mockInvoke.mock.calls.filter(
+        ([command]) => command === 'get_port_snapshot' || command === 'scan_ports'
+      )
+      expect(scans.length).toBe(1)
+    })
+  })
 
+  describe('killProcess', () => {
+    it('should kill a process and rescan ports on success', async () => {
+      respondWith({
+        get_port_snapshot: { success: true, data: [nodePort], error: null },
+        kill_process: { success: true, data: [1234], error: null },
+        scan_ports: { success: true, data: [], error: null },
+      })
+
+      const { result } = await renderScanner()
+
+      await waitFor(
+        () => {
+          expect(result.current.ports.length).toBe(1)
+        },
+        { timeout: 1000 }
+      )
+
+      let killResult: boolean = false
       await act(async () => {
-        await vi.advanceTimersByTimeAsync(100)
+        killResult = await result.current.killProcess(1234, {
+          port: 3000,
+          processName: 'node',
+        })
       })
 
+      expect(killResult).toBe(true)
+      expect(mockInvoke).toHaveBeenCalledWith('kill_process', { pid: 1234 })
+
       await waitFor(
         () => {
-          expect(mockInvoke).toHaveBeenCalledTimes(1)
+          expect(result.current.ports.length).toBe(0)
         },
         { timeout: 1000 }
       )
+    })
 
-      act(() => {
-        result.current.stopPolling()
+    it('should return false and set error on kill failure', async () => {
+      respondWith({
+        get_port_snapshot: { success: true, data: [nodePort], error: null },
+        kill_process: { success: false, data: null, error: 'Access denied' },
       })
 
-      // Advance timers - should not trigger more scans
+      const { result } = await renderScanner()
+
+      await waitFor(
+        () => {
+          expect(result.current.ports.length).toBe(1)
+        },
+        { timeout: 1000 }
+      )
+
+      let killResult: boolean = true
       await act(async () => {
-        await vi.advanceTimersByTimeAsync(5000)
+        killResult = await result.current.killProcess(1234)
       })
 
-      // Should still be 1 (no additional calls after stopping)
-      expect(mockInvoke).toHaveBeenCalledTimes(1)
+      expect(killResult).toBe(false)
+      expect(result.current.error).toBe('Access denied')
     })
   })
 })
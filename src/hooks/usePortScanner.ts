@@ -1,28 +1,61 @@
-import { useEffect, useCallback, useRef, useState } from 'react'
-import { usePortStore, type PortInfo } from '../stores/portStore'
+import { useEffect, useCallback, useState } from 'react'
+import { usePortStore, collapseListeners, type PortInfo } from '../stores/portStore'
 import { addKillHistory } from '../lib/database'
 
-interface CommandResponse<T> {
+// Stable error codes from the Rust ScanErrorKind enum
+type ScanErrorKind =
+  | 'permission_denied'
+  | 'process_not_found'
+  | 'process_changed'
+  | 'protected'
+  | 'tool_missing'
+  | 'command_failed'
+  | 'timeout'
+  | 'parse_failure'
+  | 'unsupported_os'
+  | 'io'
+  | 'other'
+
+interface ScanErrorDetails {
+  kind: ScanErrorKind
+  message: string
+  errno: number | null
+  exit_status: number | null
+  pid: number | null
+  port: number | null
+}
+
+export interface CommandResponse<T> {
   success: boolean
   data: T | null
   error: string | null
+  error_details?: ScanErrorDetails | null
 }
 
-interface BackendPortInfo {
+// Port as serialized by the Rust backend, in scan results and port-* events
+export interface BackendPortInfo {
   port: number
   pid: number
   process_name: string
   protocol: string
   local_address: string
   state: string
+  net_namespace?: { inode: number } | null
 }
 
-function mapBackendPort(port: BackendPortInfo): PortInfo {
+interface BackendPortChange {
+  previous: BackendPortInfo
+  current: BackendPortInfo
+}
+
+export function mapBackendPort(port: BackendPortInfo): PortInfo {
   return {
     port: port.port,
     pid: port.pid,
     processName: port.process_name,
-    protocol: port.protocol as 'tcp' | 'udp',
+    protocol: port.protocol as PortInfo['protocol'],
+    localAddress: port.local_address,
+    netNamespace: port.net_namespace?.inode ?? null,
   }
 }
 
@@ -31,6 +64,8 @@ function isTauri(): boolean {
   return typeof window !== 'undefined' && '__TAURI_INTERNALS__' in window
 }
 
+// The backend watcher scans on its own schedule and emits what changed;
+// this hook loads its snapshot once and then applies those events
 export function usePortScanner() {
   const {
     ports,
@@ -38,11 +73,13 @@ export function usePortScanner() {
     error,
     refreshInterval,
     setPorts,
+    addPorts,
+    removePorts,
+    updatePorts,
     setLoading,
     setError,
   } = usePortStore()
 
-  const intervalRef = useRef<number | null>(null)
   const [tauriReady, setTauriReady] = useState(false)
 
   // Check if Tauri is ready
@@ -52,28 +89,34 @@ export function usePortScanner() {
     }
   }, [])
 
-  const scanPorts = useCallback(async () => {
-    if (!tauriReady) return
-
-    setLoading(true)
-    setError(null)
+  const loadPorts = useCallback(
+    async (command: 'get_port_snapshot' | 'scan_ports') => {
+      setLoading(true)
+      setError(null)
 
-    try {
-      const { invoke } = await import('@tauri-apps/api/core')
-      const response = await invoke<CommandResponse<BackendPortInfo[]>>('scan_ports')
+      try {
+        const { invoke } = await import('@tauri-apps/api/core')
+        const response = await invoke<CommandResponse<BackendPortInfo[]>>(command)
 
-      if (response.success && response.data) {
-        const mappedPorts = response.data.map(mapBackendPort)
-        setPorts(mappedPorts)
-      } else {
-        setError(response.error || 'Failed to scan ports')
+        if (response.success && response.data) {
+          setPorts(collapseListeners(response.data.map(mapBackendPort)))
+        } else {
+          setError(response.error || 'Failed to scan ports')
+        }
+      } catch (err) {
+        setError(err instanceof Error ? err.message : 'Unknown error')
+      } finally {
+        setLoading(false)
       }
-    } catch (err) {
-      setError(err instanceof Error ? err.message : 'Unknown error')
-    } finally {
-      setLoading(false)
-    }
-  }, [tauriReady, setPorts, setLoading, setError])
+    },
+    [setPorts, setLoading, setError]
+  )
+
+  // Manual refresh: scan now instead of waiting for the watcher
+  const scanPorts = useCallback(async () => {
+    if (!tauriReady) return
+    await loadPorts('scan_ports')
+  }, [tauriReady, loadPorts])
 
   const killProcess = useCallback(
     async (pid: number, portInfo?: { port: number; processName: string }): Promise<boolean> => {
@@ -92,6 +135,7 @@ export function usePortScanner() {
               console.error('Failed to log kill to history:', historyErr)
             }
           }
+          // Show the result now rather than on the watcher's next scan
           await scanPorts()
           return true
         } else {
@@ -106,40 +150,68 @@ export function usePortScanner() {
     [tauriReady, scanPorts, setError]
   )
 
-  // Start polling
-  const startPolling = useCallback(() => {
-    if (intervalRef.current) {
-      clearInterval(intervalRef.current)
-    }
-    intervalRef.current = window.setInterval(scanPorts, refreshInterval)
-  }, [scanPorts, refreshInterval])
-
-  // Stop polling
-  const stopPolling = useCallback(() => {
-    if (intervalRef.current) {
-      clearInterval(intervalRef.current)
-      intervalRef.current = null
-    }
-  }, [])
-
-  // Initial scan and start polling when Tauri is ready
+  // Subscribe to the watcher's events, then load its snapshot
   useEffect(() => {
     if (!tauriReady) return
 
-    scanPorts()
-    startPolling()
+    let cancelled = false
+    const unlisteners: Array<() => void> = []
+
+    const subscribe = async () => {
+      try {
+        const { listen } = await import('@tauri-apps/api/event')
+        const subscriptions = await Promise.all([
+          listen<BackendPortInfo[]>('port-opened', (event) =>
+            addPorts(event.payload.map(mapBackendPort))
+          ),
+          listen<BackendPortInfo[]>('port-closed', (event) =>
+            removePorts(event.payload.map(mapBackendPort))
+          ),
+          listen<BackendPortChange[]>('port-changed', (event) =>
+            updatePorts(
+              event.payload.map((change) => ({
+                previous: mapBackendPort(change.previous),
+                current: mapBackendPort(change.current),
+              }))
+            )
+          ),
+        ])
+        if (cancelled) {
+          subscriptions.forEach((unlisten) => unlisten())
+          return
+        }
+        unlisteners.push(...subscriptions)
+      } catch (err) {
+        console.error('Failed to subscribe to port events:', err)
+      }
+      if (!cancelled) {
+        await loadPorts('get_port_snapshot')
+      }
+    }
+    subscribe()
 
     return () => {
-      stopPolling()
+      cancelled = true
+      unlisteners.forEach((unlisten) => unlisten())
     }
-  }, [tauriReady, scanPorts, startPolling, stopPolling])
+  }, [tauriReady, addPorts, removePorts, updatePorts, loadPorts])
 
-  // Restart polling when interval changes
+  // The watcher owns the schedule; pass the refresh interval on to it
   useEffect(() => {
-    if (intervalRef.current && tauriReady) {
-      startPolling()
+    if (!tauriReady) return
+
+    const setWatchInterval = async () => {
+      try {
+        const { invoke } = await import('@tauri-apps/api/core')
+        await invoke<CommandResponse<number>>('set_watch_interval', {
+          intervalMs: refreshInterval,
+        })
+      } catch (err) {
+        console.error('Failed to set watch interval:', err)
+      }
     }
-  }, [refreshInterval, startPolling, tauriReady])
+    setWatchInterval()
+  }, [tauriReady, refreshInterval])
 
   return {
     ports,
@@ -147,7 +219,5 @@ export function usePortScanner() {
     error,
     scanPorts,
     killProcess,
-    startPolling,
-    stopPolling,
   }
 }
@@ -0,0 +1,178 @@
+import { useState, useEffect, useCallback } from 'react'
+import type { CommandResponse } from './usePortScanner'
+
+function isTauri(): boolean {
+  return typeof window !== 'undefined' && '__TAURI_INTERNALS__' in window
+}
+
+export interface ScanBackendInfo {
+  name: string
+  available: boolean
+  unavailable_reason: string | null
+  preferred: boolean
+}
+
+// Every field that is set must match; name and path accept * and ?
+export interface ProtectionRule {
+  name: string | null
+  path: string | null
+  uid: number | null
+}
+
+interface ScannerSettings {
+  scan_backend: string | null
+  namespace_scanning: boolean
+}
+
+interface ProtectionPolicy {
+  builtin_names: string[]
+  rules: ProtectionRule[]
+}
+
+// Scanner options and protection rules live in the Rust backend
+// (scanner.json and protection.json in the app config dir) so they apply
+// from startup; this hook only reads and sets them
+export function useScannerSettings() {
+  const [backends, setBackends] = useState<ScanBackendInfo[]>([])
+  const [namespaceScanning, setNamespaceScanning] = useState(false)
+  const [protectionRules, setProtectionRules] = useState<ProtectionRule[]>([])
+  const [error, setError] = useState<string | null>(null)
+
+  const loadBackends = useCallback(async () => {
+    if (!isTauri()) return
+
+    try {
+      const { invoke } = await import('@tauri-apps/api/core')
+      const response = await invoke<CommandResponse<ScanBackendInfo[]>>('list_scan_backends')
+      if (response.success && response.data) {
+        setBackends(response.data)
+      }
+    } catch (err) {
+      console.error('Failed to list scan backends:', err)
+    }
+  }, [])
+
+  const loadSettings = useCallback(async () => {
+    if (!isTauri()) return
+
+    try {
+      const { invoke } = await import('@tauri-apps/api/core')
+      const response = await invoke<CommandResponse<ScannerSettings>>('get_scanner_settings')
+      if (response.success && response.data) {
+        setNamespaceScanning(response.data.namespace_scanning)
+      }
+    } catch (err) {
+      console.error('Failed to load scanner settings:', err)
+    }
+  }, [])
+
+  const loadProtectionRules = useCallback(async () => {
+    if (!isTauri()) return
+
+    try {
+      const { invoke } = await import('@tauri-apps/api/core')
+      const response = await invoke<CommandResponse<ProtectionPolicy>>('get_protection_policy')
+      if (response.success && response.data) {
+        setProtectionRules(response.data.rules)
+      }
+    } catch (err) {
+      console.error('Failed to load protection rules:', err)
+    }
+  }, [])
+
+  useEffect(() => {
+    loadBackends()
+    loadSettings()
+    loadProtectionRules()
+  }, [loadBackends, loadSettings, loadProtectionRules])
+
+  // null restores the automatic order
+  const selectBackend = useCallback(
+    async (name: string | null): Promise<boolean> => {
+      if (!isTauri()) return false
+
+      try {
+        const { invoke } = await import('@tauri-apps/api/core')
+        const response = await invoke<CommandResponse<null>>('set_scan_backend', { name })
+        if (!response.success) {
+          setError(response.error || 'Failed to select scan backend')
+          return false
+        }
+        setError(null)
+        await loadBackends()
+        return true
+      } catch (err) {
+        setError(err instanceof Error ? err.message : 'Unknown error')
+        return false
+      }
+    },
+    [loadBackends]
+  )
+
+  // Linux only; the backend refuses to enable it elsewhere
+  const toggleNamespaceScanning = useCallback(async (): Promise<boolean> => {
+    if (!isTauri()) return false
+
+    const enabled = !namespaceScanning
+    try {
+      const { invoke } = await import('@tauri-apps/api/core')
+      const response = await invoke<CommandResponse<null>>('set_namespace_scanning', { enabled })
+      if (!response.success) {
+        setError(response.error || 'Failed to change namespace scanning')
+        return false
+      }
+      setError(null)
+      setNamespaceScanning(enabled)
+      return true
+    } catch (err) {
+      setError(err instanceof Error ? err.message : 'Unknown error')
+      return false
+    }
+  }, [namespaceScanning])
+
+  const saveProtectionRules = useCallback(
+    async (rules: ProtectionRule[]): Promise<boolean> => {
+      if (!isTauri()) return false
+
+      try {
+        const { invoke } = await import('@tauri-apps/api/core')
+        const response = await invoke<CommandResponse<null>>('set_protection_rules', { rules })
+        if (!response.success) {
+          setError(response.error || 'Failed to save protection rules')
+          return false
+        }
+        setError(null)
+        setProtectionRules(rules)
+        return true
+      } catch (err) {
+        setError(err instanceof Error ? err.message : 'Unknown error')
+        return false
+      }
+    },
+    []
+  )
+
+  const addProtectionRule = useCallback(
+    (rule: ProtectionRule) => saveProtectionRules([...protectionRules, rule]),
+    [protectionRules, saveProtectionRules]
+  )
+
+  const removeProtectionRule = useCallback(
+    (index: number) => saveProtectionRules(protectionRules.filter((_, i) => i !== index)),
+    [protectionRules, saveProtectionRules]
+  )
+
+  const preferredBackend = backends.find((b) => b.preferred)?.name ?? null
+
+  return {
+    backends,
+    preferredBackend,
+    selectBackend,
+    namespaceScanning,
+    toggleNamespaceScanning,
+    protectionRules,
+    addProtectionRule,
+    removeProtectionRule,
+    error,
+  }
+}
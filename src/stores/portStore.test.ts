@@ -1,5 +1,11 @@
 import { describe, it, expect, beforeEach } from 'vitest'
-import { usePortStore, type PortInfo, type Favorite } from './portStore'
+import {
+  usePortStore,
+  collapseListeners,
+  listenerKey,
+  type PortInfo,
+  type Favorite,
+} from './portStore'
 
 describe('portStore', () => {
   beforeEach(() => {
@@ -40,6 +46,52 @@ describe('portStore', () => {
     })
   })
 
+  describe('port events', () => {
+    const v4: PortInfo = {
+      port: 3000,
+      pid: 1234,
+      processName: 'node',
+      protocol: 'tcp',
+      localAddress: '0.0.0.0',
+    }
+    const v6: PortInfo = { ...v4, localAddress: '::' }
+
+    it('should tell listeners apart by address, protocol and namespace', () => {
+      expect(listenerKey(v4)).not.toBe(listenerKey(v6))
+      expect(listenerKey(v4)).not.toBe(listenerKey({ ...v4, protocol: 'udp' }))
+      expect(listenerKey(v4)).not.toBe(listenerKey({ ...v4, netNamespace: 4026532000 }))
+      expect(listenerKey(v4)).toBe(listenerKey({ ...v4, pid: 1 }))
+    })
+
+    it('should collapse shared listeners to their lowest pid', () => {
+      const worker = { ...v4, pid: 1236 }
+      const master = { ...v4, pid: 1230 }
+
+      expect(collapseListeners([worker, v6, master])).toEqual([master, v6])
+    })
+
+    it('should add opened ports, replacing the same listener', () => {
+      usePortStore.getState().setPorts([v4])
+      usePortStore.getState().addPorts([{ ...v4, pid: 99 }, v6])
+
+      expect(usePortStore.getState().ports).toEqual([{ ...v4, pid: 99 }, v6])
+    })
+
+    it('should remove every entry of a closed listener', () => {
+      usePortStore.getState().setPorts([v4, { ...v4, pid: 1235 }, v6])
+      usePortStore.getState().removePorts([v4])
+
+      expect(usePortStore.getState().ports).toEqual([v6])
+    })
+
+    it('should update changed ports in place', () => {
+      usePortStore.getState().setPorts([v4, v6])
+      usePortStore.getState().updatePorts([{ previous: v4, current: { ...v4, pid: 99 } }])
+
+      expect(usePortStore.getState().ports).toEqual([{ ...v4, pid: 99 }, v6])
+    })
+  })
+
   describe('loading state', () => {
     it('should set loading state to true', () => {
       usePortStore.getState().setLoading(true)
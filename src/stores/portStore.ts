@@ -4,10 +4,38 @@ export interface PortInfo {
   port: number
   pid: number
   processName: string
-  protocol: 'tcp' | 'udp'
+  protocol: 'tcp' | 'udp' | 'udplite' | 'raw'
+  localAddress?: string
+  // Inode of the network namespace, for listeners outside ours
+  netNamespace?: number | null
   startTime?: number
 }
 
+export interface PortChange {
+  previous: PortInfo
+  current: PortInfo
+}
+
+// Identifies a listener the way the backend watcher does, so its
+// port-opened / port-closed / port-changed events can be applied
+export function listenerKey(port: PortInfo): string {
+  return [port.protocol, port.localAddress ?? '', port.port, port.netNamespace ?? ''].join('|')
+}
+
+// One entry per listener, as in the watcher's events: SO_REUSEPORT lets
+// several processes share one, and the lowest pid stands for all of them
+export function collapseListeners(ports: PortInfo[]): PortInfo[] {
+  const kept = new Map<string, PortInfo>()
+  for (const port of ports) {
+    const key = listenerKey(port)
+    const current = kept.get(key)
+    if (!current || port.pid < current.pid) {
+      kept.set(key, port)
+    }
+  }
+  return [...kept.values()]
+}
+
 export interface Favorite {
   port: number
   label: string
@@ -21,6 +49,9 @@ interface PortStore {
   refreshInterval: number
 
   setPorts: (ports: PortInfo[]) => void
+  addPorts: (opened: PortInfo[]) => void
+  removePorts: (closed: PortInfo[]) => void
+  updatePorts: (changes: PortChange[]) => void
   setLoading: (loading: boolean) => void
   setError: (error: string | null) => void
   setRefreshInterval: (interval: number) => void
@@ -37,6 +68,44 @@ export const usePortStore = create<PortStore>((set) => ({
   refreshInterval: 2000,
 
   setPorts: (ports) => set({ ports }),
+
+  // The watcher reports one entry per listener, so an opened listener
+  // replaces whatever the store still holds for it
+  addPorts: (opened) =>
+    set((state) => {
+      const keys = new Set(opened.map(listenerKey))
+      return { ports: [...state.ports.filter((p) => !keys.has(listenerKey(p))), ...opened] }
+    }),
+
+  removePorts: (closed) =>
+    set((state) => {
+      const keys = new Set(closed.map(listenerKey))
+      return { ports: state.ports.filter((p) => !keys.has(listenerKey(p))) }
+    }),
+
+  // A changed listener keeps its place in the list
+  updatePorts: (changes) =>
+    set((state) => {
+      const pending = new Map(
+        changes.map((c): [string, PortInfo] => [listenerKey(c.previous), c.current])
+      )
+      const placed = new Set<string>()
+      const ports: PortInfo[] = []
+      for (const port of state.ports) {
+        const key = listenerKey(port)
+        const current = pending.get(key)
+        if (!current) {
+          ports.push(port)
+        } else if (!placed.has(key)) {
+          ports.push(current)
+          placed.add(key)
+        }
+      }
+      for (const [key, current] of pending) {
+        if (!placed.has(key)) ports.push(current)
+      }
+      return { ports }
+    }),
   setLoading: (isLoading) => set({ isLoading }),
   setError: (error) => set({ error }),
   setRefreshInterval: (refreshInterval) => set({ refreshInterval }),